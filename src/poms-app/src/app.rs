@@ -1,4 +1,5 @@
 mod anim;
+pub(crate) mod cli;
 #[macro_use]
 mod data;
 mod input;
@@ -8,21 +9,27 @@ mod ui;
 use poms_common::limits::{
     MAX_DISTANCE_FIELD_RESOLUTION, MIN_DISTANCE_FIELD_RESOLUTION, MIN_PROBE_RADIUS,
 };
+use poms_common::models::atom::Atom;
 use poms_common::{models::atom::calculate_center, resources::CommonResources};
+use poms_compute::headless::DistanceFieldReadback;
 use poms_compute::{ComputeJobs, ComputeParameters};
 use poms_render::{PostprocessSettings, RenderJobs, RenderParameters};
 
 use super::gpu_context::GpuContext;
 use anim::AnimationController;
+use cli::LaunchOptions;
+use data::file_loader::{load_paths, DroppedFile, SurfaceDistanceField, SurfaceExportRequest};
 use data::molecule_storage::MoleculeStorage;
 use input::{camera_controller::CameraController, mouse_input::MouseInput};
+use poms_io::molecule_parser::load_options::LoadOptions;
 use ui::{events::UserEvent, state::UIState, UserInterface};
 
-/// Settings for the application, controlling resolution and probe radius.
+/// Settings for the application, controlling resolution, probe radius and which atoms of opened files are loaded.
 struct AppSettings {
     init_resolution: u32,
     target_resolution: u32,
     probe_radius: f32,
+    load_options: LoadOptions,
 }
 
 impl Default for AppSettings {
@@ -31,10 +38,18 @@ impl Default for AppSettings {
             init_resolution: MIN_DISTANCE_FIELD_RESOLUTION,
             target_resolution: MAX_DISTANCE_FIELD_RESOLUTION,
             probe_radius: MIN_PROBE_RADIUS,
+            load_options: LoadOptions::default(),
         }
     }
 }
 
+/// Surface export waiting for the distance field to be read back from the GPU.
+struct PendingSurfaceExport {
+    readback: DistanceFieldReadback,
+    atoms: Vec<Atom>,
+    probe_radius: f32,
+}
+
 /// Represents the main application, managing rendering, compute jobs, and user interactions.
 pub(crate) struct App {
     context: GpuContext,
@@ -50,23 +65,57 @@ pub(crate) struct App {
     mouse: MouseInput,
     camera: CameraController,
     animation: AnimationController,
+    /// Files dropped onto the window since the last frame, which are loaded together.
+    dropped_files: Vec<DroppedFile>,
+    pending_surface_export: Option<PendingSurfaceExport>,
 }
 
 impl App {
-    /// Initializes the application with default settings and sets up rendering and compute jobs.
-    pub(crate) fn new(context: GpuContext) -> Self {
-        let settings = AppSettings::default();
-
-        let initial_molecule = include_molecule!("../../../data/pdb/1cqw.pdb");
-        let molecule_storage = MoleculeStorage::new(initial_molecule, settings.probe_radius);
+    /// Initializes the application with the settings it was launched with and sets up rendering and compute jobs.
+    pub(crate) fn new(context: GpuContext, options: LaunchOptions) -> Self {
+        let settings = AppSettings {
+            target_resolution: options.target_resolution,
+            probe_radius: options.probe_radius,
+            ..Default::default()
+        };
+
+        // Files given on launch replace the bundled molecule, which is only kept if they fail to load.
+        let bundled_molecules = || include_molecule!("../../../data/pdb/1cqw.pdb");
+        let (initial_molecules, load_error) = match options.files.is_empty() {
+            true => (bundled_molecules(), None),
+            false => match load_paths(&options.files, settings.load_options) {
+                Ok(molecules) => (molecules, None),
+                Err(error) => (bundled_molecules(), Some(error)),
+            },
+        };
+        let mut molecule_storage = MoleculeStorage::new(initial_molecules, settings.probe_radius);
+        molecule_storage.set_color_scheme(options.color_scheme);
         let resources = CommonResources::new(&context.device);
 
-        let render_spacefill = true;
-        let render_molecular_surface = false;
+        let render_spacefill = options.representation.renders_spacefill();
+        let render_molecular_surface = options.representation.renders_molecular_surface();
         let postprocess_settings = PostprocessSettings::default();
         let animation = AnimationController::default();
 
-        App {
+        // This ensures the initial molecule is added to the UI state, and the structures are downloaded from the given source
+        // (or opened from the cache in offline mode).
+        let mut initial_events = vec![
+            UserEvent::ChangeActiveMolecule { index: 0 },
+            UserEvent::ChangeStructureSource {
+                source: options.structure_source.clone(),
+            },
+            UserEvent::ToggleOfflineMode {
+                is_offline: options.is_offline,
+            },
+        ];
+        initial_events.extend(
+            options
+                .downloads
+                .into_iter()
+                .map(|assembly| UserEvent::InitDownloadMolecule { assembly }),
+        );
+
+        let mut app = App {
             compute: ComputeJobs::new(
                 &context.device,
                 ComputeParameters {
@@ -86,7 +135,7 @@ impl App {
                     render_spacefill,
                     render_molecular_surface,
                     postprocess_settings,
-                    clear_color: wgpu::Color::BLACK,
+                    clear_color: clear_color(options.theme),
                 },
             ),
             resources,
@@ -96,21 +145,35 @@ impl App {
                 UIState {
                     target_resolution: settings.target_resolution,
                     probe_radius: settings.probe_radius,
+                    load_options: settings.load_options,
                     render_spacefill,
                     render_molecular_surface,
+                    color_scheme: options.color_scheme,
+                    structure_source: options.structure_source,
+                    is_offline: options.is_offline,
                     is_animation_active: animation.is_active,
                     animation_speed: animation.speed,
-                    // This ensures the initial molecule is added to the UI state.
-                    events: vec![UserEvent::ChangeActiveMolecule { index: 0 }],
+                    events: initial_events,
                     ..Default::default()
                 },
             ),
             mouse: MouseInput::default(),
             camera: CameraController::from_config(&context.config),
             animation,
+            dropped_files: Vec::new(),
+            pending_surface_export: None,
             context,
             settings,
+        };
+
+        app.ui.set_theme(options.theme);
+        app.ui.file_loader.set_cache_size(options.cache_size_mib);
+        if let Some(error) = load_error {
+            log::error!("Opening the files failed: {}", error);
+            app.ui
+                .open_error_message(format!("Opening the files failed: {}", error));
         }
+        app
     }
 
     /// Handles the rendering of each frame, processing user interactions,
@@ -120,6 +183,8 @@ impl App {
 
         let user_events = self.ui.process_frame();
         self.handle_user_events(user_events);
+        self.load_dropped_files();
+        self.poll_surface_export();
 
         let mut encoder = self.context.get_command_encoder();
 
@@ -142,8 +207,19 @@ impl App {
 
         if self.animation.advance_tick() {
             // Advance to the next frame if the animation is active and due.
-            self.molecule_storage.increment_active();
-            self.on_active_molecule_changed();
+            match self
+                .molecule_storage
+                .advance_animation(self.settings.probe_radius)
+            {
+                Ok(()) => self.on_active_molecule_changed(),
+                Err(error) => {
+                    // Stop the animation, so the error is not reported again with every frame.
+                    self.animation.is_active = false;
+                    self.ui.stop_animation();
+                    self.ui
+                        .open_error_message(format!("Reading the trajectory failed: {}", error));
+                }
+            }
         }
     }
 
@@ -162,6 +238,22 @@ impl App {
         self.ui.handle_window_event(event) || self.mouse.handle_window_event(event)
     }
 
+    /// Queues a file dropped onto the window. Dropping multiple files produces one event per file,
+    /// so they are only loaded on the next frame, together as frames of a single molecule.
+    pub(crate) fn drop_file(&mut self, file: DroppedFile) {
+        self.dropped_files.push(file);
+    }
+
+    fn load_dropped_files(&mut self) {
+        if self.dropped_files.is_empty() {
+            return;
+        }
+        let files = std::mem::take(&mut self.dropped_files);
+        self.ui
+            .file_loader
+            .load_dropped_files(files, self.settings.load_options);
+    }
+
     /// Handles device events (e.g., mouse motion) that are not tied to a specific window.
     pub(crate) fn handle_device_event(&mut self, event: &winit::event::DeviceEvent) {
         self.mouse.handle_device_event(event);
@@ -183,6 +275,7 @@ impl App {
 
         self.compute.update_buffers(&self.context.queue);
         self.ui.update_compute_progress(self.compute.progress());
+        self.update_unit_cell_edges();
 
         // Update the molecular surface texture if a new one is computed.
         if let Some((texture, grid)) = self.compute.last_computed_distance_field() {
@@ -191,6 +284,25 @@ impl App {
         }
     }
 
+    /// Projects the edges of the unit cell of the active molecule for the UI to draw them over the scene.
+    /// Edges with an end behind the camera are left out.
+    fn update_unit_cell_edges(&mut self) {
+        let edges = match &self.molecule_storage.get_active().crystal {
+            Some(crystal) => crystal
+                .unit_cell
+                .edges()
+                .into_iter()
+                .filter_map(|[start, end]| {
+                    let start = self.camera.project_to_screen(start)?;
+                    let end = self.camera.project_to_screen(end)?;
+                    Some([start.into(), end.into()])
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        self.ui.update_unit_cell_edges(edges);
+    }
+
     /// Processes events generated by the UI, updating the application state accordingly.
     fn handle_user_events(&mut self, ui_events: Vec<UserEvent>) {
         for event in ui_events {
@@ -198,6 +310,23 @@ impl App {
                 UserEvent::ChangeRenderMolecularSurface { is_enabled } => {
                     self.renderer.toggle_molecular_surface_pass(is_enabled);
                 }
+                UserEvent::ChangeColorScheme { color_scheme } => {
+                    // Only colors change, so there is no need to restart the computation of the surface.
+                    self.molecule_storage.set_color_scheme(color_scheme);
+                    self.resources.atoms_resource.update_colors(
+                        &self.context.queue,
+                        &self.molecule_storage.get_active().atoms,
+                    );
+                }
+                UserEvent::ChangeSelection { selection } => {
+                    match self
+                        .molecule_storage
+                        .set_selection(selection, self.settings.probe_radius)
+                    {
+                        Ok(()) => self.on_active_molecule_changed(),
+                        Err(error) => self.ui.open_error_message(error.to_string()),
+                    }
+                }
                 UserEvent::ChangeRenderSpacefill { is_enabled } => {
                     self.renderer.toggle_spacefill_pass(is_enabled);
                 }
@@ -209,10 +338,7 @@ impl App {
                     );
                 }
                 UserEvent::ToggleTheme { theme } => {
-                    self.renderer.update_clear_color(match theme {
-                        theme::ColorTheme::Dark => wgpu::Color::BLACK,
-                        theme::ColorTheme::Light => wgpu::Color::WHITE,
-                    });
+                    self.renderer.update_clear_color(clear_color(theme));
                 }
                 UserEvent::MoleculesParsed { molecules } => {
                     self.molecule_storage
@@ -233,6 +359,49 @@ impl App {
                     self.update_atoms_resource();
                     self.reset_compute_jobs();
                 }
+                UserEvent::ChangeTrajectoryFrame { frame_index } => {
+                    match self
+                        .molecule_storage
+                        .set_active_frame(frame_index, self.settings.probe_radius)
+                    {
+                        Ok(()) => self.on_active_molecule_changed(),
+                        Err(error) => self.ui.open_error_message(format!(
+                            "Reading the trajectory failed: {}",
+                            error
+                        )),
+                    }
+                }
+                UserEvent::ChangeAssembly { assembly_index } => {
+                    match self
+                        .molecule_storage
+                        .set_active_assembly(assembly_index, self.settings.probe_radius)
+                    {
+                        Ok(()) => self.on_active_molecule_changed(),
+                        Err(error) => self.ui.open_error_message(error.to_string()),
+                    }
+                }
+                UserEvent::ChangeCrystalPacking { crystal_packing } => {
+                    match self
+                        .molecule_storage
+                        .set_crystal_packing(crystal_packing, self.settings.probe_radius)
+                    {
+                        Ok(()) => self.on_active_molecule_changed(),
+                        Err(error) => {
+                            self.ui
+                                .set_crystal_packing(self.molecule_storage.crystal_packing());
+                            self.ui.open_error_message(error.to_string());
+                        }
+                    }
+                }
+                UserEvent::ChangeRadiusSet { radius_set } => {
+                    self.molecule_storage
+                        .set_radius_set(radius_set, self.settings.probe_radius);
+                    self.update_atoms_resource();
+                    self.reset_compute_jobs();
+                }
+                UserEvent::ChangeLoadOptions { load_options } => {
+                    self.settings.load_options = load_options;
+                }
                 UserEvent::DeleteActiveMolecule => {
                     self.molecule_storage.delete_active();
                     self.on_active_molecule_changed();
@@ -244,18 +413,84 @@ impl App {
                     self.animation.speed = speed;
                 }
                 UserEvent::InitDownloadMolecule { assembly } => {
-                    self.ui.file_loader.download_file(assembly);
+                    self.ui
+                        .file_loader
+                        .download_file(assembly, self.settings.load_options);
+                }
+                UserEvent::ChangeStructureSource { source } => {
+                    self.ui.file_loader.set_structure_source(&source);
+                }
+                UserEvent::ToggleOfflineMode { is_offline } => {
+                    self.ui.file_loader.set_offline(is_offline);
                 }
                 UserEvent::InitMoleculeSearch { query } => {
                     self.ui.file_loader.search_pdb_files(query);
                 }
                 UserEvent::InitOpenFileDialog => {
-                    self.ui.file_loader.pick_files();
+                    self.ui.file_loader.pick_files(self.settings.load_options);
                 }
+                UserEvent::InitExportSurfaceDialog => self.export_surface(),
             }
         }
     }
 
+    /// Exports the surface from the distance field that is rendered, once it is read back from the GPU.
+    /// If the computation is not finished yet, the distance field is computed on the CPU instead, which is only
+    /// done on native platforms, as it would block the page on the web.
+    fn export_surface(&mut self) {
+        let atoms = self.molecule_storage.get_active().atoms.data.clone();
+        let probe_radius = self.settings.probe_radius;
+
+        if self.compute.is_finished() {
+            let (texture, grid) = self.renderer.distance_field();
+            self.pending_surface_export = Some(PendingSurfaceExport {
+                readback: DistanceFieldReadback::start(
+                    &self.context.device,
+                    &self.context.queue,
+                    texture,
+                    grid,
+                ),
+                atoms,
+                probe_radius,
+            });
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.ui.file_loader.export_surface(SurfaceExportRequest {
+            atoms,
+            probe_radius,
+            distance_field: SurfaceDistanceField::Compute {
+                resolution: self.settings.target_resolution,
+            },
+        });
+        #[cfg(target_arch = "wasm32")]
+        self.ui.open_error_message(
+            "The molecular surface has to be computed before it can be exported".to_string(),
+        );
+    }
+
+    /// Opens the export dialog once the distance field of a pending surface export is read back.
+    fn poll_surface_export(&mut self) {
+        let Some(pending) = &mut self.pending_surface_export else {
+            return;
+        };
+        self.context.device.poll(wgpu::Maintain::Poll);
+        let Some(result) = pending.readback.try_finish() else {
+            return;
+        };
+
+        let pending = self.pending_surface_export.take().unwrap();
+        match result {
+            Ok(distance_field) => self.ui.file_loader.export_surface(SurfaceExportRequest {
+                atoms: pending.atoms,
+                probe_radius: pending.probe_radius,
+                distance_field: SurfaceDistanceField::ReadBack(distance_field),
+            }),
+            Err(error) => self.ui.open_error_message(error.to_string()),
+        }
+    }
+
     /// Handles changes necessary after different molecule was chosen to be displayed,
     /// updating the GPU resources, UI state, and setting the camera's focus to the new molecule.
     fn on_active_molecule_changed(&mut self) {
@@ -297,3 +532,11 @@ impl App {
             .update(&self.context.queue, &active_molecule.atoms);
     }
 }
+
+/// Background color of the scene matching the theme of the UI.
+fn clear_color(theme: theme::ColorTheme) -> wgpu::Color {
+    match theme {
+        theme::ColorTheme::Dark => wgpu::Color::BLACK,
+        theme::ColorTheme::Light => wgpu::Color::WHITE,
+    }
+}
@@ -3,7 +3,8 @@ use poms_common::limits::{MAX_ANIMATION_SPEED, MIN_ANIMATION_SPEED};
 /// Controls the animation speed and state.
 ///
 /// The user may select to upload multiple files at once, in which case the application will
-/// periodically switch between rendering them (if the animation is active).
+/// periodically switch between rendering them (if the animation is active). Molecules opened
+/// with a trajectory step through the trajectory frames instead.
 pub(crate) struct AnimationController {
     pub(crate) is_active: bool,
     /// The speed of the animation. The higher the value faster the animation.
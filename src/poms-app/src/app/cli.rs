@@ -0,0 +1,201 @@
+//! Command-line options of the viewer, so it can be launched from scripts and file managers
+//! with files to open and the initial settings, e.g. `poms-app 1cqw --representation surface --theme light`.
+
+use std::path::PathBuf;
+
+use poms_common::limits::{
+    MAX_DISTANCE_FIELD_RESOLUTION, MAX_PROBE_RADIUS, MIN_DISTANCE_FIELD_RESOLUTION,
+    MIN_PROBE_RADIUS,
+};
+use poms_common::options::{parse_in_range, Representation};
+
+use super::data::color_scheme::ColorScheme;
+use super::data::pdb_apis::{cache::StructureCache, provider::StructureSource};
+use super::data::Assembly;
+use super::theme::ColorTheme;
+
+pub(crate) const USAGE: &str = "Usage: poms-app [OPTIONS] [FILE | PDB_ID]...
+
+Opens the given molecule and trajectory files, or downloads structures by their PDB ID (e.g. 1cqw or 1cqw-2
+for the second assembly). The bundled molecule is opened if neither is given.
+
+Options:
+  --probe-radius <RADIUS>        Probe radius of the molecular surface in Å [default: 1.4]
+  --resolution <RESOLUTION>      Target resolution of the molecular surface [default: 256]
+  --representation <KIND>        spacefill, surface or both [default: spacefill]
+  --color-scheme <SCHEME>        element, chain, residue-type, b-factor, plddt, hydrophobicity,
+                                 secondary-structure, charge or uniform [default: element]
+  --theme <THEME>                dark or light [default: dark]
+  --window-size <WIDTHxHEIGHT>   Initial size of the window in logical pixels, e.g. 1280x720
+  --provider <PROVIDER>          Site structures are searched for and downloaded from: rcsb, pdbe, pdbj,
+                                 alphafold, modelarchive, or the URL of a mirror of the PDB archive [default: rcsb]
+  --search-url <URL>             RCSB Search API the mirror is searched with, searching is disabled without it
+  --offline                      Open structures only from the cache of downloaded ones, searches list cached structures
+  --cache-size <MIB>             Size limit of the cache of downloaded structures in MiB [default: 512]
+  -h, --help                     Print this help";
+
+/// Options the viewer is launched with. Without any arguments, they match the defaults of the viewer.
+#[derive(Debug, Clone)]
+pub(crate) struct LaunchOptions {
+    /// Files opened instead of the bundled molecule.
+    pub(crate) files: Vec<PathBuf>,
+    /// Structures downloaded from the PDB once the viewer starts.
+    pub(crate) downloads: Vec<Assembly>,
+    pub(crate) probe_radius: f32,
+    pub(crate) target_resolution: u32,
+    pub(crate) representation: Representation,
+    pub(crate) color_scheme: ColorScheme,
+    pub(crate) theme: ColorTheme,
+    /// Initial size of the window in logical pixels, `None` leaves it to the platform.
+    pub(crate) window_size: Option<(u32, u32)>,
+    /// Site structures are searched for and downloaded from.
+    pub(crate) structure_source: StructureSource,
+    /// Whether structures are only opened from the cache of downloaded ones.
+    pub(crate) is_offline: bool,
+    /// Size limit of the cache of downloaded structures in MiB.
+    pub(crate) cache_size_mib: u64,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            downloads: Vec::new(),
+            probe_radius: MIN_PROBE_RADIUS,
+            target_resolution: MAX_DISTANCE_FIELD_RESOLUTION,
+            representation: Representation::Spacefill,
+            color_scheme: ColorScheme::default(),
+            theme: ColorTheme::Dark,
+            window_size: None,
+            structure_source: StructureSource::default(),
+            is_offline: false,
+            cache_size_mib: StructureCache::DEFAULT_MAX_SIZE_MIB,
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// Parses the arguments of the process. Prints the usage and exits if help is requested or the arguments are invalid.
+    pub(crate) fn from_env() -> Self {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+
+        Self::parse(args).unwrap_or_else(|error| {
+            eprintln!("Error: {}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        })
+    }
+
+    /// Parses the arguments, without the name of the program. Arguments that are not options are opened as files
+    /// if they exist, or downloaded if they are PDB IDs.
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut search_url = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                let path = PathBuf::from(&arg);
+                match Assembly::from_pdb_id(&arg) {
+                    _ if path.exists() => options.files.push(path),
+                    Some(assembly) => options.downloads.push(assembly),
+                    None => anyhow::bail!("`{}` is neither an existing file nor a PDB ID", arg),
+                }
+                continue;
+            }
+            if arg == "--offline" {
+                options.is_offline = true;
+                continue;
+            }
+
+            // Values may be given either as a separate argument or after an equals sign, e.g. `--theme=light`.
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("{} requires a value", arg))?;
+                    (arg, value)
+                }
+            };
+            match name.as_str() {
+                "--probe-radius" => {
+                    options.probe_radius =
+                        parse_in_range(&name, &value, MIN_PROBE_RADIUS..=MAX_PROBE_RADIUS)?;
+                }
+                "--resolution" => {
+                    options.target_resolution = parse_in_range(
+                        &name,
+                        &value,
+                        MIN_DISTANCE_FIELD_RESOLUTION..=MAX_DISTANCE_FIELD_RESOLUTION,
+                    )?;
+                }
+                "--representation" => {
+                    options.representation = Representation::parse(&value)?;
+                }
+                "--color-scheme" => {
+                    // Schemes are named like in the UI, in lowercase with dashes instead of spaces.
+                    options.color_scheme = ColorScheme::ALL
+                        .into_iter()
+                        .find(|scheme| scheme.name().to_lowercase().replace(' ', "-") == value)
+                        .ok_or_else(|| anyhow::anyhow!("unknown color scheme `{}`", value))?;
+                }
+                "--theme" => {
+                    options.theme = match value.as_str() {
+                        "dark" => ColorTheme::Dark,
+                        "light" => ColorTheme::Light,
+                        _ => anyhow::bail!("unknown theme `{}`", value),
+                    };
+                }
+                "--window-size" => {
+                    let size = value
+                        .split_once('x')
+                        .and_then(|(width, height)| {
+                            Some((width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|&(width, height)| width > 0 && height > 0);
+                    options.window_size = Some(size.ok_or_else(|| {
+                        anyhow::anyhow!("invalid window size `{}`, expected e.g. 1280x720", value)
+                    })?);
+                }
+                "--provider" => {
+                    options.structure_source = match value.as_str() {
+                        "rcsb" => StructureSource::Rcsb,
+                        "pdbe" => StructureSource::Pdbe,
+                        "pdbj" => StructureSource::Pdbj,
+                        "alphafold" => StructureSource::AlphaFoldDb,
+                        "modelarchive" => StructureSource::ModelArchive,
+                        url if url.starts_with("http://") || url.starts_with("https://") => {
+                            StructureSource::Mirror {
+                                base_url: url.to_string(),
+                                search_url: String::new(),
+                            }
+                        }
+                        _ => anyhow::bail!(
+                            "unknown provider `{}`, expected rcsb, pdbe, pdbj, alphafold, modelarchive or a URL",
+                            value
+                        ),
+                    };
+                }
+                "--search-url" => search_url = Some(value),
+                "--cache-size" => {
+                    options.cache_size_mib = parse_in_range(&name, &value, 1..=1024 * 1024)?;
+                }
+                _ => anyhow::bail!("unknown option {}", name),
+            }
+        }
+
+        // The search URL may be given before the mirror, so it is only applied once all options are parsed.
+        if let Some(url) = search_url {
+            match &mut options.structure_source {
+                StructureSource::Mirror { search_url, .. } => *search_url = url,
+                _ => anyhow::bail!("--search-url requires the URL of a mirror as --provider"),
+            }
+        }
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,252 @@
+use poms_common::models::atom::{AtomMetadata, SecondaryStructure};
+
+use poms_io::is_water;
+use poms_io::molecule_parser::get_jmol_color;
+
+/// Determines how atoms (and the molecular surface around them) are colored.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) enum ColorScheme {
+    /// CPK coloring by chemical element, using the colors of Jmol.
+    #[default]
+    Element,
+    /// Each chain gets a distinct color.
+    Chain,
+    /// Residues are colored by the properties of their side chains (acidic, basic, polar, nonpolar), nucleotides and waters separately.
+    ResidueType,
+    /// Gradient from blue (lowest B-factor in the molecule) through white to red (highest B-factor).
+    BFactor,
+    /// Confidence bands of predicted models like in AlphaFold DB, from the pLDDT stored in the B-factor.
+    Plddt,
+    /// Gradient from blue (hydrophilic) through white to orange (hydrophobic) by the Kyte-Doolittle scale.
+    Hydrophobicity,
+    /// Helices, sheets and coils are colored differently.
+    SecondaryStructure,
+    /// Gradient from red (negative) through white to blue (positive) by the partial charges from the file.
+    Charge,
+    /// All atoms share the same color.
+    Uniform([f32; 3]),
+}
+
+impl ColorScheme {
+    /// All color schemes, in the order they are offered to the user.
+    pub(crate) const ALL: [ColorScheme; 9] = [
+        Self::Element,
+        Self::Chain,
+        Self::ResidueType,
+        Self::BFactor,
+        Self::Plddt,
+        Self::Hydrophobicity,
+        Self::SecondaryStructure,
+        Self::Charge,
+        Self::Uniform(UNIFORM_COLOR),
+    ];
+
+    /// Human-readable name of the scheme.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Element => "Element",
+            Self::Chain => "Chain",
+            Self::ResidueType => "Residue type",
+            Self::BFactor => "B-factor",
+            Self::Plddt => "pLDDT",
+            Self::Hydrophobicity => "Hydrophobicity",
+            Self::SecondaryStructure => "Secondary structure",
+            Self::Charge => "Charge",
+            Self::Uniform(_) => "Uniform",
+        }
+    }
+
+    /// Returns `true` if both schemes are the same regardless of their parameters (e.g. the uniform color).
+    pub(crate) fn is_same_kind(&self, other: &ColorScheme) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Computes the color of each atom described by `metadata`.
+    pub(crate) fn colors(&self, metadata: &[AtomMetadata]) -> Vec<[f32; 4]> {
+        match self {
+            Self::Element => metadata
+                .iter()
+                .map(|atom| {
+                    let element = atom
+                        .element
+                        .as_deref()
+                        .and_then(|symbol| pdbtbx::Element::try_from(symbol).ok());
+                    get_jmol_color(element.as_ref())
+                })
+                .collect(),
+            Self::Chain => {
+                // Sort the chain identifiers so that colors do not depend on the order of atoms.
+                let mut chain_ids = metadata
+                    .iter()
+                    .map(|atom| atom.chain_id.as_str())
+                    .collect::<Vec<_>>();
+                chain_ids.sort_unstable();
+                chain_ids.dedup();
+
+                metadata
+                    .iter()
+                    .map(|atom| {
+                        let index = chain_ids
+                            .binary_search(&atom.chain_id.as_str())
+                            .unwrap_or(0);
+                        CHAIN_PALETTE[index % CHAIN_PALETTE.len()]
+                    })
+                    .collect()
+            }
+            Self::ResidueType => metadata
+                .iter()
+                .map(|atom| residue_type_color(&atom.residue_name))
+                .collect(),
+            Self::BFactor => {
+                let (min, max) = metadata
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(min, max), atom| {
+                        (min.min(atom.b_factor), max.max(atom.b_factor))
+                    });
+                let range = (max - min).max(f32::EPSILON);
+
+                metadata
+                    .iter()
+                    .map(|atom| diverging_gradient((atom.b_factor - min) / range, BLUE, RED))
+                    .collect()
+            }
+            Self::Plddt => metadata
+                .iter()
+                .map(|atom| plddt_color(atom.b_factor))
+                .collect(),
+            Self::Hydrophobicity => metadata
+                .iter()
+                .map(|atom| match kyte_doolittle(&atom.residue_name) {
+                    Some(value) => {
+                        let (min, max) = KYTE_DOOLITTLE_RANGE;
+                        diverging_gradient((value - min) / (max - min), BLUE, ORANGE)
+                    }
+                    None => NEUTRAL,
+                })
+                .collect(),
+            Self::SecondaryStructure => metadata
+                .iter()
+                .map(|atom| match atom.secondary_structure {
+                    SecondaryStructure::Helix => [0.94, 0.0, 0.5, 1.0],
+                    SecondaryStructure::Sheet => [1.0, 0.78, 0.0, 1.0],
+                    SecondaryStructure::Coil => NEUTRAL,
+                })
+                .collect(),
+            Self::Charge => metadata
+                .iter()
+                .map(|atom| match atom.charge {
+                    Some(charge) => {
+                        let t = charge / MAX_CHARGE * 0.5 + 0.5;
+                        diverging_gradient(t, RED, BLUE)
+                    }
+                    None => NEUTRAL,
+                })
+                .collect(),
+            Self::Uniform([r, g, b]) => vec![[*r, *g, *b, 1.0]; metadata.len()],
+        }
+    }
+}
+
+/// Initial color of the uniform scheme.
+const UNIFORM_COLOR: [f32; 3] = [1.0, 0.8, 0.8];
+
+/// Charges (in elementary charges) at or beyond which the charge scheme uses the most saturated colors.
+const MAX_CHARGE: f32 = 1.0;
+
+const NEUTRAL: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const BLUE: [f32; 4] = [0.13, 0.4, 0.9, 1.0];
+const RED: [f32; 4] = [0.9, 0.15, 0.15, 1.0];
+const ORANGE: [f32; 4] = [1.0, 0.55, 0.1, 1.0];
+
+/// Distinct colors assigned to chains, repeated if there are more chains.
+const CHAIN_PALETTE: [[f32; 4]; 12] = [
+    [0.12, 0.47, 0.71, 1.0],
+    [1.0, 0.5, 0.05, 1.0],
+    [0.17, 0.63, 0.17, 1.0],
+    [0.84, 0.15, 0.16, 1.0],
+    [0.58, 0.4, 0.74, 1.0],
+    [0.55, 0.34, 0.29, 1.0],
+    [0.89, 0.47, 0.76, 1.0],
+    [0.5, 0.5, 0.5, 1.0],
+    [0.74, 0.74, 0.13, 1.0],
+    [0.09, 0.75, 0.81, 1.0],
+    [0.68, 0.78, 0.91, 1.0],
+    [1.0, 0.73, 0.47, 1.0],
+];
+
+/// Interpolates from `low` (at 0) through white (at 0.5) to `high` (at 1).
+fn diverging_gradient(t: f32, low: [f32; 4], high: [f32; 4]) -> [f32; 4] {
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    let t = t.clamp(0.0, 1.0);
+    let (from, to, t) = match t < 0.5 {
+        true => (low, WHITE, t * 2.0),
+        false => (WHITE, high, t * 2.0 - 1.0),
+    };
+    std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t)
+}
+
+fn residue_type_color(residue_name: &str) -> [f32; 4] {
+    match residue_name {
+        // Acidic
+        "ASP" | "GLU" => [0.9, 0.1, 0.1, 1.0],
+        // Basic
+        "LYS" | "ARG" | "HIS" => [0.1, 0.3, 0.95, 1.0],
+        // Polar
+        "SER" | "THR" | "ASN" | "GLN" | "TYR" | "CYS" => [0.2, 0.75, 0.3, 1.0],
+        // Nonpolar
+        "ALA" | "VAL" | "LEU" | "ILE" | "MET" | "PHE" | "TRP" | "PRO" | "GLY" => {
+            [0.85, 0.85, 0.85, 1.0]
+        }
+        // Nucleotides
+        "A" | "C" | "G" | "U" | "T" | "DA" | "DC" | "DG" | "DT" | "DU" => [1.0, 0.55, 0.1, 1.0],
+        // Water
+        name if is_water(name) => [0.5, 0.85, 1.0, 1.0],
+        // Ligands and everything else
+        _ => [0.8, 0.3, 0.8, 1.0],
+    }
+}
+
+/// Color of the confidence band of the pLDDT, so that regions with low confidence (below 70) stand out in yellow and orange.
+fn plddt_color(plddt: f32) -> [f32; 4] {
+    match plddt {
+        // Very high
+        plddt if plddt > 90.0 => [0.0, 0.33, 0.84, 1.0],
+        // Confident
+        plddt if plddt > 70.0 => [0.4, 0.8, 0.95, 1.0],
+        // Low
+        plddt if plddt > 50.0 => [1.0, 0.86, 0.07, 1.0],
+        // Very low
+        _ => [1.0, 0.49, 0.27, 1.0],
+    }
+}
+
+/// Lowest and highest value of the Kyte-Doolittle hydropathy scale.
+const KYTE_DOOLITTLE_RANGE: (f32, f32) = (-4.5, 4.5);
+
+/// Hydropathy index of an amino acid by Kyte and Doolittle. Returns `None` for other residues.
+fn kyte_doolittle(residue_name: &str) -> Option<f32> {
+    let value = match residue_name {
+        "ILE" => 4.5,
+        "VAL" => 4.2,
+        "LEU" => 3.8,
+        "PHE" => 2.8,
+        "CYS" => 2.5,
+        "MET" => 1.9,
+        "ALA" => 1.8,
+        "GLY" => -0.4,
+        "THR" => -0.7,
+        "SER" => -0.8,
+        "TRP" => -0.9,
+        "TYR" => -1.3,
+        "PRO" => -1.6,
+        "HIS" => -3.2,
+        "GLU" => -3.5,
+        "GLN" => -3.5,
+        "ASP" => -3.5,
+        "ASN" => -3.5,
+        "LYS" => -3.9,
+        "ARG" => -4.5,
+        _ => return None,
+    };
+    Some(value)
+}
@@ -1,8 +1,20 @@
 use std::sync::mpsc;
 
-use super::molecule_parser::{parse_multiple_files, ParsedMolecule};
-use super::pdb_apis::{download_api::PdbDownloadApi, search_api::PdbSearchApi};
-use super::{Assembly, RawFile};
+use poms_common::models::atom::{Atom, AtomsWithLookup};
+use poms_compute::export::ExportFormat;
+use poms_compute::headless::DistanceFieldData;
+
+use super::pdb_apis::{
+    cache::StructureCache, download_api::PdbDownloadApi, provider::StructureSource,
+    search_api::PdbSearchApi,
+};
+use super::Assembly;
+use poms_io::molecule_parser::{
+    attach_trajectories, load_options::LoadOptions, parse_multiple_files, MoleculeFormat,
+    ParsedMolecule,
+};
+use poms_io::trajectory::{Trajectory, TrajectoryFormat, TrajectorySource};
+use poms_io::RawFile;
 
 pub(crate) enum DownloadProgress {
     Downloading { bytes_downloaded: usize },
@@ -20,6 +32,35 @@ pub(crate) enum AsyncWorkResult {
     DownloadProgressed {
         progress: DownloadProgress,
     },
+    DownloadFailed {
+        error: anyhow::Error,
+    },
+    SurfaceExported {
+        result: anyhow::Result<String>,
+    },
+}
+
+/// Extensions of compressed files, which may contain any of the molecule formats (e.g. `.pdb.gz`).
+const COMPRESSED_EXTENSIONS: [&str; 1] = ["gz"];
+
+/// Things required to export the molecular surface of a molecule to a file.
+pub(crate) struct SurfaceExportRequest {
+    /// Atoms of the molecule whose surface should be exported.
+    pub(crate) atoms: Vec<Atom>,
+    /// Radius of the probe used to compute the molecular surface.
+    pub(crate) probe_radius: f32,
+    /// Distance field the mesh is extracted from.
+    pub(crate) distance_field: SurfaceDistanceField,
+}
+
+/// Source of the distance field of an exported surface.
+pub(crate) enum SurfaceDistanceField {
+    /// The distance field computed for rendering, read back from the GPU.
+    ReadBack(DistanceFieldData),
+    /// The distance field has to be computed on the CPU at the given resolution. Only available on native platforms,
+    /// as the computation would block the page on the web.
+    #[cfg(not(target_arch = "wasm32"))]
+    Compute { resolution: u32 },
 }
 
 /// Asynchronously loads and downloads files. This design ensures compatibility across
@@ -32,6 +73,10 @@ pub(crate) struct FileLoader {
     ),
     download_api: PdbDownloadApi,
     search_api: PdbSearchApi,
+    /// Downloaded structures, shared by the download APIs of all structure sources.
+    cache: StructureCache,
+    /// Whether structures are only opened from the cache and searches list the cached ones.
+    is_offline: bool,
 }
 
 impl FileLoader {
@@ -44,47 +89,138 @@ impl FileLoader {
     ///
     /// The selected files are read and sent over a channel for processing.
     /// User may select multiple files, which are interpreted not as separate molecules but as frames (animation) of a single molecule.
-    pub(crate) fn pick_files(&self) {
+    /// If trajectories are selected, the other selected file is used as their topology and frames are read from the trajectories on demand.
+    /// Atoms excluded by `load_options` are left out of the molecules.
+    pub(crate) fn pick_files(&self, load_options: LoadOptions) {
         let dispatch = self.data_channel.0.clone();
         execute(async move {
-            let file_dialog = rfd::AsyncFileDialog::new().add_filter("PDB", &["pdb", "cif"]);
+            // Topologies and trajectories have to be selected together, so they share the default filter.
+            let molecule_extensions = [
+                MoleculeFormat::EXTENSIONS.as_slice(),
+                &COMPRESSED_EXTENSIONS,
+            ]
+            .concat();
+            let extensions = [
+                molecule_extensions.as_slice(),
+                TrajectoryFormat::EXTENSIONS.as_slice(),
+            ]
+            .concat();
+            let file_dialog = rfd::AsyncFileDialog::new()
+                .add_filter("Molecules and trajectories", extensions.as_slice())
+                .add_filter("Molecules", molecule_extensions.as_slice())
+                .add_filter("Trajectory", &TrajectoryFormat::EXTENSIONS);
             if let Some(files) = file_dialog.pick_files().await {
-                // Load all files asynchronously
-                let loaded_files: Vec<RawFile> =
-                    futures::future::join_all(files.iter().map(|file| async {
-                        RawFile {
-                            name: file.file_name(),
-                            content: file.read().await,
-                        }
-                    }))
-                    .await;
-
-                let parsed_files = parse_multiple_files(loaded_files);
-
                 dispatch
                     .send(AsyncWorkResult::FilesParsed {
-                        result: parsed_files,
+                        result: load_file_handles(files, load_options).await,
                     })
                     .ok();
             }
         })
     }
 
+    /// Loads files dropped onto the window, in the same way as the files selected by [`FileLoader::pick_files`].
+    /// All files of a single drop are loaded together, so multiple molecule files become frames of a single molecule.
+    pub(crate) fn load_dropped_files(&self, files: Vec<DroppedFile>, load_options: LoadOptions) {
+        let dispatch = self.data_channel.0.clone();
+        execute(async move {
+            #[cfg(not(target_arch = "wasm32"))]
+            let result = {
+                let files = files.into_iter().map(rfd::FileHandle::from).collect();
+                load_file_handles(files, load_options).await
+            };
+            #[cfg(target_arch = "wasm32")]
+            let result = load_browser_files(files, load_options).await;
+
+            dispatch.send(AsyncWorkResult::FilesParsed { result }).ok();
+        })
+    }
+
+    /// Opens an async save file dialog and exports the molecular surface to the selected file.
+    ///
+    /// The distance field is converted to a mesh colored by the nearest atoms in the background.
+    /// The format is chosen by the extension of the selected file, falling back to OBJ if it is not recognized.
+    pub(crate) fn export_surface(&self, request: SurfaceExportRequest) {
+        let dispatch = self.data_channel.0.clone();
+        execute(async move {
+            let file_dialog = ExportFormat::ALL.iter().fold(
+                rfd::AsyncFileDialog::new().set_file_name("surface.obj"),
+                |dialog, format| dialog.add_filter(format.name(), &[format.extension()]),
+            );
+            if let Some(file) = file_dialog.save_file().await {
+                let file_name = file.file_name();
+                let format = ExportFormat::from_file_name(&file_name).unwrap_or(ExportFormat::Obj);
+
+                let result = match export_surface_mesh(request, format) {
+                    Ok(content) => file.write(&content).await.map(|_| file_name),
+                    Err(error) => Err(error),
+                };
+
+                dispatch
+                    .send(AsyncWorkResult::SurfaceExported {
+                        result: result.map_err(anyhow::Error::new),
+                    })
+                    .ok();
+            }
+        })
+    }
+
+    /// Searches and downloads structures from the given source from now on.
+    pub(crate) fn set_structure_source(&mut self, source: &StructureSource) {
+        let provider = source.provider();
+        self.download_api = PdbDownloadApi::new(provider.clone(), self.cache.clone());
+        self.search_api = PdbSearchApi::new(provider);
+    }
+
+    /// Serves structures only from the cache, without accessing the network, while `is_offline` is set.
+    pub(crate) fn set_offline(&mut self, is_offline: bool) {
+        self.is_offline = is_offline;
+        self.search_api.cancel_pending_search();
+    }
+
+    /// Sets the size limit of the cache of downloaded structures in MiB, evicting files that no longer fit.
+    pub(crate) fn set_cache_size(&self, max_size_mib: u64) {
+        let cache = self.cache.clone();
+        execute(async move {
+            cache.set_max_size(max_size_mib).await;
+        });
+    }
+
     /// Downloads a file asynchronously using the provided `Assembly` object used to identify the file to download.
-    /// Uses a minimal wrapper around the RCSB's public API to fetch the file content.
-    /// Fetched files are returned to the main thread via a channel.
-    pub(crate) fn download_file(&self, assembly: Assembly) {
+    /// The file is fetched from the cache if it was downloaded before, otherwise from the current structure source,
+    /// see [`FileLoader::set_structure_source`]. Fetched files are returned to the main thread via a channel.
+    pub(crate) fn download_file(&self, assembly: Assembly, load_options: LoadOptions) {
         let dispatch = self.data_channel.0.clone();
         let download_api = self.download_api.clone();
+        let is_offline = self.is_offline;
         execute(async move {
-            let _ = download_api.download_assembly(&assembly, dispatch).await;
+            if let Err(error) = download_api
+                .download_assembly(&assembly, load_options, is_offline, dispatch.clone())
+                .await
+            {
+                dispatch
+                    .send(AsyncWorkResult::DownloadFailed { error })
+                    .ok();
+            }
         });
     }
 
-    /// Initializes an asychronous task that does full-text search for PDB files using the RCSB's public API.
+    /// Initializes an asychronous task that does full-text search for PDB files using the current structure source.
+    /// In offline mode, the cached structures whose name contains the query are listed instead, without debouncing.
     /// Fetched results are returned to the main thread via a channel.
     pub(crate) fn search_pdb_files(&self, query: String) {
         let dispatch = self.data_channel.0.clone();
+        if self.is_offline {
+            let cache = self.cache.clone();
+            execute(async move {
+                let result = Ok(cache.list(&query).await);
+                dispatch
+                    .send(AsyncWorkResult::SearchResultsParsed { result })
+                    .ok();
+            });
+            return;
+        }
+
         let search_api = self.search_api.clone();
         execute(async move {
             let _ = search_api.fulltext_search_debounced(&query, dispatch).await;
@@ -99,12 +235,175 @@ impl FileLoader {
 
 impl Default for FileLoader {
     fn default() -> Self {
+        let provider = StructureSource::default().provider();
+        let cache = StructureCache::default();
         Self {
             data_channel: mpsc::channel(),
-            download_api: PdbDownloadApi::default(),
-            search_api: PdbSearchApi::default(),
+            download_api: PdbDownloadApi::new(provider.clone(), cache.clone()),
+            search_api: PdbSearchApi::new(provider),
+            cache,
+            is_offline: false,
+        }
+    }
+}
+
+/// Reads the files picked by the user and parses them with [`parse_loaded_files`]. On native platforms, trajectories are streamed from disk.
+async fn load_file_handles(
+    files: Vec<rfd::FileHandle>,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    let (trajectory_files, molecule_files): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|file| TrajectoryFormat::from_file_name(&file.file_name()).is_some());
+
+    // Load all files asynchronously
+    let loaded_files: Vec<RawFile> =
+        futures::future::join_all(molecule_files.iter().map(|file| async {
+            RawFile {
+                name: file.file_name(),
+                content: file.read().await,
+            }
+        }))
+        .await;
+
+    let trajectories = open_trajectories(&trajectory_files).await?;
+    parse_loaded_files(loaded_files, trajectories, load_options)
+}
+
+/// Reads and parses the files at the given paths (e.g. given on the command line) like [`load_file_handles`],
+/// blocking until they are loaded.
+pub(crate) fn load_paths(
+    paths: &[std::path::PathBuf],
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    let mut molecule_files = Vec::new();
+    let mut trajectories = Vec::new();
+    for path in paths {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let open_error = |error| anyhow::anyhow!("Failed to open {}: {}", path.display(), error);
+
+        match TrajectoryFormat::from_file_name(&name) {
+            Some(format) => {
+                let file = std::fs::File::open(path).map_err(open_error)?;
+                let source = Box::new(std::io::BufReader::new(file));
+                trajectories.push(Trajectory::open(name, format, source)?);
+            }
+            None => {
+                let content = std::fs::read(path).map_err(open_error)?;
+                molecule_files.push(RawFile { name, content });
+            }
+        }
+    }
+    parse_loaded_files(molecule_files, trajectories, load_options)
+}
+
+/// Parses the molecule files, either as frames of a single molecule or as the topology of the trajectories if there are any.
+fn parse_loaded_files(
+    molecule_files: Vec<RawFile>,
+    trajectories: Vec<Trajectory>,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    match trajectories.is_empty() {
+        true => parse_multiple_files(molecule_files, load_options),
+        false => attach_trajectories(molecule_files, trajectories, load_options),
+    }
+}
+
+/// A file dropped onto the window: its path on native platforms, or the file of the drop event in browsers.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type DroppedFile = std::path::PathBuf;
+#[cfg(target_arch = "wasm32")]
+pub(crate) type DroppedFile = web_sys::File;
+
+/// Reads the files dropped onto the canvas and parses them like [`load_file_handles`]. Browsers only give access
+/// to the content of dropped files, so trajectories are read into memory as a whole.
+#[cfg(target_arch = "wasm32")]
+async fn load_browser_files(
+    files: Vec<web_sys::File>,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    let mut molecule_files = Vec::new();
+    let mut trajectories = Vec::new();
+    for file in files {
+        let name = file.name();
+        let content = wasm_bindgen_futures::JsFuture::from(file.array_buffer())
+            .await
+            .map_err(|_| anyhow::anyhow!("Failed to read the dropped file {}", name))?;
+        let content = js_sys::Uint8Array::new(&content).to_vec();
+
+        match TrajectoryFormat::from_file_name(&name) {
+            Some(format) => {
+                let source = Box::new(std::io::Cursor::new(content));
+                trajectories.push(Trajectory::open(name, format, source)?);
+            }
+            None => molecule_files.push(RawFile { name, content }),
         }
     }
+    parse_loaded_files(molecule_files, trajectories, load_options)
+}
+
+/// Opens the selected trajectory files and indexes their frames.
+async fn open_trajectories(files: &[rfd::FileHandle]) -> anyhow::Result<Vec<Trajectory>> {
+    let mut trajectories = Vec::with_capacity(files.len());
+    for file in files {
+        let file_name = file.file_name();
+        let format = TrajectoryFormat::from_file_name(&file_name)
+            .expect("trajectory files are filtered by their extension");
+        let source = open_trajectory_source(file).await?;
+        trajectories.push(Trajectory::open(file_name, format, source)?);
+    }
+    Ok(trajectories)
+}
+
+/// Opens the trajectory file for reading its frames. Native platforms stream the frames from disk,
+/// while on the web the whole file has to be read into memory first.
+#[cfg(not(target_arch = "wasm32"))]
+async fn open_trajectory_source(
+    file: &rfd::FileHandle,
+) -> anyhow::Result<Box<dyn TrajectorySource>> {
+    let file = std::fs::File::open(file.path())?;
+    Ok(Box::new(std::io::BufReader::new(file)))
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn open_trajectory_source(
+    file: &rfd::FileHandle,
+) -> anyhow::Result<Box<dyn TrajectorySource>> {
+    Ok(Box::new(std::io::Cursor::new(file.read().await)))
+}
+
+/// Extracts the molecular surface mesh from the distance field and writes it in the given format.
+fn export_surface_mesh(
+    request: SurfaceExportRequest,
+    format: ExportFormat,
+) -> std::io::Result<Vec<u8>> {
+    let SurfaceExportRequest {
+        atoms,
+        probe_radius,
+        distance_field,
+    } = request;
+    let atoms = AtomsWithLookup::new(atoms, probe_radius);
+
+    let distance_field = match distance_field {
+        SurfaceDistanceField::ReadBack(distance_field) => distance_field,
+        #[cfg(not(target_arch = "wasm32"))]
+        SurfaceDistanceField::Compute { resolution } => {
+            let grid = poms_common::models::grid::create_compute_grid_around_molecule(
+                &atoms.data,
+                resolution,
+                probe_radius,
+            );
+            poms_compute::cpu::compute_distance_field_on_grid(&atoms, grid)
+        }
+    };
+    let mesh = poms_compute::mesh::extract_surface_mesh(&distance_field, Some(&atoms));
+
+    let mut content = Vec::new();
+    poms_compute::export::write_mesh(&mesh, format, &mut content)?;
+    Ok(content)
 }
 
 /// Executes a future on a separate thread or context to avoid blocking the main thread.
@@ -1,21 +1,44 @@
 use std::{fmt::Display, str::FromStr};
 
+use serde::{Deserialize, Serialize};
+
+pub(crate) mod color_scheme;
 pub(crate) mod file_loader;
-#[macro_use]
-pub(crate) mod molecule_parser;
 pub(crate) mod molecule_storage;
-mod pdb_apis;
+pub(crate) mod pdb_apis;
+pub(crate) mod selection;
+
+macro_rules! extract_file_name {
+    ($path:expr) => {{
+        $path.split('/').last().unwrap()
+    }};
+}
 
-/// Holds the raw content of a loaded file.
-pub(crate) struct RawFile {
-    pub(crate) name: String,
-    pub(crate) content: Vec<u8>,
+/// A convenience macro to parse a single molecule file from path, yielding a molecule for each of its models.
+macro_rules! include_molecule {
+    ($file_path:expr) => {{
+        use poms_io::RawFile;
+        let file_name = extract_file_name!($file_path);
+        let demo_file = include_bytes!($file_path);
+        let initial_molecules = poms_io::molecule_parser::parse_atoms_from_pdb_file(
+            RawFile {
+                name: file_name.to_string(),
+                content: demo_file.to_vec(),
+            },
+            Default::default(),
+        )
+        .unwrap();
+        initial_molecules
+    }};
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// An entry to download: a biological assembly of a PDB entry, or a predicted model, which has no assemblies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Assembly {
-    pdb_id: String,
-    assembly_id: String,
+    /// PDB ID, or the ID of a predicted model, e.g. `AF-P69905-F1-model_v4` in AlphaFold DB or `ma-bak-cepc-0001` in ModelArchive.
+    entry_id: String,
+    /// Biological assembly of a PDB entry, `None` for predicted models.
+    assembly_id: Option<String>,
 }
 
 impl FromStr for Assembly {
@@ -28,14 +51,42 @@ impl FromStr for Assembly {
         }
 
         Ok(Assembly {
-            pdb_id: parts[0].to_string(),
-            assembly_id: parts[1].to_string(),
+            entry_id: parts[0].to_string(),
+            assembly_id: Some(parts[1].to_string()),
         })
     }
 }
 
+impl Assembly {
+    /// Parses a PDB ID (e.g. `1cqw`), optionally followed by the assembly (e.g. `1cqw-2`), defaulting to the first assembly.
+    /// Returns `None` if the text is not a PDB ID, which consists of a digit and three alphanumeric characters.
+    pub(crate) fn from_pdb_id(text: &str) -> Option<Self> {
+        let (pdb_id, assembly_id) = text.split_once('-').unwrap_or((text, "1"));
+        let is_pdb_id = pdb_id.len() == 4
+            && pdb_id.starts_with(|c: char| c.is_ascii_digit())
+            && pdb_id.chars().all(|c| c.is_ascii_alphanumeric());
+        let is_assembly_id =
+            !assembly_id.is_empty() && assembly_id.chars().all(|c| c.is_ascii_digit());
+        (is_pdb_id && is_assembly_id).then(|| Assembly {
+            entry_id: pdb_id.to_ascii_uppercase(),
+            assembly_id: Some(assembly_id.to_string()),
+        })
+    }
+
+    /// Creates the entry of a predicted model with the given ID.
+    pub(crate) fn predicted_model(model_id: &str) -> Self {
+        Assembly {
+            entry_id: model_id.to_string(),
+            assembly_id: None,
+        }
+    }
+}
+
 impl Display for Assembly {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-{}", self.pdb_id, self.assembly_id)
+        match &self.assembly_id {
+            Some(assembly_id) => write!(f, "{}-{}", self.entry_id, assembly_id),
+            None => write!(f, "{}", self.entry_id),
+        }
     }
 }
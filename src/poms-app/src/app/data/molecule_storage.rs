@@ -1,9 +1,253 @@
-use super::molecule_parser::ParsedMolecule;
-use poms_common::models::atom::{Atom, AtomsWithLookup};
+use super::color_scheme::ColorScheme;
+use super::selection::Selection;
+use poms_common::limits::MAX_NUM_ATOMS;
+use poms_common::models::atom::{Atom, AtomMetadata, AtomsWithLookup};
+use poms_io::biological_assembly::BiologicalAssembly;
+use poms_io::crystal::{CrystalPacking, CrystalSymmetry};
+use poms_io::molecule_parser::{self, load_options::LoadOptions, ParsedMolecule};
+use poms_io::radius_set::RadiusSet;
+use poms_io::trajectory::Trajectory;
 
 pub(crate) struct MoleculeData {
     pub(crate) filename: String,
+    /// Atoms matching the current selection, prepared for fast neighbor look up.
     pub(crate) atoms: AtomsWithLookup,
+    /// Metadata of each atom, in the same order as `atoms.data`.
+    pub(crate) metadata: Vec<AtomMetadata>,
+
+    /// All atoms parsed from the file (the asymmetric unit), in the original order.
+    parsed_atoms: Vec<Atom>,
+    /// Metadata of all atoms parsed from the file, in the same order as `parsed_atoms`.
+    parsed_metadata: Vec<AtomMetadata>,
+    /// Radii of `parsed_atoms` assigned by the parser, used by the default radius set.
+    parsed_radii: Vec<f32>,
+    /// Options the atoms were filtered by when the file was loaded.
+    pub(crate) load_options: LoadOptions,
+    /// Number of atoms of the file removed by `load_options`.
+    pub(crate) num_removed_atoms: usize,
+    /// Whether each atom of the file is kept by `load_options`, to select the positions of `parsed_atoms` in trajectory frames.
+    kept_atoms: Vec<bool>,
+    /// Trajectory the positions of `parsed_atoms` are taken from, frame by frame.
+    trajectory: Option<Trajectory>,
+    /// Index of the trajectory frame the atoms are currently positioned in.
+    frame_index: usize,
+    /// Biological assemblies defined in the file.
+    pub(crate) assemblies: Vec<BiologicalAssembly>,
+    /// Index of the displayed assembly within `assemblies`, `None` if the asymmetric unit is displayed.
+    pub(crate) assembly_index: Option<usize>,
+    /// Unit cell and space group, if the molecule is a crystal structure.
+    pub(crate) crystal: Option<CrystalSymmetry>,
+    /// How the asymmetric unit is packed with its symmetry mates, when no assembly is displayed.
+    crystal_packing: CrystalPacking,
+    /// Atoms of the displayed assembly or the packed crystal built from `parsed_atoms`. The selection is evaluated against these.
+    assembled_atoms: Vec<Atom>,
+    /// Metadata of the displayed assembly, in the same order as `assembled_atoms`.
+    assembled_metadata: Vec<AtomMetadata>,
+}
+
+impl MoleculeData {
+    /// Creates molecule data from the parsed atoms matching `selection` (all atoms if `None`), with radii from `radius_set`.
+    /// Crystal structures are packed according to `crystal_packing`.
+    fn new(
+        parsed_molecule: ParsedMolecule,
+        selection: Option<&Selection>,
+        radius_set: RadiusSet,
+        crystal_packing: CrystalPacking,
+        probe_radius: f32,
+    ) -> Self {
+        let parsed_radii = parsed_molecule
+            .atoms
+            .iter()
+            .map(|atom| atom.radius)
+            .collect();
+        let mut molecule = Self {
+            filename: parsed_molecule.filename,
+            atoms: AtomsWithLookup::default(),
+            metadata: Vec::new(),
+            parsed_atoms: parsed_molecule.atoms,
+            parsed_metadata: parsed_molecule.metadata,
+            parsed_radii,
+            load_options: parsed_molecule.load_options,
+            num_removed_atoms: parsed_molecule
+                .kept_atoms
+                .iter()
+                .filter(|&&is_kept| !is_kept)
+                .count(),
+            kept_atoms: parsed_molecule.kept_atoms,
+            trajectory: parsed_molecule.trajectory,
+            frame_index: 0,
+            assemblies: parsed_molecule.assemblies,
+            assembly_index: None,
+            crystal: parsed_molecule.crystal,
+            crystal_packing,
+            assembled_atoms: Vec::new(),
+            assembled_metadata: Vec::new(),
+        };
+
+        molecule.assign_radii(radius_set);
+        // A crystal that cannot be packed (e.g. due to too many atoms) is displayed as the asymmetric unit.
+        let _ = molecule.assemble();
+        let mask = molecule.selection_mask(selection);
+        molecule.build_lookup(&mask, probe_radius);
+        molecule
+    }
+
+    /// Sets the radii of all parsed atoms according to the radius set. The lookup has to be rebuilt afterwards.
+    fn assign_radii(&mut self, radius_set: RadiusSet) {
+        let radii = radius_set.radii(&self.parsed_metadata, &self.parsed_radii);
+        for (atom, radius) in self.parsed_atoms.iter_mut().zip(radii) {
+            atom.radius = radius;
+        }
+    }
+
+    /// Builds the displayed assembly from the parsed atoms, which has to be done whenever they change.
+    /// Without an assembly, the asymmetric unit of a crystal is packed with its symmetry mates.
+    /// If the packing fails, the asymmetric unit alone is displayed and the error is returned.
+    fn assemble(&mut self) -> anyhow::Result<()> {
+        let assembly = self
+            .assembly_index
+            .and_then(|index| self.assemblies.get(index));
+        let assembled = match (assembly, &self.crystal) {
+            (Some(assembly), _) => Ok(assembly.build(&self.parsed_atoms, &self.parsed_metadata)),
+            (None, Some(crystal)) => crystal.pack(
+                &self.parsed_atoms,
+                &self.parsed_metadata,
+                self.crystal_packing,
+            ),
+            (None, None) => Ok((self.parsed_atoms.clone(), self.parsed_metadata.clone())),
+        };
+
+        match assembled {
+            Ok(assembled) => {
+                (self.assembled_atoms, self.assembled_metadata) = assembled;
+                Ok(())
+            }
+            Err(error) => {
+                self.assembled_atoms = self.parsed_atoms.clone();
+                self.assembled_metadata = self.parsed_metadata.clone();
+                Err(error)
+            }
+        }
+    }
+
+    /// Evaluates the selection on all atoms of the displayed assembly. Without a selection, all atoms are selected.
+    fn selection_mask(&self, selection: Option<&Selection>) -> Vec<bool> {
+        match selection {
+            Some(selection) => selection.evaluate(&self.assembled_atoms, &self.assembled_metadata),
+            None => vec![true; self.assembled_atoms.len()],
+        }
+    }
+
+    /// Creates the data structure for efficient neighbor lookup needed for molecular surface algorithm from the atoms selected by `mask`.
+    /// If no atom is selected, the whole molecule is used instead, as there would be nothing to display.
+    fn build_lookup(&mut self, mask: &[bool], probe_radius: f32) {
+        let is_anything_selected = mask.contains(&true);
+        let (atoms, metadata): (Vec<Atom>, Vec<AtomMetadata>) = self
+            .assembled_atoms
+            .iter()
+            .zip(&self.assembled_metadata)
+            .zip(mask)
+            .filter(|(_, &is_selected)| is_selected || !is_anything_selected)
+            .map(|((atom, metadata), _)| (*atom, metadata.clone()))
+            .unzip();
+
+        self.atoms = AtomsWithLookup::new(atoms, probe_radius);
+        // Atoms get sorted for the lookup, metadata has to follow the same order
+        self.metadata = self.atoms.permute(metadata);
+    }
+
+    /// Returns the current frame and the number of frames, if the molecule is animated by a trajectory.
+    pub(crate) fn trajectory_frames(&self) -> Option<(usize, usize)> {
+        self.trajectory
+            .as_ref()
+            .map(|trajectory| (self.frame_index, trajectory.num_frames()))
+    }
+
+    /// Moves the atoms to their positions in the given trajectory frame and rebuilds the lookup,
+    /// re-evaluating the selection as it may depend on the positions.
+    fn load_frame(
+        &mut self,
+        frame_index: usize,
+        selection: Option<&Selection>,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let Some(trajectory) = self.trajectory.as_mut() else {
+            anyhow::bail!("The molecule has no trajectory");
+        };
+
+        let positions = trajectory.read_frame(frame_index)?;
+        let positions = molecule_parser::kept(positions, &self.kept_atoms);
+        for (atom, position) in self.parsed_atoms.iter_mut().zip(positions) {
+            atom.position = *position;
+        }
+        self.frame_index = frame_index;
+        let assembled = self.assemble();
+
+        let mask = self.selection_mask(selection);
+        self.build_lookup(&mask, probe_radius);
+        assembled
+    }
+
+    /// Displays the assembly at the given index, or the asymmetric unit if `None`, and rebuilds the lookup.
+    fn set_assembly(
+        &mut self,
+        assembly_index: Option<usize>,
+        selection: Option<&Selection>,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        if let Some(index) = assembly_index {
+            let Some(assembly) = self.assemblies.get(index) else {
+                anyhow::bail!("The molecule has no assembly with index {}", index);
+            };
+            let num_atoms = assembly.num_atoms(&self.parsed_metadata);
+            if num_atoms == 0 {
+                anyhow::bail!("{} does not contain any of the loaded atoms", assembly.name);
+            }
+            if num_atoms > MAX_NUM_ATOMS {
+                anyhow::bail!(
+                    "{} has {} atoms, which exceeds the limit ({})",
+                    assembly.name,
+                    num_atoms,
+                    MAX_NUM_ATOMS
+                );
+            }
+        }
+
+        self.assembly_index = assembly_index;
+        let assembled = self.assemble();
+        let mask = self.selection_mask(selection);
+        self.build_lookup(&mask, probe_radius);
+        assembled
+    }
+
+    /// Packs the crystal with its symmetry mates as given by `crystal_packing` and rebuilds the lookup.
+    /// If the packing fails, the previous one is kept.
+    fn set_crystal_packing(
+        &mut self,
+        crystal_packing: CrystalPacking,
+        selection: Option<&Selection>,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let previous_packing = std::mem::replace(&mut self.crystal_packing, crystal_packing);
+        if let Err(error) = self.assemble() {
+            // The lookup was built from the previous packing, so the assembled atoms are restored to match it.
+            self.crystal_packing = previous_packing;
+            let _ = self.assemble();
+            return Err(error);
+        }
+
+        let mask = self.selection_mask(selection);
+        self.build_lookup(&mask, probe_radius);
+        Ok(())
+    }
+
+    /// Recolors the atoms according to the given color scheme. Only colors are changed, the order of atoms stays the same.
+    fn apply_color_scheme(&mut self, color_scheme: &ColorScheme) {
+        let colors = color_scheme.colors(&self.metadata);
+        for (atom, color) in self.atoms.data.iter_mut().zip(colors) {
+            atom.color = color;
+        }
+    }
 }
 
 pub(crate) struct MoleculeStorage {
@@ -11,16 +255,28 @@ pub(crate) struct MoleculeStorage {
     pub(crate) active_index: usize,
     /// Molecules that are preloaded and ready to be displayed.
     pub(crate) loaded_molecules: Vec<MoleculeData>,
+    /// Color scheme applied to all loaded molecules.
+    color_scheme: ColorScheme,
+    /// Selection of atoms that are displayed and used for the molecular surface, applied to all loaded molecules.
+    selection: Option<Selection>,
+    /// Radii of atoms, applied to all loaded molecules.
+    radius_set: RadiusSet,
+    /// Packing of crystal structures with their symmetry mates, applied to all loaded molecules.
+    crystal_packing: CrystalPacking,
 }
 
 impl MoleculeStorage {
-    pub(crate) fn new(initial_molecule: ParsedMolecule, probe_radius: f32) -> Self {
+    pub(crate) fn new(initial_molecules: Vec<ParsedMolecule>, probe_radius: f32) -> Self {
         let mut storage = Self {
             active_index: 0,
             loaded_molecules: Vec::new(),
+            color_scheme: ColorScheme::default(),
+            selection: None,
+            radius_set: RadiusSet::default(),
+            crystal_packing: CrystalPacking::default(),
         };
-        // Add the initial molecule to the storage
-        storage.add_from_parsed(vec![initial_molecule], probe_radius);
+        // Add the initial molecules (one per model of the initial file) to the storage
+        storage.add_from_parsed(initial_molecules, probe_radius);
         storage
     }
 
@@ -64,24 +320,145 @@ impl MoleculeStorage {
         // Set the first added molecule as active
         self.active_index = self.loaded_molecules.len();
 
-        for ParsedMolecule { filename, atoms } in parsed_molecules {
-            // Create data structure for efficient neighbor lookup needed for molecular surface algorithm
-            let atoms =
-                AtomsWithLookup::new(atoms.into_iter().map(Atom::from).collect(), probe_radius);
-
-            let molecule_data = MoleculeData { filename, atoms };
+        for parsed_molecule in parsed_molecules {
+            let mut molecule_data = MoleculeData::new(
+                parsed_molecule,
+                self.selection.as_ref(),
+                self.radius_set,
+                self.crystal_packing,
+                probe_radius,
+            );
+            molecule_data.apply_color_scheme(&self.color_scheme);
 
             // Add the molecule to the storage
             self.loaded_molecules.push(molecule_data);
         }
     }
 
+    /// Advances the animation. Trajectories step to their next frame, otherwise the next molecule becomes active.
+    pub(crate) fn advance_animation(&mut self, probe_radius: f32) -> anyhow::Result<()> {
+        match self.get_active().trajectory_frames() {
+            Some((frame_index, num_frames)) => {
+                self.set_active_frame((frame_index + 1) % num_frames, probe_radius)
+            }
+            None => {
+                self.increment_active();
+                Ok(())
+            }
+        }
+    }
+
+    /// Positions the atoms of the active molecule as in the given frame of its trajectory.
+    pub(crate) fn set_active_frame(
+        &mut self,
+        frame_index: usize,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let molecule = &mut self.loaded_molecules[self.active_index];
+        molecule.load_frame(frame_index, self.selection.as_ref(), probe_radius)?;
+        molecule.apply_color_scheme(&self.color_scheme);
+        Ok(())
+    }
+
+    /// Displays the given assembly of the active molecule, or its asymmetric unit if `None`.
+    pub(crate) fn set_active_assembly(
+        &mut self,
+        assembly_index: Option<usize>,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let molecule = &mut self.loaded_molecules[self.active_index];
+        molecule.set_assembly(assembly_index, self.selection.as_ref(), probe_radius)?;
+        molecule.apply_color_scheme(&self.color_scheme);
+        Ok(())
+    }
+
+    /// Returns the packing of crystal structures with their symmetry mates.
+    pub(crate) fn crystal_packing(&self) -> CrystalPacking {
+        self.crystal_packing
+    }
+
+    /// Changes the packing of crystal structures with their symmetry mates for all loaded molecules.
+    /// Returns an error if the active molecule cannot be packed, in which case nothing changes.
+    /// Other molecules that cannot be packed keep their previous packing.
+    pub(crate) fn set_crystal_packing(
+        &mut self,
+        crystal_packing: CrystalPacking,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let selection = self.selection.as_ref();
+        let active_molecule = &mut self.loaded_molecules[self.active_index];
+        if crystal_packing != CrystalPacking::None && active_molecule.crystal.is_none() {
+            anyhow::bail!("{} does not define a unit cell", active_molecule.filename);
+        }
+        active_molecule.set_crystal_packing(crystal_packing, selection, probe_radius)?;
+
+        for (index, molecule) in self.loaded_molecules.iter_mut().enumerate() {
+            if index != self.active_index {
+                let _ = molecule.set_crystal_packing(crystal_packing, selection, probe_radius);
+            }
+            molecule.apply_color_scheme(&self.color_scheme);
+        }
+        self.crystal_packing = crystal_packing;
+        Ok(())
+    }
+
+    /// Changes the color scheme and recolors all loaded molecules.
+    pub(crate) fn set_color_scheme(&mut self, color_scheme: ColorScheme) {
+        self.color_scheme = color_scheme;
+        for molecule in &mut self.loaded_molecules {
+            molecule.apply_color_scheme(&self.color_scheme);
+        }
+    }
+
+    /// Changes the selection of atoms of all loaded molecules, `None` selects all atoms.
+    /// Returns an error if the selection does not match any atom of the active molecule, in which case nothing changes.
+    /// Other molecules the selection does not match are displayed whole.
+    pub(crate) fn set_selection(
+        &mut self,
+        selection: Option<Selection>,
+        probe_radius: f32,
+    ) -> anyhow::Result<()> {
+        let masks = self
+            .loaded_molecules
+            .iter()
+            .map(|molecule| molecule.selection_mask(selection.as_ref()))
+            .collect::<Vec<_>>();
+
+        if !masks[self.active_index].contains(&true) {
+            anyhow::bail!("The selection does not match any atoms");
+        }
+
+        for (molecule, mask) in self.loaded_molecules.iter_mut().zip(masks) {
+            molecule.build_lookup(&mask, probe_radius);
+            molecule.apply_color_scheme(&self.color_scheme);
+        }
+        self.selection = selection;
+
+        Ok(())
+    }
+
+    /// Changes the radius set and rebuilds the neighbor lookup of all loaded molecules, as the radii affect it.
+    pub(crate) fn set_radius_set(&mut self, radius_set: RadiusSet, probe_radius: f32) {
+        self.radius_set = radius_set;
+        for molecule in &mut self.loaded_molecules {
+            molecule.assign_radii(radius_set);
+            // The number of atoms does not change, so a packing that succeeded before still does.
+            let _ = molecule.assemble();
+            let mask = molecule.selection_mask(self.selection.as_ref());
+            molecule.build_lookup(&mask, probe_radius);
+            molecule.apply_color_scheme(&self.color_scheme);
+        }
+    }
+
     pub(crate) fn on_probe_radius_changed(&mut self, probe_radius: f32) {
         // In case probe radius changes, neighbor lookup has to be recomputed, as the spacing of the grid depends on it.
         for molecule in &mut self.loaded_molecules {
             // Use `std::mem::take` to avoid reallocation of data
             let atoms_data = std::mem::take(&mut molecule.atoms.data);
             molecule.atoms = AtomsWithLookup::new(atoms_data, probe_radius);
+            molecule.metadata = molecule
+                .atoms
+                .permute(std::mem::take(&mut molecule.metadata));
         }
     }
 }
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+
+use super::provider::{HttpRequest, StructureProvider};
+use crate::app::data::Assembly;
+
+/// AlphaFold DB, predicted models are found by UniProt accession with its API and downloaded from its file layout,
+/// e.g. `files/AF-P69905-F1-model_v4.cif`. The files store the pLDDT of each residue in the B-factor column.
+pub(crate) struct AlphaFoldDb;
+
+impl AlphaFoldDb {
+    const FILES_URL: &'static str = "https://alphafold.ebi.ac.uk/files";
+    const PREDICTION_API_URL: &'static str = "https://alphafold.ebi.ac.uk/api/prediction";
+    /// Version of the models used if the API does not name the latest one.
+    const DEFAULT_MODEL_VERSION: u32 = 4;
+}
+
+impl StructureProvider for AlphaFoldDb {
+    fn name(&self) -> &str {
+        "AlphaFold DB"
+    }
+
+    /// IDs of the models are the names of their files, which include the version of the model.
+    fn download_url(&self, assembly: &Assembly) -> String {
+        format!("{}/{}.cif", Self::FILES_URL, assembly.entry_id)
+    }
+
+    /// Long proteins are split into several overlapping fragments, which are all returned, so `max_results` is not needed.
+    fn search_request(&self, query: &str, _max_results: usize) -> anyhow::Result<HttpRequest> {
+        let accession = parse_uniprot_accession(query)?;
+        Ok(HttpRequest {
+            url: format!("{}/{}", Self::PREDICTION_API_URL, accession),
+            json_body: None,
+            not_found_means_no_results: true,
+        })
+    }
+
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+        let predictions = serde_json::from_slice::<Vec<Prediction>>(body)?;
+        Ok(predictions
+            .into_iter()
+            .map(|prediction| {
+                let version = prediction
+                    .latest_version
+                    .unwrap_or(Self::DEFAULT_MODEL_VERSION);
+                Assembly::predicted_model(&format!("{}-model_v{}", prediction.entry_id, version))
+            })
+            .collect())
+    }
+}
+
+/// Returns the query as a UniProt accession in uppercase (e.g. `P69905` or `A0A024RBG1`), or an error if it does not have the shape of one.
+pub(super) fn parse_uniprot_accession(query: &str) -> anyhow::Result<String> {
+    let accession = query.trim().to_ascii_uppercase();
+    let is_accession = matches!(accession.len(), 6 | 10)
+        && accession.chars().all(|c| c.is_ascii_alphanumeric())
+        && accession.starts_with(|c: char| c.is_ascii_alphabetic())
+        && accession[1..].starts_with(|c: char| c.is_ascii_digit())
+        && accession.ends_with(|c: char| c.is_ascii_digit());
+
+    match is_accession {
+        true => Ok(accession),
+        false => anyhow::bail!("Predicted models are searched by UniProt accession, e.g. P69905"),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Prediction {
+    /// ID of the model without the version, e.g. `AF-P69905-F1`.
+    entry_id: String,
+    latest_version: Option<u32>,
+}
@@ -0,0 +1,323 @@
+//! Cache of downloaded structures, so that assemblies are downloaded only once and can be opened offline.
+//! Files are stored in the platform cache directory on native platforms and in the Cache Storage of the browser on the web.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::data::Assembly;
+
+/// Content of downloaded files keyed by their [`Assembly`], shared by all clones. Once the files exceed the size limit,
+/// the least recently used ones are evicted.
+#[derive(Clone)]
+pub(crate) struct StructureCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+struct CacheState {
+    /// Index of the cached files, `None` until it is read from the storage on first use.
+    index: Option<CacheIndex>,
+    /// Size limit of the cached files in bytes, above which the least recently used ones are evicted.
+    max_size: u64,
+}
+
+/// List of the cached files, stored next to them, as neither platform can cheaply list files with their sizes.
+#[derive(Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: Vec<CacheEntry>,
+    /// Incremented on each use of an entry, so that entries can be ordered by their last use without a clock.
+    use_counter: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    assembly: Assembly,
+    size: u64,
+    last_use: u64,
+}
+
+impl CacheIndex {
+    fn position(&self, assembly: &Assembly) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| &entry.assembly == assembly)
+    }
+
+    fn mark_used(&mut self, index: usize) {
+        self.use_counter += 1;
+        self.entries[index].last_use = self.use_counter;
+    }
+
+    /// Removes the least recently used entries until the files fit into `max_size`. Returns the removed entries.
+    fn evict(&mut self, max_size: u64) -> Vec<CacheEntry> {
+        let mut evicted = Vec::new();
+        while self.entries.iter().map(|entry| entry.size).sum::<u64>() > max_size {
+            let Some(least_recent) = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.last_use)
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            evicted.push(self.entries.remove(least_recent));
+        }
+        evicted
+    }
+}
+
+impl StructureCache {
+    /// Size limit used unless another one is set, in MiB.
+    pub(crate) const DEFAULT_MAX_SIZE_MIB: u64 = 512;
+    const INDEX_KEY: &'static str = "index.json";
+
+    pub(crate) fn new(max_size_mib: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState {
+                index: None,
+                max_size: max_size_mib * 1024 * 1024,
+            })),
+        }
+    }
+
+    /// Sets the size limit in MiB, removing the least recently used files that no longer fit.
+    pub(crate) async fn set_max_size(&self, max_size_mib: u64) {
+        let max_size = max_size_mib * 1024 * 1024;
+        self.state.lock().unwrap().max_size = max_size;
+
+        let evicted = self.with_index(|index| index.evict(max_size)).await;
+        if evicted.is_empty() {
+            return;
+        }
+        for entry in evicted {
+            storage::remove(&file_key(&entry.assembly)).await;
+        }
+        self.save_index().await;
+    }
+
+    /// Returns the cached file of the assembly, or `None` if it is not cached.
+    pub(crate) async fn get(&self, assembly: &Assembly) -> Option<Vec<u8>> {
+        let is_cached = self
+            .with_index(|index| match index.position(assembly) {
+                Some(position) => {
+                    index.mark_used(position);
+                    true
+                }
+                None => false,
+            })
+            .await;
+        if !is_cached {
+            return None;
+        }
+
+        let content = storage::read(&file_key(assembly)).await;
+        if content.is_none() {
+            // The file was removed from the storage, e.g. by the user or the browser.
+            self.with_index(|index| index.entries.retain(|entry| &entry.assembly != assembly))
+                .await;
+        }
+        self.save_index().await;
+        content
+    }
+
+    /// Stores the file of the assembly, evicting the least recently used files if the cache gets too large.
+    /// Files larger than the whole cache are not stored.
+    pub(crate) async fn insert(&self, assembly: &Assembly, content: &[u8]) -> anyhow::Result<()> {
+        let size = content.len() as u64;
+        if size > self.state.lock().unwrap().max_size {
+            return Ok(());
+        }
+
+        storage::write(&file_key(assembly), content).await?;
+        let max_size = self.state.lock().unwrap().max_size;
+        let evicted = self
+            .with_index(|index| {
+                index.entries.retain(|entry| &entry.assembly != assembly);
+                index.entries.push(CacheEntry {
+                    assembly: assembly.clone(),
+                    size,
+                    last_use: 0,
+                });
+                index.mark_used(index.entries.len() - 1);
+                index.evict(max_size)
+            })
+            .await;
+
+        for entry in evicted {
+            storage::remove(&file_key(&entry.assembly)).await;
+        }
+        self.save_index().await;
+        Ok(())
+    }
+
+    /// Removes the cached file of the assembly, e.g. if it cannot be opened anymore.
+    pub(crate) async fn remove(&self, assembly: &Assembly) {
+        self.with_index(|index| index.entries.retain(|entry| &entry.assembly != assembly))
+            .await;
+        storage::remove(&file_key(assembly)).await;
+        self.save_index().await;
+    }
+
+    /// Lists the cached assemblies whose name contains the query (ignoring case), the most recently used first.
+    pub(crate) async fn list(&self, query: &str) -> Vec<Assembly> {
+        let query = query.trim().to_ascii_lowercase();
+        self.with_index(|index| {
+            let mut entries = index
+                .entries
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .assembly
+                        .to_string()
+                        .to_ascii_lowercase()
+                        .contains(&query)
+                })
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_use));
+            entries
+                .into_iter()
+                .map(|entry| entry.assembly.clone())
+                .collect()
+        })
+        .await
+    }
+
+    /// Runs `f` on the index, reading it from the storage first if this is the first use of the cache.
+    /// The lock is not held while the storage is accessed, as the futures of native platforms have to be `Send`.
+    async fn with_index<R>(&self, f: impl FnOnce(&mut CacheIndex) -> R) -> R {
+        let is_loaded = self.state.lock().unwrap().index.is_some();
+        let loaded_index = match is_loaded {
+            true => None,
+            false => Some(
+                storage::read(Self::INDEX_KEY)
+                    .await
+                    .and_then(|content| serde_json::from_slice::<CacheIndex>(&content).ok())
+                    .unwrap_or_default(),
+            ),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let index = match (&mut state.index, loaded_index) {
+            (Some(index), _) => index,
+            (index, loaded_index) => index.insert(loaded_index.unwrap_or_default()),
+        };
+        f(index)
+    }
+
+    /// Writes the index to the storage. Failures are only logged, as the cache then merely forgets some files.
+    async fn save_index(&self) {
+        let content = {
+            let state = self.state.lock().unwrap();
+            let Some(index) = &state.index else {
+                return;
+            };
+            serde_json::to_vec(index).expect("the index is always serializable")
+        };
+        if let Err(error) = storage::write(Self::INDEX_KEY, &content).await {
+            log::warn!("Saving the index of the structure cache failed: {}", error);
+        }
+    }
+}
+
+impl Default for StructureCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_SIZE_MIB)
+    }
+}
+
+/// Returns the key the file of the assembly is stored under, with characters other than those of the IDs replaced.
+fn file_key(assembly: &Assembly) -> String {
+    let name = assembly
+        .to_string()
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect::<String>();
+    format!("{}.bin", name)
+}
+
+/// Files of the cache in the platform cache directory, e.g. `~/.cache/poms/structures` on Linux.
+/// They are accessed with `tokio::fs`, so the runtime threads driving the downloads are not blocked.
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::path::PathBuf;
+
+    fn directory() -> Option<PathBuf> {
+        dirs::cache_dir().map(|directory| directory.join("poms").join("structures"))
+    }
+
+    pub(super) async fn read(key: &str) -> Option<Vec<u8>> {
+        tokio::fs::read(directory()?.join(key)).await.ok()
+    }
+
+    pub(super) async fn write(key: &str, content: &[u8]) -> anyhow::Result<()> {
+        let directory =
+            directory().ok_or_else(|| anyhow::anyhow!("The platform has no cache directory"))?;
+        tokio::fs::create_dir_all(&directory).await?;
+        tokio::fs::write(directory.join(key), content).await?;
+        Ok(())
+    }
+
+    pub(super) async fn remove(key: &str) {
+        if let Some(directory) = directory() {
+            tokio::fs::remove_file(directory.join(key)).await.ok();
+        }
+    }
+}
+
+/// Files of the cache in the Cache Storage of the browser, stored as responses to URLs below the page.
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    const CACHE_NAME: &str = "poms-structures";
+
+    fn url(key: &str) -> String {
+        format!("poms-structure-cache/{}", key)
+    }
+
+    fn js_error(error: JsValue) -> anyhow::Error {
+        anyhow::anyhow!("{:?}", error)
+    }
+
+    async fn open() -> anyhow::Result<web_sys::Cache> {
+        let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("No window"))?;
+        // Cache Storage is only available in secure contexts (HTTPS or localhost).
+        let caches = window.caches().map_err(js_error)?;
+        let cache = JsFuture::from(caches.open(CACHE_NAME))
+            .await
+            .map_err(js_error)?;
+        Ok(cache.unchecked_into())
+    }
+
+    pub(super) async fn read(key: &str) -> Option<Vec<u8>> {
+        let cache = open().await.ok()?;
+        let response = JsFuture::from(cache.match_with_str(&url(key))).await.ok()?;
+        let response = response.dyn_into::<web_sys::Response>().ok()?;
+        let buffer = JsFuture::from(response.array_buffer().ok()?).await.ok()?;
+        Some(js_sys::Uint8Array::new(&buffer).to_vec())
+    }
+
+    pub(super) async fn write(key: &str, content: &[u8]) -> anyhow::Result<()> {
+        let cache = open().await?;
+        let body = js_sys::Uint8Array::from(content);
+        let response =
+            web_sys::Response::new_with_opt_buffer_source(Some(&body)).map_err(js_error)?;
+        JsFuture::from(cache.put_with_str(&url(key), &response))
+            .await
+            .map_err(js_error)?;
+        Ok(())
+    }
+
+    pub(super) async fn remove(key: &str) {
+        if let Ok(cache) = open().await {
+            JsFuture::from(cache.delete_with_str(&url(key))).await.ok();
+        }
+    }
+}
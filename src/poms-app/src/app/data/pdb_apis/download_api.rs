@@ -1,22 +1,110 @@
 use futures::StreamExt;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 
 use crate::app::data::file_loader::{AsyncWorkResult, DownloadProgress};
-use crate::app::data::molecule_parser::parse_multiple_files;
-use crate::app::data::{Assembly, RawFile};
+use crate::app::data::Assembly;
+use poms_io::molecule_parser::{load_options::LoadOptions, parse_multiple_files, ParsedMolecule};
+use poms_io::RawFile;
 
-#[derive(Clone, Default)]
+use super::cache::StructureCache;
+use super::provider::StructureProvider;
+
+#[derive(Clone)]
 pub(crate) struct PdbDownloadApi {
     client: reqwest::Client,
+    provider: Arc<dyn StructureProvider>,
+    cache: StructureCache,
 }
 
 impl PdbDownloadApi {
+    pub(crate) fn new(provider: Arc<dyn StructureProvider>, cache: StructureCache) -> Self {
+        Self {
+            client: reqwest::Client::default(),
+            provider,
+            cache,
+        }
+    }
+
+    /// Opens the assembly from the cache, or downloads it if it is not cached yet. Downloaded files are cached
+    /// once they are parsed successfully. In offline mode, only cached assemblies can be opened.
+    /// Cached files that cannot be parsed (e.g. if they were truncated) are removed and downloaded again.
     pub(crate) async fn download_assembly(
         &self,
         assembly: &Assembly,
+        load_options: LoadOptions,
+        is_offline: bool,
         dispatch: mpsc::Sender<AsyncWorkResult>,
     ) -> anyhow::Result<()> {
-        let url = Self::forge_url(assembly);
+        if let Some(data) = self.cache.get(assembly).await {
+            Self::report_progress(&dispatch, DownloadProgress::Parsing);
+            match Self::parse(assembly, data, load_options) {
+                Ok(molecules) => {
+                    Self::finish(&dispatch, Ok(molecules));
+                    return Ok(());
+                }
+                Err(error) => {
+                    log::warn!("Removing the cached file of {}: {}", assembly, error);
+                    self.cache.remove(assembly).await;
+                    if is_offline {
+                        anyhow::bail!(
+                            "The cached file of {} cannot be opened, downloads are disabled in offline mode: {}",
+                            assembly,
+                            error
+                        );
+                    }
+                }
+            }
+        } else if is_offline {
+            anyhow::bail!(
+                "{} is not cached, downloads are disabled in offline mode",
+                assembly
+            );
+        }
+
+        let data = self.download(assembly, &dispatch).await?;
+        Self::report_progress(&dispatch, DownloadProgress::Parsing);
+        let parsed = Self::parse(assembly, data.clone(), load_options);
+
+        if parsed.is_ok() {
+            // Failing to cache the file does not prevent opening it.
+            if let Err(error) = self.cache.insert(assembly, &data).await {
+                log::warn!("Caching {} failed: {}", assembly, error);
+            }
+        }
+
+        Self::finish(&dispatch, parsed);
+        Ok(())
+    }
+
+    fn parse(
+        assembly: &Assembly,
+        data: Vec<u8>,
+        load_options: LoadOptions,
+    ) -> anyhow::Result<Vec<ParsedMolecule>> {
+        let raw_file = RawFile {
+            name: assembly.to_string(),
+            content: data,
+        };
+        parse_multiple_files(vec![raw_file], load_options)
+    }
+
+    /// Sends the parsed molecules (or the error) to the main thread, once the download is finished.
+    fn finish(
+        dispatch: &mpsc::Sender<AsyncWorkResult>,
+        parsed: anyhow::Result<Vec<ParsedMolecule>>,
+    ) {
+        Self::report_progress(dispatch, DownloadProgress::Finished);
+        dispatch
+            .send(AsyncWorkResult::FilesParsed { result: parsed })
+            .ok();
+    }
+
+    async fn download(
+        &self,
+        assembly: &Assembly,
+        dispatch: &mpsc::Sender<AsyncWorkResult>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let url = self.provider.download_url(assembly);
 
         let response = self
             .client
@@ -24,51 +112,103 @@ impl PdbDownloadApi {
             .header("accept", "application/octet-stream")
             .send()
             .await
-            .map_err(anyhow::Error::new)?;
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|error| {
+                anyhow::anyhow!(
+                    "Downloading {} from {} failed: {}",
+                    assembly,
+                    self.provider.name(),
+                    error
+                )
+            })?;
 
         let mut data = Vec::new();
         let mut bytes_downloaded = 0usize;
         let mut stream = response.bytes_stream();
 
         while let Some(chunk) = stream.next().await {
-            Self::report_progress(
-                &dispatch,
-                DownloadProgress::Downloading { bytes_downloaded },
-            );
+            Self::report_progress(dispatch, DownloadProgress::Downloading { bytes_downloaded });
 
             let chunk = chunk.map_err(anyhow::Error::new)?;
             data.extend_from_slice(&chunk);
             bytes_downloaded += chunk.len();
         }
 
-        Self::report_progress(&dispatch, DownloadProgress::Parsing);
+        Ok(data)
+    }
 
-        let raw_file = RawFile {
-            name: assembly.to_string(),
-            content: data,
-        };
+    fn report_progress(dispatch: &mpsc::Sender<AsyncWorkResult>, progress: DownloadProgress) {
+        dispatch
+            .send(AsyncWorkResult::DownloadProgressed { progress })
+            .ok();
+    }
+}
 
-        let parsed = parse_multiple_files(vec![raw_file]);
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
 
-        Self::report_progress(&dispatch, DownloadProgress::Finished);
+    use super::*;
+    use crate::app::data::pdb_apis::provider::StructureSource;
 
-        dispatch
-            .send(AsyncWorkResult::FilesParsed { result: parsed })
-            .ok();
+    /// Serves `body` to a single request on a local port. Returns the base URL and the path that was requested.
+    fn serve_once(body: &'static [u8]) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let length = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..length]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
 
-        Ok(())
+            let request = String::from_utf8(request).unwrap();
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            path.to_string()
+        });
+        (url, server)
     }
 
-    fn forge_url(assembly: &Assembly) -> String {
-        format!(
-            "https://models.rcsb.org/v1/{}/assembly?name={}&encoding=cif",
-            assembly.pdb_id, assembly.assembly_id
-        )
-    }
+    #[test]
+    fn downloads_assembly_from_mirror() {
+        let (url, server) = serve_once(b"2\nwater fragment\nO 0.0 0.0 0.0\nH 0.96 0.0 0.0\n");
+        let source = StructureSource::Mirror {
+            base_url: format!("{}/pub/pdb/", url),
+            search_url: String::new(),
+        };
+        // A cache without space stores nothing, and the ID is not a real entry, so it is never cached already.
+        let api = PdbDownloadApi::new(source.provider(), StructureCache::new(0));
+        let assembly = Assembly::from_pdb_id("0pom").unwrap();
 
-    fn report_progress(dispatch: &mpsc::Sender<AsyncWorkResult>, progress: DownloadProgress) {
-        dispatch
-            .send(AsyncWorkResult::DownloadProgressed { progress })
-            .ok();
+        let (dispatch, results) = mpsc::channel();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(api.download_assembly(&assembly, LoadOptions::default(), false, dispatch))
+            .unwrap();
+
+        assert_eq!(
+            server.join().unwrap(),
+            "/pub/pdb/data/assemblies/mmCIF/divided/po/0pom-assembly1.cif.gz"
+        );
+        let molecules = results
+            .try_iter()
+            .find_map(|result| match result {
+                AsyncWorkResult::FilesParsed { result } => Some(result),
+                _ => None,
+            })
+            .expect("the parsed molecules are sent")
+            .unwrap();
+        assert_eq!(molecules.len(), 1);
+        assert_eq!(molecules[0].atoms.len(), 2);
     }
 }
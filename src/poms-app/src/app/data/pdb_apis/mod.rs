@@ -1,2 +1,9 @@
+mod alphafold_db;
+pub(crate) mod cache;
 pub(crate) mod download_api;
+mod model_archive;
+mod pdbe;
+pub(crate) mod provider;
+mod rcsb;
 pub(crate) mod search_api;
+mod wwpdb_archive;
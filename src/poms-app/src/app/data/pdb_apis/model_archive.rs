@@ -0,0 +1,66 @@
+use serde::Deserialize;
+
+use super::alphafold_db::parse_uniprot_accession;
+use super::provider::{HttpRequest, StructureProvider};
+use crate::app::data::Assembly;
+
+/// ModelArchive, predicted models are found by UniProt accession through the 3D-Beacons network, which indexes
+/// the models of ModelArchive, and downloaded as ModelCIF by their DOI.
+pub(crate) struct ModelArchive;
+
+impl ModelArchive {
+    const DOI_URL: &'static str = "https://www.modelarchive.org/doi/10.5452";
+    const BEACONS_SUMMARY_URL: &'static str =
+        "https://www.ebi.ac.uk/pdbe/pdbe-kb/3dbeacons/api/uniprot/summary";
+}
+
+impl StructureProvider for ModelArchive {
+    fn name(&self) -> &str {
+        "ModelArchive"
+    }
+
+    fn download_url(&self, assembly: &Assembly) -> String {
+        format!("{}/{}.cif", Self::DOI_URL, assembly.entry_id)
+    }
+
+    fn search_request(&self, query: &str, _max_results: usize) -> anyhow::Result<HttpRequest> {
+        let accession = parse_uniprot_accession(query)?;
+        Ok(HttpRequest {
+            url: format!(
+                "{}/{}.json?provider=modelarchive",
+                Self::BEACONS_SUMMARY_URL,
+                accession
+            ),
+            json_body: None,
+            not_found_means_no_results: true,
+        })
+    }
+
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+        let response = serde_json::from_slice::<BeaconsSummary>(body)?;
+        let mut models: Vec<Assembly> = Vec::new();
+        for structure in response.structures {
+            let model = Assembly::predicted_model(&structure.summary.model_identifier);
+            if !models.contains(&model) {
+                models.push(model);
+            }
+        }
+        Ok(models)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BeaconsSummary {
+    structures: Vec<BeaconsStructure>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BeaconsStructure {
+    summary: BeaconsModel,
+}
+
+#[derive(Debug, Deserialize)]
+struct BeaconsModel {
+    /// ID of the model in ModelArchive, e.g. `ma-bak-cepc-0001`.
+    model_identifier: String,
+}
@@ -0,0 +1,109 @@
+use serde::Deserialize;
+
+use super::provider::{HttpRequest, StructureProvider};
+use super::rcsb::model_server_url;
+use crate::app::data::Assembly;
+
+/// PDBe, assemblies are downloaded from its ModelServer and entries are searched with its Solr search service.
+pub(crate) struct Pdbe;
+
+impl Pdbe {
+    const MODEL_SERVER_URL: &'static str = "https://www.ebi.ac.uk/pdbe/model-server/v1";
+    const SEARCH_URL: &'static str = "https://www.ebi.ac.uk/pdbe/search/pdb/select";
+}
+
+impl StructureProvider for Pdbe {
+    fn name(&self) -> &str {
+        "PDBe"
+    }
+
+    fn download_url(&self, assembly: &Assembly) -> String {
+        model_server_url(Self::MODEL_SERVER_URL, assembly)
+    }
+
+    fn search_request(&self, query: &str, max_results: usize) -> anyhow::Result<HttpRequest> {
+        // The search returns a document per entity, so documents are grouped to get every entry only once.
+        let rows = max_results.to_string();
+        let url = reqwest::Url::parse_with_params(
+            Self::SEARCH_URL,
+            [
+                ("q", query),
+                ("wt", "json"),
+                ("fl", "pdb_id"),
+                ("rows", &rows),
+                ("group", "true"),
+                ("group.field", "pdb_id"),
+                ("group.main", "true"),
+            ],
+        )
+        .expect("the search URL is valid");
+
+        Ok(HttpRequest {
+            url: url.to_string(),
+            json_body: None,
+            not_found_means_no_results: false,
+        })
+    }
+
+    /// PDBe searches entries rather than assemblies, so the first assembly of each found entry is returned.
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+        let response = serde_json::from_slice::<SolrResponse>(body)?;
+        let mut assemblies: Vec<Assembly> = Vec::new();
+        for document in response.response.docs {
+            let assembly = Assembly::from_pdb_id(&document.pdb_id).ok_or_else(|| {
+                anyhow::anyhow!("Invalid PDB ID in the search results: {}", document.pdb_id)
+            })?;
+            if !assemblies.contains(&assembly) {
+                assemblies.push(assembly);
+            }
+        }
+        Ok(assemblies)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SolrResponse {
+    response: SolrDocuments,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolrDocuments {
+    docs: Vec<SolrDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolrDocument {
+    pdb_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_first_assembly_of_each_found_entry() {
+        // Each entity is a document, so an entry may be listed several times.
+        let body = br#"{
+            "responseHeader": {"status": 0, "QTime": 3},
+            "grouped": {"pdb_id": {"matches": 3, "ngroups": 2}},
+            "response": {
+                "numFound": 3,
+                "start": 0,
+                "docs": [{"pdb_id": "4hhb"}, {"pdb_id": "1cqw"}, {"pdb_id": "4hhb"}]
+            }
+        }"#;
+        assert_eq!(
+            Pdbe.parse_search_response(body).unwrap(),
+            [
+                Assembly::from_pdb_id("4HHB").unwrap(),
+                Assembly::from_pdb_id("1CQW").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_pdb_ids() {
+        let body = br#"{"response": {"docs": [{"pdb_id": "hemoglobin"}]}}"#;
+        assert!(Pdbe.parse_search_response(body).is_err());
+    }
+}
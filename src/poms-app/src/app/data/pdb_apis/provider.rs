@@ -0,0 +1,111 @@
+//! Sites structures are searched for and downloaded from. Besides the public wwPDB sites, any mirror of the PDB archive
+//! can be used, e.g. an in-house one or a local HTTP stand-in for testing.
+
+use std::sync::Arc;
+
+use crate::app::data::Assembly;
+
+use super::{alphafold_db, model_archive, pdbe, rcsb, wwpdb_archive};
+
+/// A site that structures are searched for and downloaded from.
+///
+/// Providers only describe the requests and parse the responses, the requests are sent by `PdbSearchApi` and `PdbDownloadApi`.
+pub(crate) trait StructureProvider: Send + Sync {
+    /// Name of the provider shown to the user, e.g. in error messages.
+    fn name(&self) -> &str;
+
+    /// Returns the URL of the file with the assembly. The file may be in any format the molecule parser detects, optionally gzipped.
+    fn download_url(&self, assembly: &Assembly) -> String;
+
+    /// Returns the request of a search for at most `max_results` assemblies, or an error explaining why the query cannot be searched.
+    fn search_request(&self, query: &str, max_results: usize) -> anyhow::Result<HttpRequest>;
+
+    /// Parses the body of the response to the search request into the found assemblies.
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>>;
+}
+
+/// An HTTP request sent to a provider.
+pub(crate) struct HttpRequest {
+    pub(crate) url: String,
+    /// Body of the request in JSON. Requests with a body are sent as `POST`, those without as `GET`.
+    pub(crate) json_body: Option<String>,
+    /// Whether the `404 Not Found` status means that nothing was found, rather than that the URL is wrong.
+    pub(crate) not_found_means_no_results: bool,
+}
+
+/// The provider picked by the user, see [`StructureSource::provider`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) enum StructureSource {
+    /// RCSB PDB, the provider used by default.
+    #[default]
+    Rcsb,
+    /// PDBe, the European site of the wwPDB.
+    Pdbe,
+    /// PDBj, the Japanese site of the wwPDB.
+    Pdbj,
+    /// AlphaFold DB, predicted models of UniProt entries.
+    AlphaFoldDb,
+    /// ModelArchive, predicted models deposited by their authors, found by UniProt accession.
+    ModelArchive,
+    /// A mirror of the PDB archive at `base_url`, laid out like `https://files.wwpdb.org/pub/pdb`.
+    /// Searches are sent to `search_url` in the format of the RCSB Search API, or are not supported if it is empty.
+    Mirror {
+        base_url: String,
+        search_url: String,
+    },
+}
+
+impl StructureSource {
+    pub(crate) const ALL: [StructureSource; 6] = [
+        Self::Rcsb,
+        Self::Pdbe,
+        Self::Pdbj,
+        Self::AlphaFoldDb,
+        Self::ModelArchive,
+        Self::Mirror {
+            base_url: String::new(),
+            search_url: String::new(),
+        },
+    ];
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Rcsb => "RCSB PDB",
+            Self::Pdbe => "PDBe",
+            Self::Pdbj => "PDBj",
+            Self::AlphaFoldDb => "AlphaFold DB",
+            Self::ModelArchive => "ModelArchive",
+            Self::Mirror { .. } => "Mirror",
+        }
+    }
+
+    /// Returns `true` if both sources are of the same kind, regardless of their URLs.
+    pub(crate) fn is_same_kind(&self, other: &StructureSource) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Creates the provider that searches and downloads structures from this source.
+    pub(crate) fn provider(&self) -> Arc<dyn StructureProvider> {
+        match self {
+            Self::Rcsb => Arc::new(rcsb::Rcsb),
+            Self::Pdbe => Arc::new(pdbe::Pdbe),
+            // PDBj offers no search API returning assemblies, but PDB IDs are shared by all wwPDB sites,
+            // so assemblies found by RCSB are downloaded from PDBj.
+            Self::Pdbj => Arc::new(wwpdb_archive::WwpdbArchive::new(
+                "PDBj",
+                "https://data.pdbj.org/pub/pdb",
+                Some(rcsb::Rcsb::SEARCH_URL),
+            )),
+            Self::AlphaFoldDb => Arc::new(alphafold_db::AlphaFoldDb),
+            Self::ModelArchive => Arc::new(model_archive::ModelArchive),
+            Self::Mirror {
+                base_url,
+                search_url,
+            } => Arc::new(wwpdb_archive::WwpdbArchive::new(
+                "the mirror",
+                base_url,
+                Some(search_url.trim()).filter(|url| !url.is_empty()),
+            )),
+        }
+    }
+}
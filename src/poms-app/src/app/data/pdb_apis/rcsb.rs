@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::provider::{HttpRequest, StructureProvider};
+use crate::app::data::Assembly;
+
+/// RCSB PDB, assemblies are downloaded from its ModelServer and searched with its Search API.
+pub(crate) struct Rcsb;
+
+impl Rcsb {
+    pub(crate) const SEARCH_URL: &'static str = "https://search.rcsb.org/rcsbsearch/v2/query";
+    const MODEL_SERVER_URL: &'static str = "https://models.rcsb.org/v1";
+}
+
+impl StructureProvider for Rcsb {
+    fn name(&self) -> &str {
+        "RCSB PDB"
+    }
+
+    fn download_url(&self, assembly: &Assembly) -> String {
+        model_server_url(Self::MODEL_SERVER_URL, assembly)
+    }
+
+    fn search_request(&self, query: &str, max_results: usize) -> anyhow::Result<HttpRequest> {
+        Ok(search_request(Self::SEARCH_URL, query, max_results))
+    }
+
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+        parse_search_response(body)
+    }
+}
+
+/// Returns the URL of the assembly on a ModelServer, which RCSB PDB and PDBe both run.
+/// BinaryCIF is requested, as it is several times smaller than the text mmCIF.
+pub(super) fn model_server_url(base_url: &str, assembly: &Assembly) -> String {
+    format!(
+        "{}/{}/assembly?name={}&encoding=bcif",
+        base_url,
+        assembly.entry_id,
+        assembly.assembly_id.as_deref().unwrap_or("1")
+    )
+}
+
+/// Returns the request of a full-text search with the RCSB Search API at `url`.
+pub(super) fn search_request(url: &str, query: &str, max_results: usize) -> HttpRequest {
+    let generated_id = make_uuid();
+
+    let params = SearchApiRequest {
+        query: Query {
+            query_type: "terminal",
+            service: "full_text",
+            parameters: Parameters { value: query },
+            node_id: 0,
+        },
+        return_type: "assembly",
+        request_info: RequestInfo {
+            query_id: &generated_id,
+            src: "ui",
+        },
+        request_options: RequestOptions {
+            paginate: Paginate {
+                start: 0,
+                rows: max_results,
+            },
+            results_content_type: vec!["experimental"],
+            results_verbosity: "compact",
+        },
+    };
+
+    HttpRequest {
+        url: url.to_string(),
+        json_body: Some(
+            serde_json::to_string(&params).expect("search requests are always serializable"),
+        ),
+        not_found_means_no_results: false,
+    }
+}
+
+/// Parses the response of the RCSB Search API, in which assemblies are listed as `ID-assembly`.
+pub(super) fn parse_search_response(body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+    let response = serde_json::from_slice::<SearchApiResponse>(body)?;
+    response
+        .result_set
+        .into_iter()
+        .map(|result_str| Assembly::from_str(&result_str).map_err(anyhow::Error::msg))
+        .collect::<anyhow::Result<Vec<Assembly>>>()
+}
+
+fn make_uuid() -> String {
+    Uuid::new_v4().to_string().replace('-', "")
+}
+
+#[derive(Debug, Serialize)]
+struct SearchApiRequest<'a> {
+    query: Query<'a>,
+    return_type: &'a str,
+    request_info: RequestInfo<'a>,
+    request_options: RequestOptions<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchApiResponse {
+    result_set: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Query<'a> {
+    #[serde(rename = "type")]
+    query_type: &'a str,
+    service: &'a str,
+    parameters: Parameters<'a>,
+    node_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct Parameters<'a> {
+    value: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestInfo<'a> {
+    query_id: &'a str,
+    src: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestOptions<'a> {
+    paginate: Paginate,
+    results_content_type: Vec<&'a str>,
+    results_verbosity: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct Paginate {
+    start: usize,
+    rows: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_assemblies_of_search_response() {
+        let body = br#"{
+            "query_id": "5ac3ff6b0fb54e9c9c5eb2b7ad0d4d1c",
+            "result_type": "assembly",
+            "total_count": 2,
+            "result_set": ["4HHB-1", "1CQW-2"]
+        }"#;
+        assert_eq!(
+            parse_search_response(body).unwrap(),
+            [
+                Assembly::from_pdb_id("4HHB-1").unwrap(),
+                Assembly::from_pdb_id("1CQW-2").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_search_responses() {
+        assert!(parse_search_response(br#"{"result_set": ["4HHB"]}"#).is_err());
+        assert!(parse_search_response(b"<html>Service Unavailable</html>").is_err());
+    }
+}
@@ -1,37 +1,32 @@
-use std::str::FromStr;
 use std::sync::{mpsc, Arc, Mutex};
 
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-
 use crate::app::data::file_loader::AsyncWorkResult;
 use crate::app::data::Assembly;
 
-#[derive(Debug, Serialize)]
-struct SearchApiRequest<'a> {
-    query: Query<'a>,
-    return_type: &'a str,
-    request_info: RequestInfo<'a>,
-    request_options: RequestOptions<'a>,
-}
-
-#[derive(Debug, Deserialize)]
-struct SearchApiResponse {
-    result_set: Vec<String>,
-}
+use super::provider::StructureProvider;
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub(crate) struct PdbSearchApi {
     client: reqwest::Client,
+    provider: Arc<dyn StructureProvider>,
     /// Remember the last query value to debounce (e.g., to prevent rapid queries)
     last_query_value: Arc<Mutex<Option<String>>>,
 }
 
 impl PdbSearchApi {
     const DEBOUNCE_PERIOD_IN_MS: u32 = 1_000;
-    const SEARCH_API_URL: &'static str = "https://search.rcsb.org/rcsbsearch/v2/query";
     const MAXIMUM_NUMBER_OF_MATCHES: usize = 20;
 
+    pub(crate) fn new(provider: Arc<dyn StructureProvider>) -> Self {
+        Self {
+            client: reqwest::Client::default(),
+            provider,
+            last_query_value: Arc::default(),
+        }
+    }
+
+    /// Searches after the debounce period, unless another search was started meanwhile.
+    /// Both the results and the errors of the search are dispatched.
     pub(crate) async fn fulltext_search_debounced(
         &self,
         value: &str,
@@ -56,104 +51,49 @@ impl PdbSearchApi {
             return Err(anyhow::anyhow!("Query was debounced"));
         }
 
-        let response = self.fulltext_search(&value).await?;
         dispatch
             .send(AsyncWorkResult::SearchResultsParsed {
-                result: Self::parse_search_results(response.result_set),
+                result: self.fulltext_search(&value).await,
             })
             .ok();
 
         Ok(())
     }
 
-    async fn fulltext_search(&self, value: &str) -> anyhow::Result<SearchApiResponse> {
-        let generated_id = Self::make_uuid();
-
-        let params = SearchApiRequest {
-            query: Query {
-                query_type: "terminal",
-                service: "full_text",
-                parameters: Parameters { value },
-                node_id: 0,
-            },
-            return_type: "assembly",
-            request_info: RequestInfo {
-                query_id: &generated_id,
-                src: "ui",
-            },
-            request_options: RequestOptions {
-                paginate: Paginate {
-                    start: 0,
-                    rows: Self::MAXIMUM_NUMBER_OF_MATCHES,
-                },
-                results_content_type: vec!["experimental"],
-                results_verbosity: "compact",
-            },
-        };
-
-        let response = self
-            .client
-            .post(Self::SEARCH_API_URL)
-            .json(&params)
-            .send()
-            .await
-            .map_err(anyhow::Error::new)?;
-
-        if response.status() == reqwest::StatusCode::NO_CONTENT {
-            return Ok(SearchApiResponse {
-                result_set: Vec::new(),
-            });
-        }
-
-        response
-            .json::<SearchApiResponse>()
-            .await
-            .map_err(anyhow::Error::new)
-    }
-
-    fn make_uuid() -> String {
-        Uuid::new_v4().to_string().replace('-', "")
-    }
-
-    fn parse_search_results(results: Vec<String>) -> anyhow::Result<Vec<Assembly>> {
-        results
-            .into_iter()
-            .map(|result_str| Assembly::from_str(&result_str).map_err(anyhow::Error::msg))
-            .collect::<anyhow::Result<Vec<Assembly>>>()
+    /// Discards the search waiting for the end of its debounce period, if any.
+    pub(crate) fn cancel_pending_search(&self) {
+        *self.last_query_value.lock().unwrap() = None;
     }
-}
 
-#[derive(Debug, Serialize)]
-struct Query<'a> {
-    #[serde(rename = "type")]
-    query_type: &'a str,
-    service: &'a str,
-    parameters: Parameters<'a>,
-    node_id: i32,
-}
+    async fn fulltext_search(&self, value: &str) -> anyhow::Result<Vec<Assembly>> {
+        let request = self
+            .provider
+            .search_request(value, Self::MAXIMUM_NUMBER_OF_MATCHES)?;
+        let not_found_means_no_results = request.not_found_means_no_results;
+
+        let request_builder = match request.json_body {
+            Some(body) => self
+                .client
+                .post(&request.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body),
+            None => self.client.get(&request.url),
+        };
 
-#[derive(Debug, Serialize)]
-struct Parameters<'a> {
-    value: &'a str,
-}
+        let response = request_builder.send().await.map_err(anyhow::Error::new)?;
 
-#[derive(Debug, Serialize)]
-struct RequestInfo<'a> {
-    query_id: &'a str,
-    src: &'a str,
-}
+        let status = response.status();
+        if status == reqwest::StatusCode::NO_CONTENT
+            || (status == reqwest::StatusCode::NOT_FOUND && not_found_means_no_results)
+        {
+            return Ok(Vec::new());
+        }
 
-#[derive(Debug, Serialize)]
-struct RequestOptions<'a> {
-    paginate: Paginate,
-    results_content_type: Vec<&'a str>,
-    results_verbosity: &'a str,
-}
+        let response = response.error_for_status().map_err(anyhow::Error::new)?;
 
-#[derive(Debug, Serialize)]
-struct Paginate {
-    start: usize,
-    rows: usize,
+        let body = response.bytes().await.map_err(anyhow::Error::new)?;
+        self.provider.parse_search_response(&body)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -0,0 +1,85 @@
+use super::provider::{HttpRequest, StructureProvider};
+use super::rcsb;
+use crate::app::data::Assembly;
+
+/// A copy of the wwPDB archive, such as the one of PDBj or a mirror, from which assemblies are downloaded as gzipped mmCIF.
+/// Searches are sent to a server speaking the RCSB Search API, if there is one.
+pub(crate) struct WwpdbArchive {
+    name: String,
+    /// URL of the directory containing `data/`, e.g. `https://files.wwpdb.org/pub/pdb`.
+    base_url: String,
+    search_url: Option<String>,
+}
+
+impl WwpdbArchive {
+    pub(crate) fn new(name: &str, base_url: &str, search_url: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            search_url: search_url.map(str::to_string),
+        }
+    }
+}
+
+impl StructureProvider for WwpdbArchive {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Files of the archive are divided into directories by the middle two characters of the lowercase PDB ID,
+    /// e.g. `data/assemblies/mmCIF/divided/cq/1cqw-assembly1.cif.gz`.
+    fn download_url(&self, assembly: &Assembly) -> String {
+        let pdb_id = assembly.entry_id.to_ascii_lowercase();
+        format!(
+            "{}/data/assemblies/mmCIF/divided/{}/{}-assembly{}.cif.gz",
+            self.base_url,
+            pdb_id.get(1..3).unwrap_or_default(),
+            pdb_id,
+            assembly.assembly_id.as_deref().unwrap_or("1")
+        )
+    }
+
+    fn search_request(&self, query: &str, max_results: usize) -> anyhow::Result<HttpRequest> {
+        let url = self.search_url.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("Searching {} requires the URL of a search API", self.name)
+        })?;
+        Ok(rcsb::search_request(url, query, max_results))
+    }
+
+    fn parse_search_response(&self, body: &[u8]) -> anyhow::Result<Vec<Assembly>> {
+        rcsb::parse_search_response(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn download_url_is_divided_by_the_middle_of_the_pdb_id() {
+        let archive = WwpdbArchive::new("PDBj", "https://data.pdbj.org/pub/pdb", None);
+        assert_eq!(
+            archive.download_url(&Assembly::from_pdb_id("1CQW").unwrap()),
+            "https://data.pdbj.org/pub/pdb/data/assemblies/mmCIF/divided/cq/1cqw-assembly1.cif.gz"
+        );
+        assert_eq!(
+            archive.download_url(&Assembly::from_pdb_id("4hhb-2").unwrap()),
+            "https://data.pdbj.org/pub/pdb/data/assemblies/mmCIF/divided/hh/4hhb-assembly2.cif.gz"
+        );
+    }
+
+    #[test]
+    fn base_url_is_trimmed() {
+        let archive = WwpdbArchive::new("the mirror", " http://localhost:8080/pdb/ ", None);
+        assert_eq!(
+            archive.download_url(&Assembly::from_pdb_id("1cqw").unwrap()),
+            "http://localhost:8080/pdb/data/assemblies/mmCIF/divided/cq/1cqw-assembly1.cif.gz"
+        );
+    }
+
+    #[test]
+    fn searching_requires_a_search_url() {
+        let archive = WwpdbArchive::new("the mirror", "http://localhost:8080/pdb", None);
+        assert!(archive.search_request("hemoglobin", 10).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+//! A small atom selection language in the spirit of PyMOL and VMD.
+//!
+//! Examples: `chain A`, `not water`, `resi 40-60 and name CA`, `within 5 of (resname HEM)`.
+//!
+//! Supported keywords:
+//! - `all`, `hetero`, `water`
+//! - `chain <ids>`, `resname <names>`, `name <names>`, `element <symbols>`, each accepting one or more values
+//! - `resi <numbers or ranges>`, e.g. `resi 10 20-30`
+//! - `within <distance> of <selection>`
+//! - `not`, `and`, `or` and parentheses, with `not` binding the tightest and `or` the loosest
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use cgmath::{MetricSpace, Point3};
+use poms_common::models::atom::{Atom, AtomMetadata};
+use poms_io::is_water;
+
+/// A parsed selection expression. Use `Selection::parse` to create one and `Selection::evaluate` to apply it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Selection {
+    All,
+    Chain(Vec<String>),
+    ResidueName(Vec<String>),
+    ResidueNumber(Vec<RangeInclusive<isize>>),
+    AtomName(Vec<String>),
+    Element(Vec<String>),
+    Hetero,
+    Water,
+    Within {
+        distance: f32,
+        selection: Box<Selection>,
+    },
+    Not(Box<Selection>),
+    And(Box<Selection>, Box<Selection>),
+    Or(Box<Selection>, Box<Selection>),
+}
+
+impl Selection {
+    /// Parses a selection expression. Returns an error describing the first problem found in the expression.
+    pub(crate) fn parse(expression: &str) -> anyhow::Result<Selection> {
+        let tokens = tokenize(expression);
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let selection = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(selection),
+            Some(token) => anyhow::bail!("Unexpected `{}` in the selection", token),
+        }
+    }
+
+    /// Evaluates the selection against the atoms, returning a mask with `true` for each selected atom.
+    /// `atoms` and `metadata` have to be in the same order.
+    pub(crate) fn evaluate(&self, atoms: &[Atom], metadata: &[AtomMetadata]) -> Vec<bool> {
+        let matches = |predicate: &dyn Fn(&AtomMetadata) -> bool| -> Vec<bool> {
+            metadata.iter().map(predicate).collect()
+        };
+
+        match self {
+            Self::All => vec![true; metadata.len()],
+            Self::Chain(ids) => matches(&|atom| ids.iter().any(|id| *id == atom.chain_id)),
+            Self::ResidueName(names) => matches(&|atom| {
+                names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&atom.residue_name))
+            }),
+            Self::ResidueNumber(ranges) => matches(&|atom| {
+                ranges
+                    .iter()
+                    .any(|range| range.contains(&atom.residue_number))
+            }),
+            Self::AtomName(names) => matches(&|atom| {
+                names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&atom.atom_name))
+            }),
+            Self::Element(symbols) => matches(&|atom| {
+                atom.element.as_ref().is_some_and(|element| {
+                    symbols
+                        .iter()
+                        .any(|symbol| symbol.eq_ignore_ascii_case(element))
+                })
+            }),
+            Self::Hetero => matches(&|atom| atom.hetero),
+            Self::Water => matches(&|atom| is_water(&atom.residue_name)),
+            Self::Within {
+                distance,
+                selection,
+            } => within(atoms, &selection.evaluate(atoms, metadata), *distance),
+            Self::Not(selection) => selection
+                .evaluate(atoms, metadata)
+                .into_iter()
+                .map(|selected| !selected)
+                .collect(),
+            Self::And(left, right) => left
+                .evaluate(atoms, metadata)
+                .into_iter()
+                .zip(right.evaluate(atoms, metadata))
+                .map(|(left, right)| left && right)
+                .collect(),
+            Self::Or(left, right) => left
+                .evaluate(atoms, metadata)
+                .into_iter()
+                .zip(right.evaluate(atoms, metadata))
+                .map(|(left, right)| left || right)
+                .collect(),
+        }
+    }
+}
+
+/// Selects atoms that are within `distance` of any atom selected by `mask` (including those atoms).
+fn within(atoms: &[Atom], mask: &[bool], distance: f32) -> Vec<bool> {
+    // Bucket the selected atoms into cells of size `distance`, so only the neighboring cells have to be searched.
+    let cell_size = distance.max(f32::EPSILON);
+    let cell_of = |position: [f32; 3]| position.map(|c| (c / cell_size).floor() as i32);
+
+    let mut cells: HashMap<[i32; 3], Vec<Point3<f32>>> = HashMap::new();
+    for (atom, _) in atoms.iter().zip(mask).filter(|(_, &selected)| selected) {
+        cells
+            .entry(cell_of(atom.position))
+            .or_default()
+            .push(Point3::from(atom.position));
+    }
+
+    let distance_squared = distance * distance;
+    atoms
+        .iter()
+        .map(|atom| {
+            let position = Point3::from(atom.position);
+            let [x, y, z] = cell_of(atom.position);
+
+            (-1..=1).any(|dx| {
+                (-1..=1).any(|dy| {
+                    (-1..=1).any(|dz| {
+                        cells.get(&[x + dx, y + dy, z + dz]).is_some_and(|points| {
+                            points
+                                .iter()
+                                .any(|point| point.distance2(position) <= distance_squared)
+                        })
+                    })
+                })
+            })
+        })
+        .collect()
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    // Parentheses are tokens on their own, even if not separated by whitespace.
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+const KEYWORDS: [&str; 12] = [
+    "and", "or", "not", "all", "chain", "resname", "resi", "name", "element", "hetero", "water",
+    "within",
+];
+
+fn is_keyword(token: &str) -> bool {
+    token == "(" || token == ")" || KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(token))
+}
+
+/// A recursive descent parser over the tokens of the expression.
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the given keyword.
+    fn accept(&mut self, keyword: &str) -> bool {
+        let is_match = self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword));
+        if is_match {
+            self.position += 1;
+        }
+        is_match
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Selection> {
+        let mut selection = self.parse_and()?;
+        while self.accept("or") {
+            selection = Selection::Or(Box::new(selection), Box::new(self.parse_and()?));
+        }
+        Ok(selection)
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Selection> {
+        let mut selection = self.parse_unary()?;
+        while self.accept("and") {
+            selection = Selection::And(Box::new(selection), Box::new(self.parse_unary()?));
+        }
+        Ok(selection)
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<Selection> {
+        if self.accept("not") {
+            return Ok(Selection::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.accept("within") {
+            let distance = self
+                .next()
+                .and_then(|token| token.parse::<f32>().ok())
+                .filter(|distance| *distance >= 0.0)
+                .ok_or_else(|| anyhow::anyhow!("Expected a distance after `within`"))?;
+            if !self.accept("of") {
+                anyhow::bail!("Expected `of` after `within {}`", distance);
+            }
+            let selection = Box::new(self.parse_unary()?);
+            return Ok(Selection::Within {
+                distance,
+                selection,
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Selection> {
+        let Some(token) = self.next() else {
+            anyhow::bail!("Unexpected end of the selection");
+        };
+
+        let selection = match token.to_ascii_lowercase().as_str() {
+            "(" => {
+                let selection = self.parse_or()?;
+                if !self.accept(")") {
+                    anyhow::bail!("Missing closing parenthesis");
+                }
+                selection
+            }
+            "all" => Selection::All,
+            "hetero" => Selection::Hetero,
+            "water" => Selection::Water,
+            "chain" => Selection::Chain(self.parse_values(token)?),
+            "resname" => Selection::ResidueName(self.parse_values(token)?),
+            "name" => Selection::AtomName(self.parse_values(token)?),
+            "element" => Selection::Element(self.parse_values(token)?),
+            "resi" => Selection::ResidueNumber(
+                self.parse_values(token)?
+                    .iter()
+                    .map(|value| parse_residue_range(value))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            _ => anyhow::bail!("Unknown keyword `{}`", token),
+        };
+        Ok(selection)
+    }
+
+    /// Parses one or more values following `keyword`, up to the next keyword or parenthesis.
+    fn parse_values(&mut self, keyword: &str) -> anyhow::Result<Vec<String>> {
+        let mut values = Vec::new();
+        while let Some(token) = self.peek().filter(|token| !is_keyword(token)) {
+            values.push(token.to_string());
+            self.position += 1;
+        }
+        match values.is_empty() {
+            true => anyhow::bail!("Expected at least one value after `{}`", keyword),
+            false => Ok(values),
+        }
+    }
+}
+
+/// Parses a residue number (e.g. `42`) or an inclusive range (e.g. `40-60`), negative numbers are allowed.
+fn parse_residue_range(value: &str) -> anyhow::Result<RangeInclusive<isize>> {
+    let parse = |number: &str| {
+        number
+            .parse::<isize>()
+            .map_err(|_| anyhow::anyhow!("Invalid residue number `{}`", value))
+    };
+
+    // Skip the first character when looking for the separator, as it may be a minus sign.
+    match value
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| c == '-')
+        .map(|(i, _)| i)
+    {
+        Some(separator) => Ok(parse(&value[..separator])?..=parse(&value[separator + 1..])?),
+        None => {
+            let number = parse(value)?;
+            Ok(number..=number)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(id: &str) -> Selection {
+        Selection::Chain(vec![id.to_string()])
+    }
+
+    fn not(selection: Selection) -> Selection {
+        Selection::Not(Box::new(selection))
+    }
+
+    fn and(left: Selection, right: Selection) -> Selection {
+        Selection::And(Box::new(left), Box::new(right))
+    }
+
+    fn or(left: Selection, right: Selection) -> Selection {
+        Selection::Or(Box::new(left), Box::new(right))
+    }
+
+    fn parse_error(expression: &str) -> String {
+        Selection::parse(expression).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn not_binds_tightest_and_or_loosest() {
+        assert_eq!(
+            Selection::parse("not chain A and resi 1-5 or water").unwrap(),
+            or(
+                and(not(chain("A")), Selection::ResidueNumber(vec![1..=5])),
+                Selection::Water
+            )
+        );
+    }
+
+    #[test]
+    fn parentheses_group_subexpressions() {
+        assert_eq!(
+            Selection::parse("not (chain A or chain B)").unwrap(),
+            not(or(chain("A"), chain("B")))
+        );
+        assert_eq!(
+            Selection::parse("water and(chain A or hetero)").unwrap(),
+            and(Selection::Water, or(chain("A"), Selection::Hetero))
+        );
+    }
+
+    #[test]
+    fn within_applies_to_the_following_selection() {
+        assert_eq!(
+            Selection::parse("within 5 of (resname HEM or chain B) and water").unwrap(),
+            and(
+                Selection::Within {
+                    distance: 5.0,
+                    selection: Box::new(or(
+                        Selection::ResidueName(vec!["HEM".to_string()]),
+                        chain("B")
+                    )),
+                },
+                Selection::Water
+            )
+        );
+    }
+
+    #[test]
+    fn within_selects_atoms_near_the_selection() {
+        let atoms = (0..4)
+            .map(|i| Atom {
+                position: [i as f32 * 3.0, 0.0, 0.0],
+                radius: 1.0,
+                color: [1.0; 4],
+            })
+            .collect::<Vec<_>>();
+        let metadata = ["A", "B", "B", "B"]
+            .map(|id| AtomMetadata {
+                chain_id: id.to_string(),
+                ..Default::default()
+            })
+            .to_vec();
+
+        let selection = Selection::parse("within 5 of (chain A)").unwrap();
+        assert_eq!(
+            selection.evaluate(&atoms, &metadata),
+            [true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn residue_ranges_accept_negative_numbers() {
+        assert_eq!(
+            Selection::parse("resi -5--1 -3 2 -2-4").unwrap(),
+            Selection::ResidueNumber(vec![-5..=-1, -3..=-3, 2..=2, -2..=4])
+        );
+        assert_eq!(parse_error("resi 1-x"), "Invalid residue number `1-x`");
+    }
+
+    #[test]
+    fn values_end_at_the_next_keyword() {
+        assert_eq!(
+            Selection::parse("chain A B and name CA CB or resname HOH").unwrap(),
+            or(
+                and(
+                    Selection::Chain(vec!["A".to_string(), "B".to_string()]),
+                    Selection::AtomName(vec!["CA".to_string(), "CB".to_string()])
+                ),
+                Selection::ResidueName(vec!["HOH".to_string()])
+            )
+        );
+        assert_eq!(
+            Selection::parse("(element C N)").unwrap(),
+            Selection::Element(vec!["C".to_string(), "N".to_string()])
+        );
+    }
+
+    #[test]
+    fn reports_invalid_expressions() {
+        assert_eq!(parse_error("(chain A"), "Missing closing parenthesis");
+        assert_eq!(
+            parse_error("within 5 chain A"),
+            "Expected `of` after `within 5`"
+        );
+        assert_eq!(
+            parse_error("within of chain A"),
+            "Expected a distance after `within`"
+        );
+        assert_eq!(
+            parse_error("chain and water"),
+            "Expected at least one value after `chain`"
+        );
+        assert_eq!(parse_error("chain A )"), "Unexpected `)` in the selection");
+        assert_eq!(parse_error("all and"), "Unexpected end of the selection");
+        assert_eq!(parse_error("foo"), "Unknown keyword `foo`");
+    }
+}
@@ -1,6 +1,6 @@
 use std::f32::consts::PI;
 
-use cgmath::{InnerSpace, Matrix4, MetricSpace, Point3, Rad, SquareMatrix, Vector3};
+use cgmath::{InnerSpace, Matrix4, MetricSpace, Point2, Point3, Rad, SquareMatrix, Vector3};
 
 use super::mouse_input::MouseInput;
 
@@ -58,6 +58,20 @@ impl CameraController {
             * cgmath::perspective(Rad(Self::FOVY), self.get_aspect(), Self::ZNEAR, Self::ZFAR)
     }
 
+    /// Projects the point onto the screen, in fractions of the screen size from its top left corner.
+    /// Returns `None` if the point is behind the camera.
+    pub(crate) fn project_to_screen(&self, point: Point3<f32>) -> Option<Point2<f32>> {
+        let clip = self.projection_matrix() * self.view_matrix * point.to_homogeneous();
+        // The `w` coordinate is the distance in front of the camera.
+        if clip.w < Self::ZNEAR {
+            return None;
+        }
+        Some(Point2::new(
+            (clip.x / clip.w + 1.0) / 2.0,
+            (1.0 - clip.y / clip.w) / 2.0,
+        ))
+    }
+
     /// Updates the target position the camera is focusing on, adjusting the camera's position accordingly.
     pub(crate) fn set_target(&mut self, target: Point3<f32>) {
         if target.distance(self.target) > Self::DISTANCE_THRESHOLD {
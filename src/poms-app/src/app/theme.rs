@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum ColorTheme {
     Light,
     Dark,
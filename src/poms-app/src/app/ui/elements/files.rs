@@ -22,12 +22,18 @@ pub(crate) fn file_menu(context: &mut egui::Context, state: &mut UIState) {
                         " "
                     };
                     let text = egui::RichText::new(format!("{}{}", prefix, &file.path)).small();
+                    let shortcut_text = match file.trajectory_frames {
+                        Some((_, num_frames)) => {
+                            format!("{} frames, {} atoms", num_frames, file.number_of_atoms)
+                        }
+                        None => format!("{} atoms", file.number_of_atoms),
+                    };
 
                     let button = ui.add_sized(
                         [ui.available_width(), 16.0],
                         egui::Button::new(text)
                             .small()
-                            .shortcut_text(format!("{} atoms", file.number_of_atoms))
+                            .shortcut_text(shortcut_text)
                             .frame(false),
                     );
 
@@ -39,8 +45,16 @@ pub(crate) fn file_menu(context: &mut egui::Context, state: &mut UIState) {
                 }
             });
 
+            load_options_info(ui, state);
+            trajectory_slider(ui, state);
+            assembly_picker(ui, state);
+
             ui.horizontal(|ui| {
-                if ui.button("Open...").clicked() {
+                if ui
+                    .button("Open...")
+                    .on_hover_text("Files can also be dropped onto the window.")
+                    .clicked()
+                {
                     state.dispatch_event(UserEvent::InitOpenFileDialog);
                 }
 
@@ -62,3 +76,70 @@ pub(crate) fn file_menu(context: &mut egui::Context, state: &mut UIState) {
         state.dispatch_event(UserEvent::ChangeActiveMolecule { index });
     }
 }
+
+/// Shows the options the active molecule was loaded with.
+fn load_options_info(ui: &mut egui::Ui, state: &UIState) {
+    let Some(file) = state.files_loaded.get(state.active_file_index) else {
+        return;
+    };
+    ui.label(egui::RichText::new(file.load_options.description()).small());
+    if file.number_of_removed_atoms > 0 {
+        ui.label(
+            egui::RichText::new(format!("{} atoms removed", file.number_of_removed_atoms)).small(),
+        );
+    }
+}
+
+/// Shows a slider for picking the frame of the active molecule, if it is animated by a trajectory.
+fn trajectory_slider(ui: &mut egui::Ui, state: &mut UIState) {
+    let Some((mut frame_index, num_frames)) = state
+        .files_loaded
+        .get(state.active_file_index)
+        .and_then(|file| file.trajectory_frames)
+    else {
+        return;
+    };
+
+    let slider = egui::Slider::new(&mut frame_index, 0..=num_frames - 1).text("Frame");
+    if ui.add(slider).changed() {
+        state.dispatch_event(UserEvent::ChangeTrajectoryFrame { frame_index });
+    }
+}
+
+/// Shows a combo box for switching between the asymmetric unit and the biological assemblies of the active molecule.
+fn assembly_picker(ui: &mut egui::Ui, state: &mut UIState) {
+    let Some(file) = state
+        .files_loaded
+        .get(state.active_file_index)
+        .filter(|file| !file.assemblies.is_empty())
+    else {
+        return;
+    };
+
+    const ASYMMETRIC_UNIT: &str = "Asymmetric unit";
+    let name = |index: Option<usize>| match index {
+        Some(index) => file.assemblies[index].as_str(),
+        None => ASYMMETRIC_UNIT,
+    };
+
+    let mut clicked_assembly = None;
+    egui::ComboBox::from_label("Assembly")
+        .selected_text(name(file.assembly_index))
+        .show_ui(ui, |ui| {
+            let options = std::iter::once(None).chain((0..file.assemblies.len()).map(Some));
+            for assembly_index in options {
+                let is_selected = file.assembly_index == assembly_index;
+                if ui
+                    .selectable_label(is_selected, name(assembly_index))
+                    .clicked()
+                    && !is_selected
+                {
+                    clicked_assembly = Some(assembly_index);
+                }
+            }
+        });
+
+    if let Some(assembly_index) = clicked_assembly {
+        state.dispatch_event(UserEvent::ChangeAssembly { assembly_index });
+    }
+}
@@ -4,7 +4,7 @@ use crate::app::theme::ColorTheme;
 use crate::app::ui::{events::UserEvent, state::UIState};
 
 /// Component that displays the menu bar.
-/// Contains buttons for opening files and changing visual theme.
+/// Contains buttons for opening files, exporting the molecular surface and changing visual theme.
 pub(crate) fn menu_bar(context: &mut egui::Context, state: &mut UIState) {
     TopBottomPanel::top("menu_bar").show(context, |ui| {
         menu::bar(ui, |ui| {
@@ -27,6 +27,9 @@ pub(crate) fn menu_bar(context: &mut egui::Context, state: &mut UIState) {
                 if ui.button("Open").clicked() {
                     state.dispatch_event(UserEvent::InitOpenFileDialog);
                 }
+                if ui.button("Export surface…").clicked() {
+                    state.dispatch_event(UserEvent::InitExportSurfaceDialog);
+                }
             });
         });
     });
@@ -1,10 +1,11 @@
 mod error;
 mod files;
 mod menu;
+mod overlay;
 mod search;
 mod settings;
 
 // Re-export the modules
-pub(crate) use self::{error::*, files::*, menu::*, search::*, settings::*};
+pub(crate) use self::{error::*, files::*, menu::*, overlay::*, search::*, settings::*};
 
 pub(crate) type UiElement = fn(&mut egui::Context, &mut super::UIState);
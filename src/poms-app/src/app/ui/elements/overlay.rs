@@ -0,0 +1,19 @@
+use crate::app::ui::state::UIState;
+
+/// Draws the edges of the unit cell of the active molecule over the rendered scene, if enabled in the settings.
+pub(crate) fn unit_cell_overlay(context: &mut egui::Context, state: &mut UIState) {
+    if !state.is_unit_cell_shown {
+        return;
+    }
+
+    let screen = context.screen_rect();
+    let to_screen =
+        |[x, y]: [f32; 2]| screen.min + egui::vec2(x * screen.width(), y * screen.height());
+    let stroke = egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 196, 64));
+
+    // The background layer is below all windows, but still above the rendered scene.
+    let painter = context.layer_painter(egui::LayerId::background());
+    for &[start, end] in &state.unit_cell_edges {
+        painter.line_segment([to_screen(start), to_screen(end)], stroke);
+    }
+}
@@ -1,7 +1,7 @@
 use egui::{RichText, Widget, Window};
 
 use crate::app::{
-    data::{file_loader::DownloadProgress, Assembly},
+    data::{file_loader::DownloadProgress, pdb_apis::provider::StructureSource, Assembly},
     ui::{events::UserEvent, state::UIState},
 };
 
@@ -17,8 +17,21 @@ pub(crate) fn search(context: &mut egui::Context, state: &mut UIState) {
         .default_size([250., 100.])
         .show(context, |ui| {
             ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                structure_source(ui, state);
+                offline_mode(ui, state);
+
+                let hint_text = match &state.structure_source {
+                    _ if state.is_offline => "Filter cached structures..".to_string(),
+                    StructureSource::Mirror { .. } => {
+                        "Search and download data from the mirror..".to_string()
+                    }
+                    StructureSource::AlphaFoldDb | StructureSource::ModelArchive => {
+                        "Search predicted models by UniProt accession..".to_string()
+                    }
+                    source => format!("Search and download data from {}..", source.name()),
+                };
                 let search_bar = egui::TextEdit::singleline(&mut state.search_term)
-                    .hint_text("Search and download data from rcsb.org..")
+                    .hint_text(hint_text)
                     .desired_width(f32::INFINITY)
                     .ui(ui);
 
@@ -27,20 +40,33 @@ pub(crate) fn search(context: &mut egui::Context, state: &mut UIState) {
                         m.request_focus(search_bar.id);
                     });
                     state.is_search_first_time_rendered = false;
+                    // Cached structures are listed right away, as they can be browsed without a query.
+                    if state.is_offline {
+                        init_search(state);
+                    }
                 }
 
-                if search_bar.changed() && !state.search_term.is_empty() {
-                    state.is_search_in_progress = true;
-                    state.dispatch_event(UserEvent::InitMoleculeSearch {
-                        query: state.search_term.clone(),
-                    })
+                if search_bar.changed() && (!state.search_term.is_empty() || state.is_offline) {
+                    init_search(state);
                 };
 
                 egui::Separator::default().spacing(6.0).ui(ui);
 
-                if !state.search_term.is_empty() && state.is_search_in_progress {
+                if state.is_search_in_progress
+                    && (!state.search_term.is_empty() || state.is_offline)
+                {
                     ui.label("Searching...");
                     egui::Separator::default().spacing(3.0).ui(ui);
+                } else if let Some(error) = state
+                    .search_error
+                    .as_ref()
+                    .filter(|_| !state.search_term.is_empty())
+                {
+                    ui.label(error);
+                    egui::Separator::default().spacing(3.0).ui(ui);
+                } else if state.search_results.is_empty() && state.is_offline {
+                    ui.label("No cached structures found.");
+                    egui::Separator::default().spacing(3.0).ui(ui);
                 } else if state.search_results.is_empty() && !state.search_term.is_empty() {
                     ui.label("No results found.");
                     egui::Separator::default().spacing(3.0).ui(ui);
@@ -94,3 +120,75 @@ pub(crate) fn search(context: &mut egui::Context, state: &mut UIState) {
         state.dispatch_event(UserEvent::InitDownloadMolecule { assembly });
     }
 }
+
+fn init_search(state: &mut UIState) {
+    state.is_search_in_progress = true;
+    state.dispatch_event(UserEvent::InitMoleculeSearch {
+        query: state.search_term.clone(),
+    })
+}
+
+/// Toggle of the offline mode. Going offline replaces the results with the cached structures matching the search term.
+fn offline_mode(ui: &mut egui::Ui, state: &mut UIState) {
+    if ui
+        .checkbox(&mut state.is_offline, "Offline (cached structures only)")
+        .changed()
+    {
+        state.search_results.clear();
+        state.search_error = None;
+        state.is_search_in_progress = false;
+        state.dispatch_event(UserEvent::ToggleOfflineMode {
+            is_offline: state.is_offline,
+        });
+        if state.is_offline {
+            init_search(state);
+        }
+    }
+}
+
+/// Picker of the site structures are searched for and downloaded from. Mirrors are only used once their URLs are applied,
+/// so that they are not contacted while the URLs are being typed.
+fn structure_source(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::ComboBox::from_label("Source")
+        .selected_text(state.structure_source.name())
+        .show_ui(ui, |ui| {
+            for source in StructureSource::ALL {
+                let is_selected = state.structure_source.is_same_kind(&source);
+                if ui.selectable_label(is_selected, source.name()).clicked() && !is_selected {
+                    // Results of other sources may not be available from the new one.
+                    state.search_results.clear();
+                    state.search_error = None;
+                    if !matches!(source, StructureSource::Mirror { .. }) {
+                        state.dispatch_event(UserEvent::ChangeStructureSource {
+                            source: source.clone(),
+                        });
+                    }
+                    state.structure_source = source;
+                }
+            }
+        });
+
+    if let StructureSource::Mirror {
+        base_url,
+        search_url,
+    } = &mut state.structure_source
+    {
+        egui::TextEdit::singleline(base_url)
+            .hint_text("Base URL, e.g. https://files.wwpdb.org/pub/pdb")
+            .desired_width(f32::INFINITY)
+            .ui(ui);
+        egui::TextEdit::singleline(search_url)
+            .hint_text("Search URL (optional, RCSB Search API)")
+            .desired_width(f32::INFINITY)
+            .ui(ui);
+
+        let is_valid = base_url.starts_with("http://") || base_url.starts_with("https://");
+        if ui
+            .add_enabled(is_valid, egui::Button::new("Apply"))
+            .clicked()
+        {
+            let source = state.structure_source.clone();
+            state.dispatch_event(UserEvent::ChangeStructureSource { source });
+        }
+    }
+}
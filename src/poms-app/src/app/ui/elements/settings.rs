@@ -4,7 +4,12 @@ use poms_common::limits::{
     MIN_DISTANCE_FIELD_RESOLUTION, MIN_PROBE_RADIUS,
 };
 
+use crate::app::data::color_scheme::ColorScheme;
+use crate::app::data::selection::Selection;
 use crate::app::ui::{events::UserEvent, UIState};
+use poms_io::crystal::CrystalPacking;
+use poms_io::molecule_parser::load_options::AltLocPolicy;
+use poms_io::radius_set::RadiusSet;
 
 /// Component that displays settings window.
 /// Allows to change model parameters and toggle render passes.
@@ -26,6 +31,12 @@ pub(crate) fn settings(context: &mut egui::Context, state: &mut UIState) {
                     ui.separator();
                 });
 
+            radius_set_settings(ui, state);
+            color_scheme_settings(ui, state);
+            selection_settings(ui, state);
+            crystal_settings(ui, state);
+            load_settings(ui, state);
+
             animation_controls(ui, state);
         });
 }
@@ -180,6 +191,180 @@ fn ssao_render_settings(ui: &mut egui::Ui, state: &mut UIState) {
         });
 }
 
+fn radius_set_settings(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::CollapsingHeader::new("Radii")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ComboBox::from_label("Radius set")
+                .selected_text(state.radius_set.name())
+                .show_ui(ui, |ui| {
+                    for radius_set in RadiusSet::ALL {
+                        if ui
+                            .selectable_value(&mut state.radius_set, radius_set, radius_set.name())
+                            .clicked()
+                        {
+                            state.dispatch_event(UserEvent::ChangeRadiusSet { radius_set });
+                        }
+                    }
+                });
+        });
+}
+
+fn color_scheme_settings(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::CollapsingHeader::new("Coloring")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ComboBox::from_label("Color scheme")
+                .selected_text(state.color_scheme.name())
+                .show_ui(ui, |ui| {
+                    for color_scheme in ColorScheme::ALL {
+                        let is_selected = state.color_scheme.is_same_kind(&color_scheme);
+                        if ui
+                            .selectable_label(is_selected, color_scheme.name())
+                            .clicked()
+                            && !is_selected
+                        {
+                            state.color_scheme = color_scheme;
+                            state.dispatch_event(UserEvent::ChangeColorScheme { color_scheme });
+                        }
+                    }
+                });
+
+            // The uniform scheme additionally allows to pick the color.
+            if let ColorScheme::Uniform(mut color) = state.color_scheme {
+                ui.horizontal(|ui| {
+                    ui.label("Color");
+                    if ui.color_edit_button_rgb(&mut color).changed() {
+                        state.color_scheme = ColorScheme::Uniform(color);
+                        state.dispatch_event(UserEvent::ChangeColorScheme {
+                            color_scheme: state.color_scheme,
+                        });
+                    }
+                });
+            }
+        });
+}
+
+fn selection_settings(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::CollapsingHeader::new("Selection")
+        .default_open(true)
+        .show(ui, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut state.selection_expression)
+                    .hint_text("e.g. chain A and not water"),
+            );
+            let is_submitted =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() || is_submitted {
+                    apply_selection(state);
+                }
+                if ui.button("Clear").clicked() {
+                    state.selection_expression.clear();
+                    apply_selection(state);
+                }
+            });
+        });
+}
+
+/// Parses the selection expression and dispatches the selection, or displays an error if the expression is invalid.
+fn apply_selection(state: &mut UIState) {
+    let expression = state.selection_expression.trim();
+    if expression.is_empty() {
+        state.dispatch_event(UserEvent::ChangeSelection { selection: None });
+        return;
+    }
+
+    match Selection::parse(expression) {
+        Ok(selection) => state.dispatch_event(UserEvent::ChangeSelection {
+            selection: Some(selection),
+        }),
+        Err(error) => state.open_error_message(format!("Invalid selection: {}", error)),
+    }
+}
+
+fn crystal_settings(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::CollapsingHeader::new("Crystal")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::ComboBox::from_label("Packing")
+                .selected_text(state.crystal_packing.name())
+                .show_ui(ui, |ui| {
+                    for crystal_packing in CrystalPacking::ALL {
+                        let is_selected = state.crystal_packing.is_same_kind(&crystal_packing);
+                        if ui
+                            .selectable_label(is_selected, crystal_packing.name())
+                            .clicked()
+                            && !is_selected
+                        {
+                            state.crystal_packing = crystal_packing;
+                            state.dispatch_event(UserEvent::ChangeCrystalPacking {
+                                crystal_packing,
+                            });
+                        }
+                    }
+                });
+
+            let response =
+                match &mut state.crystal_packing {
+                    CrystalPacking::None => None,
+                    CrystalPacking::UnitCells { count } => Some(ui.add(
+                        Slider::new(count, 1..=CrystalPacking::MAX_UNIT_CELLS).text("Unit cells"),
+                    )),
+                    CrystalPacking::Radius { radius } => Some(ui.add(
+                        Slider::new(radius, 1.0..=CrystalPacking::MAX_RADIUS).text("Radius (Å)"),
+                    )),
+                };
+            // Packing the crystal takes a while, so it is only repacked once the slider is released.
+            if response.is_some_and(|response| {
+                response.drag_stopped() || (response.changed() && !response.dragged())
+            }) {
+                state.dispatch_event(UserEvent::ChangeCrystalPacking {
+                    crystal_packing: state.crystal_packing,
+                });
+            }
+
+            ui.add(Checkbox::new(
+                &mut state.is_unit_cell_shown,
+                "Show unit cell",
+            ));
+        });
+}
+
+fn load_settings(ui: &mut egui::Ui, state: &mut UIState) {
+    egui::CollapsingHeader::new("Loading")
+        .default_open(false)
+        .show(ui, |ui| {
+            let mut load_options = state.load_options;
+            egui::ComboBox::from_label("Alt-locs")
+                .selected_text(load_options.alt_loc_policy.name())
+                .show_ui(ui, |ui| {
+                    for alt_loc_policy in AltLocPolicy::ALL {
+                        ui.selectable_value(
+                            &mut load_options.alt_loc_policy,
+                            alt_loc_policy,
+                            alt_loc_policy.name(),
+                        );
+                    }
+                });
+            ui.add(Slider::new(&mut load_options.min_occupancy, 0.0..=1.0).text("Min. occupancy"));
+            ui.checkbox(&mut load_options.remove_hydrogens, "Remove hydrogens");
+            ui.checkbox(&mut load_options.remove_waters, "Remove waters");
+            ui.checkbox(&mut load_options.remove_ions, "Remove ions");
+            ui.checkbox(
+                &mut load_options.remove_buffer_ligands,
+                "Remove buffer ligands",
+            );
+
+            if load_options != state.load_options {
+                state.load_options = load_options;
+                state.dispatch_event(UserEvent::ChangeLoadOptions { load_options });
+            }
+            ui.label(egui::RichText::new("Applies to files opened afterwards.").small());
+        });
+}
+
 fn animation_controls(ui: &mut egui::Ui, state: &mut UIState) {
     egui::CollapsingHeader::new("Animation")
         .default_open(false)
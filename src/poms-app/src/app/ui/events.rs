@@ -1,14 +1,26 @@
 use poms_render::PostprocessSettings;
 
-use crate::app::data::molecule_parser::ParsedMolecule;
+use crate::app::data::color_scheme::ColorScheme;
+use crate::app::data::pdb_apis::provider::StructureSource;
+use crate::app::data::selection::Selection;
 use crate::app::data::Assembly;
 use crate::app::theme::ColorTheme;
+use poms_io::crystal::CrystalPacking;
+use poms_io::molecule_parser::load_options::LoadOptions;
+use poms_io::molecule_parser::ParsedMolecule;
+use poms_io::radius_set::RadiusSet;
 
 /// Represents an event that is triggered by the user interacting with the UI.
 pub(crate) enum UserEvent {
     /// User clicks on a file in the file menu.
     ChangeActiveMolecule { index: usize },
 
+    /// User moves the slider of the trajectory frames of the active molecule.
+    ChangeTrajectoryFrame { frame_index: usize },
+
+    /// User picks a biological assembly of the active molecule, `None` stands for the asymmetric unit.
+    ChangeAssembly { assembly_index: Option<usize> },
+
     /// User changes the speed of the animation using a slider.
     ChangeAnimationSpeed { speed: u32 },
 
@@ -18,6 +30,21 @@ pub(crate) enum UserEvent {
     /// User changes the radius of the probe used for molecular surface rendering.
     ChangeProbeRadius { probe_radius: f32 },
 
+    /// User picks a different set of atomic radii.
+    ChangeRadiusSet { radius_set: RadiusSet },
+
+    /// User changes how crystal structures are packed with their symmetry mates.
+    ChangeCrystalPacking { crystal_packing: CrystalPacking },
+
+    /// User picks a different color scheme or changes its parameters (e.g. the uniform color).
+    ChangeColorScheme { color_scheme: ColorScheme },
+
+    /// User applies a new selection expression. `None` means that all atoms are selected.
+    ChangeSelection { selection: Option<Selection> },
+
+    /// User changes which atoms are loaded from files opened afterwards (e.g. which alternate locations).
+    ChangeLoadOptions { load_options: LoadOptions },
+
     /// User changes the visibility of the molecular surface pass.
     ChangeRenderMolecularSurface { is_enabled: bool },
 
@@ -30,12 +57,21 @@ pub(crate) enum UserEvent {
     /// User clicks the "Open file" button in the UI. Opens a file dialog.
     InitOpenFileDialog,
 
+    /// User clicks the "Export surface…" button in the menu. Opens a save file dialog.
+    InitExportSurfaceDialog,
+
     /// User changes the input field in the search bar and initiates a search for PDB files.
     InitMoleculeSearch { query: String },
 
     /// User clicks on a molecule in the search results. Initiates the download of the selected PDB file.
     InitDownloadMolecule { assembly: Assembly },
 
+    /// User picks the site structures are searched for and downloaded from.
+    ChangeStructureSource { source: StructureSource },
+
+    /// User toggles the offline mode, in which structures are only opened from the cache and searches list the cached ones.
+    ToggleOfflineMode { is_offline: bool },
+
     /// User toggles the animation of multiple molecule frames.
     ToggleAnimation,
 
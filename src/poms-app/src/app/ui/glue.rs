@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use super::{elements::UiElement, UIState};
+use crate::app::theme::ColorTheme;
 use crate::gpu_context::GpuContext;
 
 /// Serves as a glue between `winit`, `wgpu`, and `egui`, providing an abstraction used by the UI system.
@@ -46,6 +47,14 @@ impl EguiWrapper {
         self.end_frame();
     }
 
+    /// Switches between the dark and light visuals of `egui`.
+    pub(crate) fn set_theme(&mut self, theme: ColorTheme) {
+        self.egui_handle.set_visuals(match theme {
+            ColorTheme::Dark => egui::Visuals::dark(),
+            ColorTheme::Light => egui::Visuals::light(),
+        });
+    }
+
     /// Handles window events, forwarding them to `egui`. Returns `true` if the event was consumed.
     pub(crate) fn handle_window_event(&mut self, event: &winit::event::WindowEvent) -> bool {
         self.egui_winit_state
@@ -8,8 +8,10 @@ use winit::event::WindowEvent;
 
 use super::data::file_loader::{AsyncWorkResult, DownloadProgress, FileLoader};
 use super::data::molecule_storage::MoleculeData;
+use super::theme::ColorTheme;
 use crate::gpu_context::GpuContext;
 use events::UserEvent;
+use poms_io::crystal::CrystalPacking;
 use state::{MoleculeFileInfo, UIState};
 
 /// Primary struct for managing and rendering the application's UI and I/O.
@@ -41,6 +43,7 @@ impl UserInterface {
         self.egui_wrapper.add_elements(
             &mut self.state,
             &[
+                elements::unit_cell_overlay,
                 elements::menu_bar,
                 elements::settings,
                 elements::error_messages,
@@ -65,6 +68,11 @@ impl UserInterface {
         self.egui_wrapper.render(context, view, encoder);
     }
 
+    /// Switches the visuals of the UI to the theme, e.g. when the app is launched with a light theme.
+    pub(crate) fn set_theme(&mut self, theme: ColorTheme) {
+        self.egui_wrapper.set_theme(theme);
+    }
+
     /// Handles a window event, routing them to the `egui` wrapper. Returns `true` if the event was consumed.
     pub(crate) fn handle_window_event(&mut self, window_event: &WindowEvent) -> bool {
         self.egui_wrapper.handle_window_event(window_event)
@@ -83,12 +91,41 @@ impl UserInterface {
                 index: i,
                 path: file.filename.clone(),
                 number_of_atoms: file.atoms.data.len(),
+                trajectory_frames: file.trajectory_frames(),
+                assemblies: file
+                    .assemblies
+                    .iter()
+                    .map(|assembly| assembly.name.clone())
+                    .collect(),
+                assembly_index: file.assembly_index,
+                load_options: file.load_options,
+                number_of_removed_atoms: file.num_removed_atoms,
             })
             .collect();
 
         self.state.active_file_index = active_index;
     }
 
+    /// Displays an error message to the user.
+    pub(crate) fn open_error_message(&mut self, message: String) {
+        self.state.open_error_message(message);
+    }
+
+    /// Marks the animation as stopped, e.g. when it could not continue.
+    pub(crate) fn stop_animation(&mut self) {
+        self.state.is_animation_active = false;
+    }
+
+    /// Sets the displayed packing of crystal structures, e.g. when the picked one could not be applied.
+    pub(crate) fn set_crystal_packing(&mut self, crystal_packing: CrystalPacking) {
+        self.state.crystal_packing = crystal_packing;
+    }
+
+    /// Updates the edges of the unit cell drawn over the molecule, in fractions of the screen size.
+    pub(crate) fn update_unit_cell_edges(&mut self, edges: Vec<[[f32; 2]; 2]>) {
+        self.state.unit_cell_edges = edges;
+    }
+
     /// Updates the displayed progress of the compute pipeline.
     pub(crate) fn update_compute_progress(&mut self, progress: Option<ComputeProgress>) {
         self.state.compute_progress = progress;
@@ -108,11 +145,14 @@ impl UserInterface {
                 AsyncWorkResult::SearchResultsParsed { result: results } => match results {
                     Ok(search_results) => {
                         self.state.search_results = search_results;
+                        self.state.search_error = None;
                         self.state.is_search_in_progress = false;
                     }
                     Err(error) => {
+                        log::error!("Search failed: {}", error);
                         self.state.search_results = vec![];
-                        eprintln!("Search failed: {}", error);
+                        self.state.search_error = Some(format!("Search failed: {}", error));
+                        self.state.is_search_in_progress = false;
                     }
                 },
                 AsyncWorkResult::DownloadProgressed { progress } => {
@@ -122,6 +162,17 @@ impl UserInterface {
                         _ => Some(progress),
                     };
                 }
+                AsyncWorkResult::DownloadFailed { error } => {
+                    self.state.download_progress = None;
+                    self.state
+                        .open_error_message(format!("Download failed: {}", error));
+                }
+                AsyncWorkResult::SurfaceExported { result } => match result {
+                    Ok(file_name) => log::info!("Surface exported to {}", file_name),
+                    Err(error) => self
+                        .state
+                        .open_error_message(format!("Export failed: {}", error)),
+                },
             }
         }
     }
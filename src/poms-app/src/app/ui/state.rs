@@ -1,5 +1,9 @@
 use super::events::UserEvent;
-use crate::app::data::{file_loader::DownloadProgress, Assembly};
+use crate::app::data::pdb_apis::provider::StructureSource;
+use crate::app::data::{color_scheme::ColorScheme, file_loader::DownloadProgress, Assembly};
+use poms_io::crystal::CrystalPacking;
+use poms_io::molecule_parser::load_options::LoadOptions;
+use poms_io::radius_set::RadiusSet;
 
 /// Struct that represents an error message that should be displayed to the user.
 pub(crate) struct ErrorMessage {
@@ -15,6 +19,16 @@ pub(crate) struct MoleculeFileInfo {
     pub(crate) path: String,
     /// N.o. of atoms in the molecule parsed from the file.
     pub(crate) number_of_atoms: usize,
+    /// Current frame and the n.o. of frames, if the molecule is animated by a trajectory.
+    pub(crate) trajectory_frames: Option<(usize, usize)>,
+    /// Names of the biological assemblies defined in the file.
+    pub(crate) assemblies: Vec<String>,
+    /// Index of the displayed assembly, `None` for the asymmetric unit.
+    pub(crate) assembly_index: Option<usize>,
+    /// Options the atoms were filtered by when the file was loaded.
+    pub(crate) load_options: LoadOptions,
+    /// Number of atoms of the file removed by `load_options`.
+    pub(crate) number_of_removed_atoms: usize,
 }
 
 /// Struct that holds current state of the UI.
@@ -31,6 +45,21 @@ pub(crate) struct UIState {
     pub(crate) render_molecular_surface: bool,
     /// Settings for postprocessing effects.
     pub(crate) postprocess_settings: poms_render::PostprocessSettings,
+    /// Set of atomic radii used for both spacefill and molecular surface.
+    pub(crate) radius_set: RadiusSet,
+    /// Color scheme used for both spacefill and molecular surface.
+    pub(crate) color_scheme: ColorScheme,
+    /// State of the input field with the selection expression.
+    pub(crate) selection_expression: String,
+    /// Packing of crystal structures with their symmetry mates.
+    pub(crate) crystal_packing: CrystalPacking,
+    /// Flag that indicates if the edges of the unit cell are drawn over the molecule.
+    pub(crate) is_unit_cell_shown: bool,
+    /// Edges of the unit cell of the active molecule projected to the screen, in fractions of the screen size.
+    pub(crate) unit_cell_edges: Vec<[[f32; 2]; 2]>,
+
+    /// Options applied to the atoms of files opened from now on.
+    pub(crate) load_options: LoadOptions,
 
     /// Flag that indicates if animation is active.
     pub(crate) is_animation_active: bool,
@@ -52,6 +81,12 @@ pub(crate) struct UIState {
     pub(crate) is_search_window_shown: bool,
     /// Flag that indicates if a search is currently in progress.
     pub(crate) is_search_in_progress: bool,
+    /// Error of the last search, shown instead of the results.
+    pub(crate) search_error: Option<String>,
+    /// Site structures are searched for and downloaded from, edited in the search window.
+    pub(crate) structure_source: StructureSource,
+    /// Flag that indicates if structures are only opened from the cache, and the search lists the cached ones.
+    pub(crate) is_offline: bool,
     /// Flag used for forcing focus to the search bar.
     pub(crate) is_search_first_time_rendered: bool,
 
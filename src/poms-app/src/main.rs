@@ -3,34 +3,41 @@ mod gpu_context;
 
 use std::sync::Arc;
 
+use winit::dpi::LogicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::EventLoop;
 use winit::window::{Window, WindowBuilder};
 
+use app::cli::LaunchOptions;
 use app::App;
 use gpu_context::GpuContext;
 
 fn main() {
+    // Browsers pass no arguments, so the web version always starts with the defaults.
+    let options = LaunchOptions::from_env();
     let event_loop = EventLoop::new().expect("Failed to create event loop");
 
-    let window = WindowBuilder::new()
-        .with_title("POMS")
+    let mut window_builder = WindowBuilder::new().with_title("POMS");
+    if let Some((width, height)) = options.window_size {
+        window_builder = window_builder.with_inner_size(LogicalSize::new(width, height));
+    }
+    let window = window_builder
         .build(&event_loop)
         .expect("Failed to create window");
 
     #[cfg(not(target_arch = "wasm32"))]
     {
         simple_logger::init_with_level(log::Level::Info).expect("Failed to initialize logger");
-        futures::executor::block_on(run_loop(event_loop, window));
+        futures::executor::block_on(run_loop(event_loop, window, options));
     }
     #[cfg(target_arch = "wasm32")]
     {
         wasm::init_browser_window(&window);
-        wasm_bindgen_futures::spawn_local(run_loop(event_loop, window));
+        wasm_bindgen_futures::spawn_local(run_loop(event_loop, window, options));
     }
 }
 
-async fn run_loop(event_loop: EventLoop<()>, window: Window) {
+async fn run_loop(event_loop: EventLoop<()>, window: Window, options: LaunchOptions) {
     let window = Arc::new(window);
 
     let context = match GpuContext::initialize(window.clone()).await {
@@ -43,12 +50,17 @@ async fn run_loop(event_loop: EventLoop<()>, window: Window) {
         }
     };
 
-    let mut app = App::new(context);
+    let mut app = App::new(context, options);
 
     event_loop
         .run(|event, elwt| {
             #[cfg(target_arch = "wasm32")]
-            wasm::resize_app_if_canvas_changed(&window, &mut app);
+            {
+                wasm::resize_app_if_canvas_changed(&window, &mut app);
+                for file in wasm::take_dropped_files() {
+                    app.drop_file(file);
+                }
+            }
 
             match event {
                 Event::WindowEvent { event, .. } if !app.handle_window_event(&event) => match event
@@ -58,6 +70,7 @@ async fn run_loop(event_loop: EventLoop<()>, window: Window) {
                     WindowEvent::RedrawRequested => {
                         app.redraw();
                     }
+                    WindowEvent::DroppedFile(path) => app.drop_file(path),
                     _ => {}
                 },
                 Event::DeviceEvent { event, .. } => app.handle_device_event(&event),
@@ -72,10 +85,19 @@ async fn run_loop(event_loop: EventLoop<()>, window: Window) {
 /// This module contains utilities specific to the browser environment.
 #[cfg(target_arch = "wasm32")]
 mod wasm {
+    use std::cell::RefCell;
+
     use super::app::App;
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
     use winit::platform::web::WindowExtWebSys;
     use winit::window::Window;
 
+    thread_local! {
+        /// Files dropped onto the canvas that have not been passed to the app yet.
+        static DROPPED_FILES: RefCell<Vec<web_sys::File>> = const { RefCell::new(Vec::new()) };
+    }
+
     /// Initialize the browser window by setting up logging and creating a canvas element.
     pub(crate) fn init_browser_window(window: &Window) {
         // Log detailed error info to browser's dev console
@@ -89,6 +111,7 @@ mod wasm {
             .set_attribute("style", "width: 100%; height: 100%;")
             .unwrap();
 
+        listen_to_dropped_files(&canvas);
         append_node_to_body(&web_sys::Element::from(canvas));
 
         log::info!("Initialized browser window");
@@ -121,6 +144,38 @@ mod wasm {
         }
     }
 
+    /// Collects files dropped onto the canvas, which winit does not report on the web.
+    fn listen_to_dropped_files(canvas: &web_sys::HtmlCanvasElement) {
+        // Browsers open dropped files in the tab, unless the default action of both events is prevented.
+        let on_drag_over = Closure::<dyn FnMut(_)>::new(|event: web_sys::DragEvent| {
+            event.prevent_default();
+        });
+        let on_drop = Closure::<dyn FnMut(_)>::new(|event: web_sys::DragEvent| {
+            event.prevent_default();
+            let Some(files) = event.data_transfer().and_then(|transfer| transfer.files()) else {
+                return;
+            };
+            DROPPED_FILES.with_borrow_mut(|dropped_files| {
+                dropped_files.extend((0..files.length()).filter_map(|i| files.get(i)));
+            });
+        });
+
+        canvas
+            .add_event_listener_with_callback("dragover", on_drag_over.as_ref().unchecked_ref())
+            .unwrap();
+        canvas
+            .add_event_listener_with_callback("drop", on_drop.as_ref().unchecked_ref())
+            .unwrap();
+        // The listeners live as long as the canvas, which is never removed.
+        on_drag_over.forget();
+        on_drop.forget();
+    }
+
+    /// Returns the files dropped onto the canvas since the last call.
+    pub(crate) fn take_dropped_files() -> Vec<web_sys::File> {
+        DROPPED_FILES.with_borrow_mut(std::mem::take)
+    }
+
     /// Show an error message in the browser window if WebGPU is not supported or some other error occurred.
     pub(crate) fn show_webgpu_error() {
         let error_element = web_sys::window()
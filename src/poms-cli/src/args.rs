@@ -0,0 +1,277 @@
+//! Command-line options of the tool, parsed the same way as those of the viewer.
+
+use std::path::PathBuf;
+
+use poms_common::limits::{
+    MAX_DISTANCE_FIELD_RESOLUTION, MAX_PROBE_RADIUS, MIN_DISTANCE_FIELD_RESOLUTION,
+    MIN_PROBE_RADIUS,
+};
+use poms_common::options::{parse_in_range, Representation};
+use poms_compute::export::ExportFormat;
+use poms_io::molecule_parser::load_options::{AltLocPolicy, LoadOptions};
+use poms_io::radius_set::RadiusSet;
+
+pub(crate) const USAGE: &str = "Usage: poms-cli [OPTIONS] <FILE>...
+
+Computes the molecular surface of each molecule file (PDB, mmCIF, BinaryCIF, PQR, GRO, XYZ, MOL2 or SDF,
+optionally gzip compressed) without a window and writes the requested outputs. Atoms are loaded like in
+the viewer, only the first model of each file is used. Outputs are named after the input files,
+e.g. `1cqw.pdb` is exported to `1cqw.mrc`, `1cqw.obj` and `1cqw.png`.
+
+Outputs:
+  --distance-field               Write the signed distance field as an MRC volume
+  --mesh <FORMAT>                Write the surface mesh as obj, ply, stl or glb
+  --measure <FORMAT>             Print the SES and SAS areas and volumes to stdout as json or csv
+  --render <WIDTHxHEIGHT>        Write a PNG image of the given size
+
+Options:
+  -o, --output-dir <DIR>         Directory to write the outputs to [default: .]
+  --probe-radius <RADIUS>        Probe radius of the molecular surface in Å [default: 1.4]
+  --resolution <RESOLUTION>      Resolution of the distance field [default: 256]
+  --representation <KIND>        Rendered representation, spacefill, surface or both [default: surface]
+  --background <THEME>           Background of the render, dark or light [default: light]
+  --radius-set <SET>             Atomic radii, default, bondi, alvarez, richards, protor or united-atom
+                                 [default: default]
+  --cpu                          Compute the distance field on the CPU instead of the GPU, which is
+                                 done anyway on OpenGL adapters
+  --software                     Use a software adapter, e.g. on machines without a GPU
+  -h, --help                     Print this help
+
+Load options:
+  --alt-locs <POLICY>            Alternate locations kept, highest-occupancy, all or an identifier (e.g. B)
+                                 [default: highest-occupancy]
+  --min-occupancy <OCCUPANCY>    Remove atoms with a lower occupancy [default: 0]
+  --remove-hydrogens             Remove hydrogen atoms
+  --remove-waters                Remove water molecules
+  --remove-ions                  Remove common monoatomic ions
+  --remove-buffer-ligands        Remove common buffer ligands and crystallization additives";
+
+/// Format of the printed surface measures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MeasureFormat {
+    Json,
+    Csv,
+}
+
+/// Options of a render written as a PNG image.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderOptions {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) representation: Representation,
+    pub(crate) background: wgpu::Color,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Options {
+    pub(crate) inputs: Vec<PathBuf>,
+    pub(crate) output_dir: PathBuf,
+    pub(crate) probe_radius: f32,
+    pub(crate) target_resolution: u32,
+    /// Which atoms of the files are loaded.
+    pub(crate) load_options: LoadOptions,
+    pub(crate) radius_set: RadiusSet,
+    pub(crate) write_distance_field: bool,
+    pub(crate) mesh_format: Option<ExportFormat>,
+    pub(crate) measure_format: Option<MeasureFormat>,
+    pub(crate) render: Option<RenderOptions>,
+    pub(crate) use_cpu: bool,
+    pub(crate) force_fallback_adapter: bool,
+}
+
+impl Options {
+    /// Parses the arguments of the process. Prints the usage and exits if help is requested or the arguments are invalid.
+    pub(crate) fn from_env() -> Self {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+
+        Self::parse(args).unwrap_or_else(|error| {
+            eprintln!("Error: {}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        })
+    }
+
+    /// Parses the arguments, without the name of the program. Arguments that are not options are input files.
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Options {
+            inputs: Vec::new(),
+            output_dir: PathBuf::from("."),
+            probe_radius: MIN_PROBE_RADIUS,
+            target_resolution: MAX_DISTANCE_FIELD_RESOLUTION,
+            load_options: LoadOptions::default(),
+            radius_set: RadiusSet::default(),
+            write_distance_field: false,
+            mesh_format: None,
+            measure_format: None,
+            render: None,
+            use_cpu: false,
+            force_fallback_adapter: false,
+        };
+        let mut render_size = None;
+        let mut representation = Representation::Surface;
+        let mut background = wgpu::Color::WHITE;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                options.inputs.push(PathBuf::from(arg));
+                continue;
+            }
+
+            // Flags take no value.
+            match arg.as_str() {
+                "--distance-field" => {
+                    options.write_distance_field = true;
+                    continue;
+                }
+                "--cpu" => {
+                    options.use_cpu = true;
+                    continue;
+                }
+                "--software" => {
+                    options.force_fallback_adapter = true;
+                    continue;
+                }
+                "--remove-hydrogens" => {
+                    options.load_options.remove_hydrogens = true;
+                    continue;
+                }
+                "--remove-waters" => {
+                    options.load_options.remove_waters = true;
+                    continue;
+                }
+                "--remove-ions" => {
+                    options.load_options.remove_ions = true;
+                    continue;
+                }
+                "--remove-buffer-ligands" => {
+                    options.load_options.remove_buffer_ligands = true;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Values may be given either as a separate argument or after an equals sign, e.g. `--mesh=ply`.
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("{} requires a value", arg))?;
+                    (arg, value)
+                }
+            };
+            match name.as_str() {
+                "-o" | "--output-dir" => options.output_dir = PathBuf::from(value),
+                "--probe-radius" => {
+                    options.probe_radius =
+                        parse_in_range(&name, &value, MIN_PROBE_RADIUS..=MAX_PROBE_RADIUS)?;
+                }
+                "--resolution" => {
+                    options.target_resolution = parse_in_range(
+                        &name,
+                        &value,
+                        MIN_DISTANCE_FIELD_RESOLUTION..=MAX_DISTANCE_FIELD_RESOLUTION,
+                    )?;
+                }
+                "--mesh" => {
+                    options.mesh_format = Some(
+                        ExportFormat::ALL
+                            .into_iter()
+                            .find(|format| format.extension() == value)
+                            .ok_or_else(|| anyhow::anyhow!("unknown mesh format `{}`", value))?,
+                    );
+                }
+                "--measure" => {
+                    options.measure_format = Some(match value.as_str() {
+                        "json" => MeasureFormat::Json,
+                        "csv" => MeasureFormat::Csv,
+                        _ => anyhow::bail!("unknown measure format `{}`", value),
+                    });
+                }
+                "--render" => {
+                    let size = value
+                        .split_once('x')
+                        .and_then(|(width, height)| {
+                            Some((width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|&(width, height)| width > 0 && height > 0);
+                    render_size = Some(size.ok_or_else(|| {
+                        anyhow::anyhow!("invalid render size `{}`, expected e.g. 1920x1080", value)
+                    })?);
+                }
+                "--representation" => {
+                    representation = Representation::parse(&value)?;
+                }
+                "--radius-set" => {
+                    // Sets are named like in the UI, in lowercase with dashes instead of spaces.
+                    options.radius_set = RadiusSet::ALL
+                        .into_iter()
+                        .find(|set| set.name().to_lowercase().replace(' ', "-") == value)
+                        .ok_or_else(|| anyhow::anyhow!("unknown radius set `{}`", value))?;
+                }
+                "--alt-locs" => {
+                    let mut chars = value.chars();
+                    options.load_options.alt_loc_policy =
+                        match (value.as_str(), chars.next(), chars.next()) {
+                            ("highest-occupancy", ..) => AltLocPolicy::HighestOccupancy,
+                            ("all", ..) => AltLocPolicy::All,
+                            (_, Some(alt_loc), None) if alt_loc.is_ascii_alphanumeric() => {
+                                AltLocPolicy::Only(alt_loc)
+                            }
+                            _ => anyhow::bail!("unknown alternate location policy `{}`", value),
+                        };
+                }
+                "--min-occupancy" => {
+                    options.load_options.min_occupancy = parse_in_range(&name, &value, 0.0..=1.0)?;
+                }
+                "--background" => {
+                    background = match value.as_str() {
+                        "dark" => wgpu::Color::BLACK,
+                        "light" => wgpu::Color::WHITE,
+                        _ => anyhow::bail!("unknown background `{}`", value),
+                    };
+                }
+                _ => anyhow::bail!("unknown option {}", name),
+            }
+        }
+
+        options.render = render_size.map(|(width, height)| RenderOptions {
+            width,
+            height,
+            representation,
+            background,
+        });
+
+        if options.inputs.is_empty() {
+            anyhow::bail!("no input files given");
+        }
+        if !options.writes_anything() {
+            anyhow::bail!(
+                "no output requested, use --distance-field, --mesh, --measure or --render"
+            );
+        }
+        Ok(options)
+    }
+
+    fn writes_anything(&self) -> bool {
+        self.write_distance_field
+            || self.mesh_format.is_some()
+            || self.measure_format.is_some()
+            || self.render.is_some()
+    }
+
+    /// Whether the distance field has to be available on the CPU.
+    pub(crate) fn needs_distance_field(&self) -> bool {
+        self.write_distance_field || self.mesh_format.is_some() || self.measure_format.is_some()
+    }
+
+    /// Whether the distance field has to be available on the GPU, either computed there or uploaded.
+    pub(crate) fn renders_molecular_surface(&self) -> bool {
+        self.render
+            .is_some_and(|render| render.representation.renders_molecular_surface())
+    }
+}
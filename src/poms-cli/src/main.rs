@@ -0,0 +1,225 @@
+//! Non-interactive counterpart of `poms-app`: computes molecular surfaces without a window and writes
+//! distance fields, meshes, surface measures and renders, e.g. on cluster nodes with only a software adapter.
+
+mod args;
+mod measures;
+mod mrc;
+mod render;
+mod structure;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use futures::executor::block_on;
+use poms_common::models::atom::{Atom, AtomsWithLookup};
+use poms_common::resources::CommonResources;
+use poms_compute::export::write_mesh;
+use poms_compute::headless::{self, HeadlessDevice, HeadlessParameters};
+use poms_compute::{cpu, mesh::extract_surface_mesh};
+
+use args::Options;
+use measures::{write_measures, StructureMeasures};
+
+/// Requests the device shared by all input structures.
+fn request_gpu(force_fallback_adapter: bool) -> anyhow::Result<HeadlessDevice> {
+    let gpu = block_on(headless::request_device(force_fallback_adapter))?;
+    let info = &gpu.adapter_info;
+    if !headless::supports_compute_passes(info) {
+        eprintln!(
+            "Distance fields are computed on the CPU, as the {} adapter ({:?}) cannot run the compute passes",
+            info.name, info.backend
+        );
+    }
+    Ok(gpu)
+}
+
+fn main() {
+    let options = Options::from_env();
+
+    if let Err(error) = run(&options) {
+        eprintln!("Error: {:#}", error);
+        std::process::exit(1);
+    }
+}
+
+/// Processes all input structures. A structure that fails is reported and skipped, so the others are still processed,
+/// but the run fails in the end.
+fn run(options: &Options) -> anyhow::Result<()> {
+    std::fs::create_dir_all(&options.output_dir)?;
+
+    let needs_gpu =
+        options.render.is_some() || (options.needs_distance_field() && !options.use_cpu);
+    let gpu = match needs_gpu {
+        true => Some(request_gpu(options.force_fallback_adapter)?),
+        false => None,
+    };
+
+    let mut measures = Vec::new();
+    let mut failed_count = 0;
+    for path in &options.inputs {
+        match process_structure(path, options, gpu.as_ref()) {
+            Ok(structure_measures) => measures.extend(structure_measures),
+            Err(error) => {
+                eprintln!("{}: {:#}", path.display(), error);
+                failed_count += 1;
+            }
+        }
+    }
+
+    if let Some(format) = options.measure_format {
+        write_measures(&measures, format, &mut std::io::stdout().lock())?;
+    }
+
+    match failed_count {
+        0 => Ok(()),
+        _ => anyhow::bail!(
+            "{} of {} structures failed",
+            failed_count,
+            options.inputs.len()
+        ),
+    }
+}
+
+/// Computes the requested outputs of a single structure. Returns its measures if they were requested.
+fn process_structure(
+    path: &Path,
+    options: &Options,
+    gpu: Option<&HeadlessDevice>,
+) -> anyhow::Result<Option<StructureMeasures>> {
+    let atoms = structure::read_atoms(path, options.load_options, options.radius_set)?;
+    let name = structure_name(path);
+    let params = || HeadlessParameters {
+        molecule: &atoms,
+        probe_radius: options.probe_radius,
+        target_resolution: options.target_resolution,
+    };
+
+    // On the GPU, the distance field is kept in a texture to render the surface, and read back if the other outputs need it.
+    // Adapters that cannot run the compute passes fall back to the CPU within `headless`.
+    // With `--cpu`, it is computed on the CPU and uploaded to a texture to render the surface.
+    let computes_distance_field =
+        options.needs_distance_field() || options.renders_molecular_surface();
+    let mut common_resources = None;
+    let mut distance_field_texture = None;
+    let mut distance_field = None;
+    match gpu.filter(|_| !options.use_cpu) {
+        Some(gpu) if options.renders_molecular_surface() => {
+            let mut resources = CommonResources::new(&gpu.device);
+            let (texture, grid) =
+                headless::compute_distance_field_texture(gpu, &mut resources, params())?;
+            if options.needs_distance_field() {
+                distance_field = Some(block_on(headless::read_distance_field(
+                    &gpu.device,
+                    &gpu.queue,
+                    &texture,
+                    grid,
+                ))?);
+            }
+            common_resources = Some(resources);
+            distance_field_texture = Some((texture, grid));
+        }
+        Some(gpu) if computes_distance_field => {
+            distance_field = Some(block_on(headless::compute_distance_field(gpu, params()))?);
+        }
+        None if computes_distance_field => {
+            let cpu_distance_field = cpu::compute_distance_field(params())?;
+            if let (Some(gpu), true) = (gpu, options.renders_molecular_surface()) {
+                let atoms_with_lookup = AtomsWithLookup::new(atoms.clone(), options.probe_radius);
+                let texture = headless::upload_distance_field(
+                    &gpu.device,
+                    &gpu.queue,
+                    &cpu_distance_field,
+                    Some(&atoms_with_lookup),
+                );
+                distance_field_texture = Some((texture, cpu_distance_field.grid));
+            }
+            distance_field = Some(cpu_distance_field);
+        }
+        _ => {}
+    }
+
+    let mut structure_measures = None;
+    if let Some(distance_field) = &distance_field {
+        if options.write_distance_field {
+            write_output(&output_path(options, &name, "mrc"), |writer| {
+                Ok(mrc::write_mrc(distance_field, writer)?)
+            })?;
+        }
+
+        if options.mesh_format.is_some() || options.measure_format.is_some() {
+            let atoms_with_lookup = AtomsWithLookup::new(atoms.clone(), options.probe_radius);
+            let mesh = extract_surface_mesh(distance_field, Some(&atoms_with_lookup));
+
+            if let Some(format) = options.mesh_format {
+                write_output(&output_path(options, &name, format.extension()), |writer| {
+                    Ok(write_mesh(&mesh, format, writer)?)
+                })?;
+            }
+            if options.measure_format.is_some() {
+                structure_measures = Some(StructureMeasures::new(
+                    name.clone(),
+                    &atoms,
+                    &mesh,
+                    options.probe_radius,
+                    distance_field.grid.resolution,
+                ));
+            }
+        }
+    }
+
+    if let (Some(gpu), Some(render_options)) = (gpu, options.render) {
+        let common_resources =
+            common_resources.unwrap_or_else(|| upload_atoms(gpu, &atoms, options.probe_radius));
+        write_output(&output_path(options, &name, "png"), |writer| {
+            render::render_png(
+                gpu,
+                &common_resources,
+                &atoms,
+                options.probe_radius,
+                distance_field_texture,
+                render_options,
+                writer,
+            )
+        })?;
+    }
+
+    Ok(structure_measures)
+}
+
+/// Uploads the atoms for rendering, when they were not already uploaded to compute the distance field.
+fn upload_atoms(gpu: &HeadlessDevice, atoms: &[Atom], probe_radius: f32) -> CommonResources {
+    let mut resources = CommonResources::new(&gpu.device);
+    let atoms = AtomsWithLookup::new(atoms.to_vec(), probe_radius);
+    resources.atoms_resource.update(&gpu.queue, &atoms);
+    resources
+}
+
+/// Returns the name of the structure, i.e. the file name without the extension and the compression suffix.
+fn structure_name(path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(&file_name);
+    match file_name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => file_name.to_string(),
+    }
+}
+
+fn output_path(options: &Options, name: &str, extension: &str) -> PathBuf {
+    options.output_dir.join(format!("{}.{}", name, extension))
+}
+
+/// Creates the output file and writes it, reporting the written file on stderr, so stdout is left for the measures.
+fn write_output(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Areas and volumes of the surfaces of each input structure, printed as JSON or CSV.
+
+use std::io::Write;
+
+use poms_common::models::atom::Atom;
+use poms_common::models::mesh::SurfaceMesh;
+use poms_compute::measure::measure_solvent_accessible_surface;
+use serde::Serialize;
+
+use crate::args::MeasureFormat;
+
+/// Measures of a single structure, areas are in Å² and volumes in Å³.
+#[derive(Debug, Serialize)]
+pub(crate) struct StructureMeasures {
+    /// Name of the input file.
+    pub(crate) name: String,
+    pub(crate) atoms_count: usize,
+    pub(crate) probe_radius: f32,
+    /// Resolution of the distance field the solvent-excluded surface was extracted from.
+    pub(crate) resolution: u32,
+    pub(crate) ses_area: f32,
+    pub(crate) ses_volume: f32,
+    pub(crate) sas_area: f32,
+    pub(crate) sas_volume: f32,
+}
+
+impl StructureMeasures {
+    const CSV_HEADER: &'static str =
+        "name,atoms_count,probe_radius,resolution,ses_area,ses_volume,sas_area,sas_volume";
+
+    /// Measures the solvent-excluded surface on its mesh and the solvent-accessible surface on the atoms.
+    pub(crate) fn new(
+        name: String,
+        atoms: &[Atom],
+        mesh: &SurfaceMesh,
+        probe_radius: f32,
+        resolution: u32,
+    ) -> Self {
+        let sas = measure_solvent_accessible_surface(atoms, probe_radius);
+        Self {
+            name,
+            atoms_count: atoms.len(),
+            probe_radius,
+            resolution,
+            ses_area: mesh.area(),
+            ses_volume: mesh.enclosed_volume(),
+            sas_area: sas.area,
+            sas_volume: sas.volume,
+        }
+    }
+
+    fn to_csv_row(&self) -> String {
+        // Names are quoted, as file names may contain commas.
+        format!(
+            "\"{}\",{},{},{},{:.2},{:.2},{:.2},{:.2}",
+            self.name.replace('"', "\"\""),
+            self.atoms_count,
+            self.probe_radius,
+            self.resolution,
+            self.ses_area,
+            self.ses_volume,
+            self.sas_area,
+            self.sas_volume
+        )
+    }
+}
+
+/// Writes the measures of all structures, as a JSON array or as a CSV table with a header.
+pub(crate) fn write_measures(
+    measures: &[StructureMeasures],
+    format: MeasureFormat,
+    writer: &mut impl Write,
+) -> anyhow::Result<()> {
+    match format {
+        MeasureFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, measures)?;
+            writeln!(writer)?;
+        }
+        MeasureFormat::Csv => {
+            writeln!(writer, "{}", StructureMeasures::CSV_HEADER)?;
+            for structure in measures {
+                writeln!(writer, "{}", structure.to_csv_row())?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! Writer of the distance field as an MRC volume (MRC2014), readable by e.g. ChimeraX, PyMOL or VMD.
+//!
+//! See <https://www.ccpem.ac.uk/mrc_format/mrc2014.php> for the layout of the header.
+
+use std::io::Write;
+
+use poms_compute::headless::DistanceFieldData;
+
+/// The header is 256 words long, followed by the optional extended header (none here) and the data.
+const HEADER_SIZE: usize = 1024;
+/// Data stored as 32-bit floats.
+const MODE_FLOAT: i32 = 2;
+/// Space group of a single volume, as opposed to a crystallographic map.
+const SPACE_GROUP_VOLUME: i32 = 1;
+const FORMAT_VERSION: i32 = 20140;
+/// Machine stamp of little-endian data.
+const MACHINE_STAMP_LITTLE_ENDIAN: [u8; 4] = [0x44, 0x44, 0x00, 0x00];
+
+/// Writes the distance field in Å, with the grid points positioned in the coordinates of the molecule.
+pub(crate) fn write_mrc(
+    distance_field: &DistanceFieldData,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let grid = &distance_field.grid;
+    let values = &distance_field.values;
+    let resolution = grid.resolution as i32;
+    let cell_size = grid.spacing * grid.resolution as f32;
+
+    let count = values.len().max(1) as f64;
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let mean = values.iter().map(|&v| v as f64).sum::<f64>() / count;
+    let rms = (values
+        .iter()
+        .map(|&v| (v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+
+    let mut header = Header(vec![0; HEADER_SIZE]);
+    // Number of columns, rows and sections, as well as the sampling along each axis of the cell.
+    for word in [0, 1, 2, 7, 8, 9] {
+        header.write_i32(word, resolution);
+    }
+    header.write_i32(3, MODE_FLOAT);
+    for word in [10, 11, 12] {
+        header.write_f32(word, cell_size);
+    }
+    for word in [13, 14, 15] {
+        header.write_f32(word, 90.0);
+    }
+    // Columns, rows and sections go along x, y and z, matching the layout of the distance field.
+    for (word, axis) in [(16, 1), (17, 2), (18, 3)] {
+        header.write_i32(word, axis);
+    }
+    header.write_f32(19, min);
+    header.write_f32(20, max);
+    header.write_f32(21, mean as f32);
+    header.write_i32(22, SPACE_GROUP_VOLUME);
+    header.write_i32(27, FORMAT_VERSION);
+    for (word, coordinate) in [(49, 0), (50, 1), (51, 2)] {
+        header.write_f32(word, grid.origin[coordinate]);
+    }
+    header.0[208..212].copy_from_slice(b"MAP ");
+    header.0[212..216].copy_from_slice(&MACHINE_STAMP_LITTLE_ENDIAN);
+    header.write_f32(54, rms as f32);
+    header.write_i32(55, 1);
+    let label = b"Signed distance field of the molecular surface by POMS";
+    header.0[224..224 + label.len()].copy_from_slice(label);
+
+    writer.write_all(&header.0)?;
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// The header as bytes, addressed by 4-byte words like in the specification.
+struct Header(Vec<u8>);
+
+impl Header {
+    fn write_i32(&mut self, word: usize, value: i32) {
+        self.0[word * 4..word * 4 + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_f32(&mut self, word: usize, value: f32) {
+        self.0[word * 4..word * 4 + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
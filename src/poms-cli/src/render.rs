@@ -0,0 +1,178 @@
+//! Rendering of the molecule into an offscreen texture, which is read back and encoded as a PNG image.
+
+use std::io::Write;
+
+use cgmath::{InnerSpace, Matrix4, MetricSpace, Point3, Rad, Vector3};
+use poms_common::models::atom::{calculate_center, Atom};
+use poms_common::models::grid::GridUniform;
+use poms_common::resources::CommonResources;
+use poms_compute::headless::HeadlessDevice;
+use poms_render::{PostprocessSettings, RenderJobs, RenderParameters};
+
+use crate::args::RenderOptions;
+
+/// The texture is read back as 8-bit RGBA, stored in sRGB like the window surface of the viewer.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Same field of view as the camera of the viewer.
+const FOVY: f32 = std::f32::consts::PI / 4.0;
+const ZNEAR: f32 = 0.1;
+
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+/// Renders the molecule uploaded to `common_resources` and writes the image as a PNG.
+/// The molecular surface is only rendered if its distance field texture is given.
+///
+/// The camera looks at the molecule along the negative z axis, like the viewer does initially,
+/// from a distance at which the whole molecule fits the image.
+pub(crate) fn render_png(
+    gpu: &HeadlessDevice,
+    common_resources: &CommonResources,
+    atoms: &[Atom],
+    probe_radius: f32,
+    distance_field: Option<(wgpu::Texture, GridUniform)>,
+    options: RenderOptions,
+    writer: &mut impl Write,
+) -> anyhow::Result<()> {
+    let HeadlessDevice { device, queue, .. } = gpu;
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        format: FORMAT,
+        width: options.width,
+        height: options.height,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![FORMAT],
+        desired_maximum_frame_latency: 1,
+    };
+
+    let mut renderer = RenderJobs::new(
+        device,
+        RenderParameters {
+            common_resources,
+            surface_config: &config,
+            queue,
+            render_spacefill: options.representation.renders_spacefill(),
+            render_molecular_surface: options.representation.renders_molecular_surface(),
+            postprocess_settings: PostprocessSettings::default(),
+            clear_color: options.background,
+        },
+    );
+    renderer.update_clear_color(options.background);
+    if let Some((texture, grid)) = distance_field {
+        renderer.update_distance_field_texture(device, texture, grid);
+    }
+
+    // The bounding sphere of the molecule, including the probe around it.
+    let center = calculate_center(atoms);
+    let radius = atoms
+        .iter()
+        .map(|atom| center.distance(Point3::from(atom.position)) + atom.radius)
+        .fold(0.0, f32::max)
+        + probe_radius;
+    let aspect = options.width as f32 / options.height as f32;
+    // The sphere has to fit both the vertical and the horizontal field of view.
+    let half_fov = f32::min(FOVY / 2.0, ((FOVY / 2.0).tan() * aspect).atan());
+    let distance = radius / half_fov.sin();
+    let position = center + Vector3::unit_z() * distance;
+
+    let view_matrix = Matrix4::look_at_rh(position, center, Vector3::unit_y());
+    let projection_matrix = OPENGL_TO_WGPU_MATRIX
+        * cgmath::perspective(Rad(FOVY), aspect, ZNEAR, distance + 2.0 * radius);
+    renderer.update_camera(queue, position, view_matrix, projection_matrix);
+    renderer.update_light(queue, (position - center).normalize());
+
+    let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("render_output_texture"),
+        size: wgpu::Extent3d {
+            width: options.width,
+            height: options.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: config.usage,
+        view_formats: &[],
+    });
+    let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("render_command_encoder"),
+    });
+    renderer.render(&output_view, &mut encoder, common_resources);
+    queue.submit(Some(encoder.finish()));
+
+    let pixels = read_texture(device, queue, &output_texture)?;
+
+    let mut encoder = png::Encoder::new(writer, options.width, options.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)?;
+    Ok(())
+}
+
+/// Copies the texture into a mappable buffer and returns its pixels, row by row without padding.
+fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = (texture.width(), texture.height());
+
+    // Rows copied from a texture have to be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    let unpadded_bytes_per_row = width * BYTES_PER_PIXEL;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("render_readback_buffer"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("render_readback_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = readback_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver
+        .recv()?
+        .map_err(|e| anyhow::anyhow!("Failed to read the render from the GPU: {}", e))?;
+
+    let pixels = buffer_slice
+        .get_mapped_range()
+        .chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| row[..unpadded_bytes_per_row as usize].iter().copied())
+        .collect();
+    readback_buffer.unmap();
+
+    Ok(pixels)
+}
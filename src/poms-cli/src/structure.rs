@@ -0,0 +1,40 @@
+//! Reading of the input structures into the atoms the surface is computed from.
+
+use std::path::Path;
+
+use poms_common::models::atom::Atom;
+use poms_io::molecule_parser::{load_options::LoadOptions, parse_atoms_from_pdb_file};
+use poms_io::radius_set::RadiusSet;
+use poms_io::RawFile;
+
+/// Reads the atoms of the first model of a molecule file in any format the viewer opens, decompressing it first if it is gzipped.
+/// Atoms are filtered by `load_options` and get the radii of `radius_set`, like in the viewer.
+pub(crate) fn read_atoms(
+    path: &Path,
+    load_options: LoadOptions,
+    radius_set: RadiusSet,
+) -> anyhow::Result<Vec<Atom>> {
+    let file = RawFile {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        content: std::fs::read(path)?,
+    };
+    let molecule = parse_atoms_from_pdb_file(file, load_options)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No atoms found in file"))?;
+
+    let parsed_radii = molecule
+        .atoms
+        .iter()
+        .map(|atom| atom.radius)
+        .collect::<Vec<_>>();
+    let radii = radius_set.radii(&molecule.metadata, &parsed_radii);
+    let mut atoms = molecule.atoms;
+    for (atom, radius) in atoms.iter_mut().zip(radii) {
+        atom.radius = radius;
+    }
+    Ok(atoms)
+}
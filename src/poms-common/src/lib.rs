@@ -1,3 +1,4 @@
 pub mod limits;
 pub mod models;
+pub mod options;
 pub mod resources;
@@ -1,4 +1,4 @@
-use cgmath::{Bounded, Point3, Vector3};
+use cgmath::{Bounded, InnerSpace, Point3, Vector3};
 
 use super::grid::{create_atoms_lookup_grid_around_molecule, position_to_voxel_index, GridUniform};
 
@@ -10,6 +10,48 @@ pub struct Atom {
     pub color: [f32; 4],
 }
 
+/// Information about an atom retained from the molecule file, kept on the CPU alongside the `Atom` data uploaded to the GPU.
+/// Used for selecting, labeling and coloring atoms.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AtomMetadata {
+    /// Identifier of the chain the atom belongs to (e.g. `A`).
+    pub chain_id: String,
+    /// Label chain identifier (`label_asym_id`) of mmCIF and BinaryCIF files, which their assemblies refer to.
+    pub label_asym_id: Option<String>,
+    /// Name of the residue the atom belongs to (e.g. `ALA` or `HOH`).
+    pub residue_name: String,
+    /// Sequence number of the residue the atom belongs to.
+    pub residue_number: isize,
+    /// Insertion code distinguishing residues with the same sequence number, if any.
+    pub insertion_code: Option<String>,
+    /// Name of the atom within its residue (e.g. `CA`).
+    pub atom_name: String,
+    /// Symbol of the chemical element (e.g. `C`), if known.
+    pub element: Option<String>,
+    /// Temperature factor of the atom.
+    pub b_factor: f32,
+    /// Occupancy of the atom, between 0 and 1.
+    pub occupancy: f32,
+    /// Alternate location indicator, if the atom has alternative conformations.
+    pub alt_loc: Option<String>,
+    /// Whether the atom comes from a `HETATM` record (ligands, waters, ions, etc.).
+    pub hetero: bool,
+    /// Partial charge of the atom in elementary charges, if the file provides it (e.g. PQR).
+    pub charge: Option<f32>,
+    /// Secondary structure of the residue the atom belongs to, as annotated in the file.
+    pub secondary_structure: SecondaryStructure,
+}
+
+/// Secondary structure of a residue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SecondaryStructure {
+    Helix,
+    Sheet,
+    /// Any residue that is neither in a helix nor in a sheet, including non-polymer residues.
+    #[default]
+    Coil,
+}
+
 /// This struct is used to store the first atom index and the number of atoms in each voxel of the neighbor lookup grid.
 /// Since the atoms are sorted by voxel index, we can use this to quickly find all atoms in a voxel.
 #[repr(C)]
@@ -28,33 +70,37 @@ pub struct AtomsWithLookup {
     pub atoms_lookup_grid: GridUniform,
     /// Segment of atoms for each voxel of the neighbor lookup grid. The length of this vector is equal to the number of voxels in the grid (resolution^3).
     pub atoms_by_voxel: Vec<AtomSegment>,
+    /// For each atom in `data`, the index it had in the vector the lookup was created from.
+    pub original_indices: Vec<usize>,
 }
 
 impl AtomsWithLookup {
     pub fn new(atoms: Vec<Atom>, probe_radius: f32) -> Self {
         let atoms_lookup_grid = create_atoms_lookup_grid_around_molecule(&atoms, probe_radius);
-        // Extend atom data with corresponding voxel index
+        // Extend atom data with corresponding voxel index and the original index of the atom
         let mut atoms_with_voxel_indices = atoms
             .into_iter()
-            .map(|atom| {
+            .enumerate()
+            .map(|(original_index, atom)| {
                 (
                     atom,
                     position_to_voxel_index(
                         cgmath::Point3::from(atom.position),
                         &atoms_lookup_grid,
                     ),
+                    original_index,
                 )
             })
             .collect::<Vec<_>>();
-        // Sort atoms by the index of corresponding voxel
-        atoms_with_voxel_indices.sort_by(|(_, i), (_, j)| i.cmp(j));
+        // Sort atoms by the index of corresponding voxel, the sort is stable so atoms within a voxel keep their order
+        atoms_with_voxel_indices.sort_by(|(_, i, _), (_, j, _)| i.cmp(j));
 
         // Create a look-up table for each voxel in the grid.
         let voxels_count = u32::pow(atoms_lookup_grid.resolution, 3) as usize;
         let mut atoms_by_voxel = vec![AtomSegment::default(); voxels_count];
 
         // Assign first index and count the number of atoms in each voxel
-        for (atom_index, &(_, voxel_index)) in atoms_with_voxel_indices.iter().enumerate() {
+        for (atom_index, &(_, voxel_index, _)) in atoms_with_voxel_indices.iter().enumerate() {
             if atoms_by_voxel[voxel_index].atoms_count == 0 {
                 atoms_by_voxel[voxel_index].first_atom_index = atom_index as u32;
             }
@@ -62,17 +108,68 @@ impl AtomsWithLookup {
         }
 
         // Strip voxel indices from atoms
-        let data = atoms_with_voxel_indices
+        let (data, original_indices) = atoms_with_voxel_indices
             .into_iter()
-            .map(|(atom, _)| atom)
-            .collect();
+            .map(|(atom, _, original_index)| (atom, original_index))
+            .unzip();
 
         Self {
             data,
             atoms_lookup_grid,
             atoms_by_voxel,
+            original_indices,
         }
     }
+
+    /// Reorders per-atom `items` (e.g. `AtomMetadata`), given in the order of atoms the lookup was created from, to match the order of `data`.
+    /// `items` must contain exactly one element per atom.
+    pub fn permute<T>(&self, items: Vec<T>) -> Vec<T> {
+        let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+        self.original_indices
+            .iter()
+            .map(|&index| {
+                items[index]
+                    .take()
+                    .expect("indices should be a permutation")
+            })
+            .collect()
+    }
+
+    /// Returns the index (within `data`) of the atom whose van der Waals surface is the closest to `position`.
+    /// Only atoms in the neighborhood voxels are considered, so `None` is returned for positions far from the molecule.
+    pub fn nearest_atom(&self, position: Point3<f32>) -> Option<usize> {
+        let grid = &self.atoms_lookup_grid;
+        let offset =
+            (position - Point3::new(grid.origin[0], grid.origin[1], grid.origin[2])) / grid.spacing;
+        let voxel = [offset.x, offset.y, offset.z].map(|v| v.floor() as i64);
+        let r = grid.resolution as i64;
+
+        let mut nearest: Option<(usize, f32)> = None;
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (x, y, z) = (voxel[0] + dx, voxel[1] + dy, voxel[2] + dz);
+                    if !(0..r).contains(&x) || !(0..r).contains(&y) || !(0..r).contains(&z) {
+                        continue;
+                    }
+                    let segment = self.atoms_by_voxel[(x + y * r + z * r * r) as usize];
+                    let first = segment.first_atom_index as usize;
+
+                    for index in first..first + segment.atoms_count as usize {
+                        let atom = &self.data[index];
+                        let distance =
+                            (position - Point3::from(atom.position)).magnitude() - atom.radius;
+                        if nearest.map_or(true, |(_, min)| distance < min) {
+                            nearest = Some((index, distance));
+                        }
+                    }
+                }
+            }
+        }
+
+        nearest.map(|(index, _)| index)
+    }
 }
 
 pub fn calculate_center(atoms: &[Atom]) -> Point3<f32> {
@@ -0,0 +1,112 @@
+/// Color of atoms whose element is unknown or has no color assigned.
+const DEFAULT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// Returns the CPK color of the element with the given symbol (case insensitive) as used by Jmol.
+pub fn jmol_color(symbol: &str) -> [f32; 4] {
+    match symbol.to_ascii_uppercase().as_str() {
+        "H" => [1.0, 1.0, 1.0, 1.0],
+        "HE" => [0.85, 1.0, 1.0, 1.0],
+        "LI" => [0.8, 0.5, 1.0, 1.0],
+        "BE" => [0.76, 1.0, 0.0, 1.0],
+        "B" => [1.0, 0.71, 0.71, 1.0],
+        "C" => [0.56, 0.56, 0.56, 1.0],
+        "N" => [0.19, 0.31, 0.97, 1.0],
+        "O" => [1.0, 0.05, 0.05, 1.0],
+        "F" => [0.56, 0.88, 0.31, 1.0],
+        "NE" => [0.7, 0.89, 0.96, 1.0],
+        "NA" => [0.67, 0.36, 0.95, 1.0],
+        "MG" => [0.54, 1.0, 0.0, 1.0],
+        "AL" => [0.75, 0.65, 0.65, 1.0],
+        "SI" => [0.94, 0.78, 0.63, 1.0],
+        "P" => [1.0, 0.5, 0.0, 1.0],
+        "S" => [1.0, 1.0, 0.19, 1.0],
+        "CL" => [0.12, 0.94, 0.12, 1.0],
+        "AR" => [0.5, 0.82, 0.89, 1.0],
+        "K" => [0.56, 0.25, 0.83, 1.0],
+        "CA" => [0.24, 1.0, 0.0, 1.0],
+        "SC" => [0.9, 0.9, 0.9, 1.0],
+        "TI" => [0.75, 0.76, 0.78, 1.0],
+        "V" => [0.65, 0.65, 0.67, 1.0],
+        "CR" => [0.54, 0.6, 0.78, 1.0],
+        "MN" => [0.61, 0.48, 0.78, 1.0],
+        "FE" => [0.88, 0.4, 0.2, 1.0],
+        "CO" => [0.94, 0.56, 0.63, 1.0],
+        "NI" => [0.31, 0.82, 0.31, 1.0],
+        "CU" => [0.78, 0.5, 0.2, 1.0],
+        "ZN" => [0.49, 0.5, 0.69, 1.0],
+        "GA" => [0.76, 0.56, 0.56, 1.0],
+        "GE" => [0.4, 0.56, 0.56, 1.0],
+        "AS" => [0.74, 0.5, 0.89, 1.0],
+        "SE" => [1.0, 0.63, 0.0, 1.0],
+        "BR" => [0.65, 0.16, 0.16, 1.0],
+        "KR" => [0.36, 0.72, 0.82, 1.0],
+        "RB" => [0.44, 0.18, 0.69, 1.0],
+        "SR" => [0.0, 1.0, 0.0, 1.0],
+        "Y" => [0.58, 1.0, 1.0, 1.0],
+        "ZR" => [0.58, 0.88, 0.88, 1.0],
+        "NB" => [0.45, 0.76, 0.79, 1.0],
+        "MO" => [0.33, 0.71, 0.71, 1.0],
+        "TC" => [0.23, 0.62, 0.62, 1.0],
+        "RU" => [0.14, 0.56, 0.56, 1.0],
+        "RH" => [0.04, 0.49, 0.55, 1.0],
+        "PD" => [0.0, 0.41, 0.52, 1.0],
+        "AG" => [0.75, 0.75, 0.75, 1.0],
+        "CD" => [1.0, 0.85, 0.56, 1.0],
+        "IN" => [0.65, 0.46, 0.45, 1.0],
+        "SN" => [0.4, 0.5, 0.5, 1.0],
+        "SB" => [0.62, 0.39, 0.71, 1.0],
+        "TE" => [0.83, 0.48, 0.0, 1.0],
+        "I" => [0.58, 0.0, 0.58, 1.0],
+        "XE" => [0.26, 0.62, 0.69, 1.0],
+        "CS" => [0.34, 0.09, 0.56, 1.0],
+        "BA" => [0.0, 0.79, 0.0, 1.0],
+        "LA" => [0.44, 0.83, 1.0, 1.0],
+        "CE" => [1.0, 1.0, 0.78, 1.0],
+        "PR" => [0.85, 1.0, 0.78, 1.0],
+        "ND" => [0.78, 1.0, 0.78, 1.0],
+        "PM" => [0.64, 1.0, 0.78, 1.0],
+        "SM" => [0.56, 1.0, 0.78, 1.0],
+        "EU" => [0.38, 1.0, 0.78, 1.0],
+        "GD" => [0.27, 1.0, 0.78, 1.0],
+        "TB" => [0.19, 1.0, 0.78, 1.0],
+        "DY" => [0.12, 1.0, 0.78, 1.0],
+        "HO" => [0.0, 1.0, 0.61, 1.0],
+        "ER" => [0.0, 0.9, 0.46, 1.0],
+        "TM" => [0.0, 0.83, 0.32, 1.0],
+        "YB" => [0.0, 0.75, 0.22, 1.0],
+        "LU" => [0.0, 0.67, 0.14, 1.0],
+        "HF" => [0.3, 0.76, 1.0, 1.0],
+        "TA" => [0.3, 0.65, 1.0, 1.0],
+        "W" => [0.12, 0.56, 1.0, 1.0],
+        "RE" => [0.15, 0.49, 0.67, 1.0],
+        "OS" => [0.15, 0.4, 0.59, 1.0],
+        "IR" => [0.09, 0.33, 0.53, 1.0],
+        "PT" => [0.0, 0.31, 0.49, 1.0],
+        "AU" => [1.0, 0.82, 0.12, 1.0],
+        "HG" => [0.72, 0.72, 0.82, 1.0],
+        "TL" => [0.65, 0.32, 0.3, 1.0],
+        "PB" => [0.34, 0.35, 0.38, 1.0],
+        "BI" => [0.62, 0.31, 0.71, 1.0],
+        "PO" => [0.67, 0.36, 0.0, 1.0],
+        "AT" => [0.46, 0.31, 0.27, 1.0],
+        "RN" => [0.26, 0.51, 0.59, 1.0],
+        "FR" => [0.26, 0.0, 0.4, 1.0],
+        "RA" => [0.0, 0.49, 0.0, 1.0],
+        "AC" => [0.44, 0.67, 0.98, 1.0],
+        "TH" => [0.0, 0.73, 1.0, 1.0],
+        "PA" => [0.0, 0.63, 1.0, 1.0],
+        "U" => [0.0, 0.56, 1.0, 1.0],
+        "NP" => [0.0, 0.5, 1.0, 1.0],
+        "PU" => [0.0, 0.42, 1.0, 1.0],
+        "AM" => [0.33, 0.36, 0.95, 1.0],
+        "CM" => [0.47, 0.36, 0.89, 1.0],
+        "BK" => [0.54, 0.31, 0.89, 1.0],
+        "CF" => [0.63, 0.21, 0.83, 1.0],
+        "ES" => [0.7, 0.12, 0.83, 1.0],
+        "FM" => [0.7, 0.12, 0.73, 1.0],
+        "MD" => [0.7, 0.05, 0.65, 1.0],
+        "NO" => [0.74, 0.05, 0.53, 1.0],
+        "LR" => [0.78, 0.0, 0.41, 1.0],
+        _ => DEFAULT_COLOR,
+    }
+}
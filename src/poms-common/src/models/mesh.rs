@@ -0,0 +1,52 @@
+use cgmath::{InnerSpace, Vector3};
+
+/// An indexed triangle mesh, e.g. the molecular surface extracted from a distance field.
+#[derive(Debug, Default, Clone)]
+pub struct SurfaceMesh {
+    /// Position of each vertex in space.
+    pub positions: Vec<[f32; 3]>,
+    /// Unit normal of each vertex, pointing out of the molecule.
+    pub normals: Vec<[f32; 3]>,
+    /// Optional color of each vertex, usually taken from the nearest atom.
+    pub colors: Option<Vec<[f32; 4]>>,
+    /// Indices into the vertex attributes, three per triangle. Triangles are wound counter-clockwise when viewed from outside.
+    pub indices: Vec<u32>,
+}
+
+impl SurfaceMesh {
+    /// Returns the number of triangles in the mesh.
+    pub fn triangles_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Iterates over the triangles of the mesh as triplets of vertex indices.
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+    }
+
+    /// Returns the total area of the triangles.
+    pub fn area(&self) -> f32 {
+        self.triangle_corners()
+            .map(|[a, b, c]| 0.5 * (b - a).cross(c - a).magnitude())
+            .sum()
+    }
+
+    /// Returns the volume enclosed by the mesh, computed by the divergence theorem as the sum of signed volumes
+    /// of tetrahedra spanned by each triangle and the origin. Only meaningful for closed meshes, as triangles
+    /// are expected to be wound counter-clockwise when viewed from outside.
+    pub fn enclosed_volume(&self) -> f32 {
+        let volume: f64 = self
+            .triangle_corners()
+            .map(|[a, b, c]| a.dot(b.cross(c)) as f64 / 6.0)
+            .sum();
+        volume as f32
+    }
+
+    /// Iterates over the positions of the corners of each triangle.
+    fn triangle_corners(&self) -> impl Iterator<Item = [Vector3<f32>; 3]> + '_ {
+        self.triangles()
+            .map(|triangle| triangle.map(|index| Vector3::from(self.positions[index as usize])))
+    }
+}
@@ -1,2 +1,4 @@
 pub mod atom;
+pub mod element;
 pub mod grid;
+pub mod mesh;
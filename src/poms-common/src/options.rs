@@ -0,0 +1,50 @@
+//! Command-line options shared by the viewer and `poms-cli`, so both parse them the same way.
+
+/// Which passes render the molecule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Representation {
+    Spacefill,
+    Surface,
+    Both,
+}
+
+impl Representation {
+    /// Parses the value of the `--representation` option.
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "spacefill" => Ok(Self::Spacefill),
+            "surface" => Ok(Self::Surface),
+            "both" => Ok(Self::Both),
+            _ => anyhow::bail!("unknown representation `{}`", value),
+        }
+    }
+
+    pub fn renders_spacefill(&self) -> bool {
+        matches!(self, Self::Spacefill | Self::Both)
+    }
+
+    pub fn renders_molecular_surface(&self) -> bool {
+        matches!(self, Self::Surface | Self::Both)
+    }
+}
+
+/// Parses the value of an option, which has to lie within the range.
+pub fn parse_in_range<T>(
+    name: &str,
+    value: &str,
+    range: std::ops::RangeInclusive<T>,
+) -> anyhow::Result<T>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    match value.parse::<T>() {
+        Ok(parsed) if range.contains(&parsed) => Ok(parsed),
+        _ => anyhow::bail!(
+            "{} has to be a number between {} and {}, got `{}`",
+            name,
+            range.start(),
+            range.end(),
+            value
+        ),
+    }
+}
@@ -91,6 +91,17 @@ impl AtomsWithLookupResource {
             bytemuck::cast_slice(&atoms.atoms_by_voxel),
         );
     }
+
+    /// Updates the colors of atoms after they were changed (e.g. by switching a color scheme).
+    /// Only the atom data buffer is rewritten, the positions and radii of atoms are expected to be unchanged,
+    /// so that the lookup grid and the computed distance field remain valid.
+    pub fn update_colors(&self, queue: &wgpu::Queue, atoms: &AtomsWithLookup) {
+        queue.write_buffer(
+            &self.atoms_data_buffer,
+            0,
+            bytemuck::cast_slice(atoms.data.as_slice()),
+        );
+    }
 }
 
 const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
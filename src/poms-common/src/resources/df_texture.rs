@@ -2,6 +2,7 @@ pub const DF_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Fl
 
 /// Creates a new distance field texture with the given resolution.
 /// Used both by the compute pipeline and the rendering pipeline.
+/// The texture can also be copied out of, which is used to read the distance field back to the CPU.
 pub fn create_distance_field_texture(device: &wgpu::Device, resolution: u32) -> wgpu::Texture {
     // wgpu requires that textures have at least 1 texel in each dimension.
     // When initializing resources, it may happen that the resolution is 0.
@@ -18,7 +19,9 @@ pub fn create_distance_field_texture(device: &wgpu::Device, resolution: u32) ->
         sample_count: 1,
         dimension: wgpu::TextureDimension::D3,
         format: DF_TEXTURE_FORMAT,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
         view_formats: &[],
     })
 }
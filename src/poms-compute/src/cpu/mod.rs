@@ -0,0 +1,75 @@
+//! A pure-Rust implementation of the probe and refinement passes.
+//!
+//! Mirrors `probe.wgsl` and `refinement.wgsl` grid point by grid point, so it can be used as a fallback
+//! on adapters without storage texture support, or as a reference to validate the output of the GPU passes.
+
+mod probe;
+mod refinement;
+
+use cgmath::{Point3, Vector3, Vector4};
+use poms_common::models::atom::AtomsWithLookup;
+use poms_common::models::grid::{create_compute_grid_around_molecule, GridUniform};
+
+use crate::headless::{DistanceFieldData, HeadlessError, HeadlessParameters};
+
+const EXTERIOR_GRID_POINT: u32 = 0;
+const INTERIOR_GRID_POINT: u32 = 1;
+const BOUNDARY_GRID_POINT: u32 = 2;
+
+/// Values of the grid point memory greater or equal to this offset store the index of the closest exterior grid point.
+const PREDECESSOR_OFFSET: u32 = 3;
+
+/// Computes the distance field of a molecule on the CPU, using the same grid as the GPU pipeline would.
+/// The target resolution is validated in the same way, see `HeadlessParameters`.
+pub fn compute_distance_field(
+    params: HeadlessParameters<'_>,
+) -> Result<DistanceFieldData, HeadlessError> {
+    params.validate()?;
+
+    let grid = create_compute_grid_around_molecule(
+        params.molecule,
+        params.target_resolution,
+        params.probe_radius,
+    );
+    let atoms = AtomsWithLookup::new(params.molecule.to_vec(), params.probe_radius);
+
+    Ok(compute_distance_field_on_grid(&atoms, grid))
+}
+
+/// Computes the distance field on the provided grid.
+/// Use this to compare against a distance field read back from the GPU, as it ensures both fields share the same grid.
+pub fn compute_distance_field_on_grid(
+    atoms: &AtomsWithLookup,
+    grid: GridUniform,
+) -> DistanceFieldData {
+    let mut grid_point_memory = probe::classify_grid_points(atoms, &grid);
+    let values = refinement::refine_grid_points(&mut grid_point_memory, &grid);
+
+    DistanceFieldData { grid, values }
+}
+
+/// Computes the position of a grid point in space from its linear index.
+fn grid_point_index_to_position(grid_point_index: usize, grid: &GridUniform) -> Point3<f32> {
+    let r = grid.resolution as usize;
+    let origin = Point3::from_homogeneous(Vector4::from(grid.origin));
+    let offset = Vector3::new(
+        (grid_point_index % r) as f32,
+        ((grid_point_index / r) % r) as f32,
+        (grid_point_index / (r * r)) as f32,
+    );
+    origin + offset * grid.spacing
+}
+
+/// Iterates over linear indices of the 3x3x3 neighborhood of `index` within a grid of the given resolution.
+/// Same as in the shaders, indices are offset linearly, out of range indices are skipped.
+fn neighbor_indices(index: usize, resolution: u32) -> impl Iterator<Item = usize> {
+    let r = resolution as i64;
+    let total = r * r * r;
+    let index = index as i64;
+
+    (-1..=1)
+        .flat_map(move |x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
+        .map(move |(x, y, z)| index + x + y * r + z * r * r)
+        .filter(move |&neighbor| neighbor >= 0 && neighbor < total)
+        .map(|neighbor| neighbor as usize)
+}
@@ -0,0 +1,62 @@
+use cgmath::{MetricSpace, Point3, Vector4};
+use poms_common::models::atom::AtomsWithLookup;
+use poms_common::models::grid::GridUniform;
+
+use super::{BOUNDARY_GRID_POINT, EXTERIOR_GRID_POINT, INTERIOR_GRID_POINT};
+
+/// Classifies each grid point as exterior, interior or boundary, see `probe.wgsl`.
+pub(super) fn classify_grid_points(atoms: &AtomsWithLookup, grid: &GridUniform) -> Vec<u32> {
+    let total_grid_points_count = grid.resolution.pow(3) as usize;
+
+    (0..total_grid_points_count)
+        .map(|grid_point_index| classify_grid_point(grid_point_index, atoms, grid))
+        .collect()
+}
+
+fn classify_grid_point(
+    grid_point_index: usize,
+    atoms: &AtomsWithLookup,
+    grid: &GridUniform,
+) -> u32 {
+    let grid_point = super::grid_point_index_to_position(grid_point_index, grid);
+
+    // Find index of the voxel containing the grid point within the atoms lookup grid
+    let lookup_grid = &atoms.atoms_lookup_grid;
+    let offset = grid_point - Point3::from_homogeneous(Vector4::from(lookup_grid.origin));
+    let res = lookup_grid.resolution as i64;
+    let voxel_index = (offset.x / lookup_grid.spacing) as i64
+        + (offset.y / lookup_grid.spacing) as i64 * res
+        + (offset.z / lookup_grid.spacing) as i64 * res * res;
+
+    let mut classification = EXTERIOR_GRID_POINT;
+
+    // Check all 27 neighboring voxels
+    for neighbor_voxel_index in neighbor_voxel_indices(voxel_index, res) {
+        let segment = atoms.atoms_by_voxel[neighbor_voxel_index];
+        let first = segment.first_atom_index as usize;
+        let last = first + segment.atoms_count as usize;
+
+        for atom in &atoms.data[first..last] {
+            let distance = grid_point.distance(Point3::from(atom.position));
+
+            if distance < atom.radius - grid.spacing {
+                // The grid point is inside an atom, no need to check the other atoms
+                return INTERIOR_GRID_POINT;
+            }
+            if distance < atom.radius + grid.probe_radius {
+                classification = BOUNDARY_GRID_POINT;
+            }
+        }
+    }
+
+    classification
+}
+
+/// Unlike the distance field grid, the voxel index may lie outside of the lookup grid, hence the signed index.
+fn neighbor_voxel_indices(voxel_index: i64, res: i64) -> impl Iterator<Item = usize> {
+    (-1..=1)
+        .flat_map(move |x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
+        .map(move |(x, y, z)| voxel_index + x + y * res + z * res * res)
+        .filter(move |&neighbor| neighbor >= 0 && neighbor < res * res * res)
+        .map(|neighbor| neighbor as usize)
+}
@@ -0,0 +1,74 @@
+use cgmath::MetricSpace;
+use poms_common::models::grid::GridUniform;
+
+use super::{BOUNDARY_GRID_POINT, EXTERIOR_GRID_POINT, INTERIOR_GRID_POINT, PREDECESSOR_OFFSET};
+
+/// Computes the signed distance of each grid point from the classified grid point memory, see `refinement.wgsl`.
+///
+/// The boundary points are refined in as many cycles as the GPU pipeline does, each cycle propagating
+/// the closest exterior point (predecessor) by one layer of grid points.
+pub(super) fn refine_grid_points(grid_point_memory: &mut [u32], grid: &GridUniform) -> Vec<f32> {
+    let mut values: Vec<f32> = grid_point_memory
+        .iter()
+        .map(|&memory| match memory {
+            EXTERIOR_GRID_POINT => grid.probe_radius,
+            _ => -grid.spacing,
+        })
+        .collect();
+
+    let boundary_grid_points: Vec<usize> = grid_point_memory
+        .iter()
+        .enumerate()
+        .filter(|(_, &memory)| memory == BOUNDARY_GRID_POINT)
+        .map(|(index, _)| index)
+        .collect();
+
+    // Same number of cycles as `ComputeState::refinement_cycles_count`.
+    let cycles_count = (grid.probe_radius / grid.spacing) as u32 + 1;
+
+    for _ in 0..cycles_count {
+        for &grid_point_index in &boundary_grid_points {
+            values[grid_point_index] = compute_distance(grid_point_index, grid_point_memory, grid);
+        }
+    }
+
+    values
+}
+
+fn compute_distance(
+    grid_point_index: usize,
+    grid_point_memory: &mut [u32],
+    grid: &GridUniform,
+) -> f32 {
+    let grid_point = super::grid_point_index_to_position(grid_point_index, grid);
+
+    let mut min_distance: Option<f32> = None;
+
+    // Loop over all 3x3x3 neighboring points, find the closest exterior point.
+    for neighbor_index in super::neighbor_indices(grid_point_index, grid.resolution) {
+        let closest_exterior_index = match grid_point_memory[neighbor_index] {
+            // The neighbor itself is an exterior point.
+            EXTERIOR_GRID_POINT => neighbor_index,
+            // The neighbor does not know its closest exterior point yet.
+            INTERIOR_GRID_POINT | BOUNDARY_GRID_POINT => continue,
+            // The closest exterior point of the neighbor (its predecessor).
+            memory => (memory - PREDECESSOR_OFFSET) as usize,
+        };
+
+        let closest_exterior_point =
+            super::grid_point_index_to_position(closest_exterior_index, grid);
+        let distance = grid_point.distance(closest_exterior_point);
+
+        if min_distance.map_or(true, |min| distance < min) {
+            min_distance = Some(distance);
+            grid_point_memory[grid_point_index] =
+                closest_exterior_index as u32 + PREDECESSOR_OFFSET;
+        }
+    }
+
+    match min_distance {
+        Some(distance) => grid.probe_radius - distance,
+        // No exterior point found.
+        None => -grid.spacing,
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Write;
+
+use poms_common::models::mesh::SurfaceMesh;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const CHUNK_TYPE_JSON: &[u8; 4] = b"JSON";
+const CHUNK_TYPE_BIN: &[u8; 4] = b"BIN\0";
+
+const COMPONENT_TYPE_FLOAT: u32 = 5126;
+const COMPONENT_TYPE_UNSIGNED_INT: u32 = 5125;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Writes the mesh as a binary glTF 2.0 file (`.glb`) containing a single mesh with normals and optional vertex colors.
+/// Empty meshes are rejected, as the specification requires bounds of the positions, which they do not have.
+pub fn write_glb(mesh: &SurfaceMesh, writer: &mut impl Write) -> std::io::Result<()> {
+    if mesh.positions.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "The mesh is empty, so there is no surface to export",
+        ));
+    }
+
+    let mut binary = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+
+    let (min, max) = bounding_box(&mesh.positions);
+    let vertex_count = mesh.positions.len();
+
+    // Vertex attributes, in the order of `attributes` below.
+    let mut attributes = vec![
+        ("POSITION", flatten(&mesh.positions), "VEC3"),
+        ("NORMAL", flatten(&mesh.normals), "VEC3"),
+    ];
+    if let Some(colors) = &mesh.colors {
+        attributes.push(("COLOR_0", flatten(colors), "VEC4"));
+    }
+
+    let mut attributes_json = Vec::new();
+    for (name, values, accessor_type) in &attributes {
+        let view_index = push_buffer_view(
+            &mut binary,
+            &mut buffer_views,
+            bytemuck::cast_slice(values.as_slice()),
+            TARGET_ARRAY_BUFFER,
+        );
+        let bounds = match *name {
+            // The specification requires bounds for positions.
+            "POSITION" => format!(r#","min":{:?},"max":{:?}"#, min, max),
+            _ => String::new(),
+        };
+        attributes_json.push(format!(r#""{}":{}"#, name, accessors.len()));
+        accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"{}"{}}}"#,
+            view_index, COMPONENT_TYPE_FLOAT, vertex_count, accessor_type, bounds
+        ));
+    }
+
+    let indices_view = push_buffer_view(
+        &mut binary,
+        &mut buffer_views,
+        bytemuck::cast_slice(&mesh.indices),
+        TARGET_ELEMENT_ARRAY_BUFFER,
+    );
+    let indices_accessor = accessors.len();
+    accessors.push(format!(
+        r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+        indices_view,
+        COMPONENT_TYPE_UNSIGNED_INT,
+        mesh.indices.len()
+    ));
+
+    let json = format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"POMS"}},"#,
+            r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"molecular_surface"}}],"#,
+            r#""meshes":[{{"primitives":[{{"attributes":{{{}}},"indices":{},"mode":4}}]}}],"#,
+            r#""buffers":[{{"byteLength":{}}}],"bufferViews":[{}],"accessors":[{}]}}"#
+        ),
+        attributes_json.join(","),
+        indices_accessor,
+        binary.len(),
+        buffer_views.join(","),
+        accessors.join(","),
+    );
+
+    // Chunks have to be aligned to 4 bytes, JSON is padded with spaces and binary data with zeros.
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    binary.resize(binary.len().next_multiple_of(4), 0);
+
+    let total_length = 12 + 8 + json.len() + 8 + binary.len();
+
+    writer.write_all(GLB_MAGIC)?;
+    writer.write_all(&GLB_VERSION.to_le_bytes())?;
+    writer.write_all(&(total_length as u32).to_le_bytes())?;
+
+    for (chunk_type, chunk_data) in [(CHUNK_TYPE_JSON, &json), (CHUNK_TYPE_BIN, &binary)] {
+        writer.write_all(&(chunk_data.len() as u32).to_le_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(chunk_data)?;
+    }
+
+    Ok(())
+}
+
+/// Appends `data` to the binary buffer and describes it by a new buffer view. Returns the index of the view.
+fn push_buffer_view(
+    binary: &mut Vec<u8>,
+    buffer_views: &mut Vec<String>,
+    data: &[u8],
+    target: u32,
+) -> usize {
+    let offset = binary.len();
+    binary.extend_from_slice(data);
+    buffer_views.push(format!(
+        r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+        offset,
+        data.len(),
+        target
+    ));
+    buffer_views.len() - 1
+}
+
+fn flatten<const N: usize>(values: &[[f32; N]]) -> Vec<f32> {
+    values.iter().flatten().copied().collect()
+}
+
+fn bounding_box(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    positions
+        .iter()
+        .fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), position| {
+            (
+                [0, 1, 2].map(|i| f32::min(min[i], position[i])),
+                [0, 1, 2].map(|i| f32::max(max[i], position[i])),
+            )
+        })
+}
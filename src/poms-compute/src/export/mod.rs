@@ -0,0 +1,199 @@
+//! Writers of the surface mesh into common 3D file formats.
+
+mod gltf;
+mod obj;
+mod ply;
+mod stl;
+
+use std::io::Write;
+
+use poms_common::models::mesh::SurfaceMesh;
+
+pub use gltf::write_glb;
+pub use obj::write_obj;
+pub use ply::write_ply;
+pub use stl::write_stl;
+
+/// File formats the surface mesh can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Wavefront OBJ (text), vertex colors are written as the widely supported `v x y z r g b` extension.
+    Obj,
+    /// Binary PLY with normals and 8-bit vertex colors.
+    Ply,
+    /// Binary STL, suitable for 3D printing. Contains neither normals per vertex nor colors.
+    Stl,
+    /// Binary glTF 2.0 (`.glb`) with normals and vertex colors.
+    Glb,
+}
+
+impl ExportFormat {
+    /// All supported formats, in the order they should be offered to the user.
+    pub const ALL: [ExportFormat; 4] = [Self::Obj, Self::Ply, Self::Stl, Self::Glb];
+
+    /// Returns the file extension (without the leading dot) of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Obj => "obj",
+            Self::Ply => "ply",
+            Self::Stl => "stl",
+            Self::Glb => "glb",
+        }
+    }
+
+    /// Returns a human readable name of the format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Obj => "Wavefront OBJ",
+            Self::Ply => "Stanford PLY",
+            Self::Stl => "STL",
+            Self::Glb => "glTF Binary",
+        }
+    }
+
+    /// Finds the format matching the extension of the given file name (case insensitive).
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+}
+
+/// Writes the mesh in the given format.
+pub fn write_mesh(
+    mesh: &SurfaceMesh,
+    format: ExportFormat,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Obj => write_obj(mesh, writer),
+        ExportFormat::Ply => write_ply(mesh, writer),
+        ExportFormat::Stl => write_stl(mesh, writer),
+        ExportFormat::Glb => write_glb(mesh, writer),
+    }
+}
+
+/// Converts a color with components in the range `0.0..=1.0` to 8-bit RGB.
+fn color_to_rgb8(color: [f32; 4]) -> [u8; 3] {
+    [color[0], color[1], color[2]].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single triangle in the `xy` plane with a color per vertex.
+    fn triangle() -> SurfaceMesh {
+        SurfaceMesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            colors: Some(vec![[1.0, 0.5, 0.0, 1.0]; 3]),
+            indices: vec![0, 1, 2],
+        }
+    }
+
+    fn export(mesh: &SurfaceMesh, format: ExportFormat) -> Vec<u8> {
+        let mut content = Vec::new();
+        write_mesh(mesh, format, &mut content).unwrap();
+        content
+    }
+
+    fn u32_at(content: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes(content[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn glb_chunks_are_padded_to_four_bytes() {
+        let content = export(&triangle(), ExportFormat::Glb);
+        assert_eq!(&content[0..4], b"glTF");
+        assert_eq!(u32_at(&content, 4), 2);
+        assert_eq!(u32_at(&content, 8), content.len());
+
+        let json_length = u32_at(&content, 12);
+        assert_eq!(&content[16..20], b"JSON");
+        assert_eq!(json_length % 4, 0);
+        let json = std::str::from_utf8(&content[20..20 + json_length]).unwrap();
+        assert!(json.trim_end().ends_with('}'));
+
+        let binary_start = 20 + json_length;
+        let binary_length = u32_at(&content, binary_start);
+        assert_eq!(&content[binary_start + 4..binary_start + 8], b"BIN\0");
+        assert_eq!(binary_length % 4, 0);
+        assert_eq!(binary_start + 8 + binary_length, content.len());
+        // Positions, normals, colors and indices.
+        let data_length = 3 * 12 + 3 * 12 + 3 * 16 + 3 * 4;
+        assert!(json.contains(&format!(r#""buffers":[{{"byteLength":{}}}]"#, data_length)));
+    }
+
+    #[test]
+    fn ply_header_counts_match_the_body() {
+        let content = export(&triangle(), ExportFormat::Ply);
+        let header_end = b"end_header\n";
+        let body_start = content
+            .windows(header_end.len())
+            .position(|window| window == header_end)
+            .unwrap()
+            + header_end.len();
+        let header = std::str::from_utf8(&content[..body_start]).unwrap();
+
+        let count = |element: &str| {
+            header
+                .lines()
+                .find_map(|line| line.strip_prefix(&format!("element {} ", element)))
+                .unwrap()
+                .parse::<usize>()
+                .unwrap()
+        };
+        let (vertices, faces) = (count("vertex"), count("face"));
+        assert_eq!((vertices, faces), (3, 1));
+        // Position and normal floats with 8-bit colors, then the vertex count and indices of each face.
+        let body_length = vertices * (6 * 4 + 3) + faces * (1 + 3 * 4);
+        assert_eq!(content.len() - body_start, body_length);
+    }
+
+    #[test]
+    fn stl_triangle_count_matches_the_file_size() {
+        let content = export(&triangle(), ExportFormat::Stl);
+        let triangles = u32_at(&content, 80);
+        assert_eq!(triangles, 1);
+        assert_eq!(content.len(), 80 + 4 + triangles * 50);
+    }
+
+    #[test]
+    fn obj_indices_start_at_one() {
+        let content = String::from_utf8(export(&triangle(), ExportFormat::Obj)).unwrap();
+        let faces = content
+            .lines()
+            .filter(|line| line.starts_with("f "))
+            .collect::<Vec<_>>();
+        assert_eq!(faces, ["f 1//1 2//2 3//3"]);
+        assert_eq!(
+            content
+                .lines()
+                .filter(|line| line.starts_with("v "))
+                .count(),
+            3
+        );
+        assert_eq!(
+            content
+                .lines()
+                .filter(|line| line.starts_with("vn "))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn format_is_found_by_extension() {
+        assert_eq!(
+            ExportFormat::from_file_name("surface.GLB"),
+            Some(ExportFormat::Glb)
+        );
+        assert_eq!(
+            ExportFormat::from_file_name("surface.stl"),
+            Some(ExportFormat::Stl)
+        );
+        assert_eq!(ExportFormat::from_file_name("surface"), None);
+    }
+}
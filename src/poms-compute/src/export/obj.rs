@@ -0,0 +1,29 @@
+use std::io::Write;
+
+use poms_common::models::mesh::SurfaceMesh;
+
+/// Writes the mesh as a Wavefront OBJ file.
+pub fn write_obj(mesh: &SurfaceMesh, writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, "# Molecular surface exported by POMS")?;
+
+    for (i, [x, y, z]) in mesh.positions.iter().enumerate() {
+        match &mesh.colors {
+            Some(colors) => {
+                let [r, g, b, _] = colors[i];
+                writeln!(writer, "v {} {} {} {} {} {}", x, y, z, r, g, b)?;
+            }
+            None => writeln!(writer, "v {} {} {}", x, y, z)?,
+        }
+    }
+
+    for [x, y, z] in &mesh.normals {
+        writeln!(writer, "vn {} {} {}", x, y, z)?;
+    }
+
+    // OBJ indices start at 1.
+    for [a, b, c] in mesh.triangles().map(|triangle| triangle.map(|i| i + 1)) {
+        writeln!(writer, "f {a}//{a} {b}//{b} {c}//{c}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use poms_common::models::mesh::SurfaceMesh;
+
+/// Writes the mesh as a binary (little endian) PLY file. Vertex colors are written if present.
+pub fn write_ply(mesh: &SurfaceMesh, writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "comment Molecular surface exported by POMS")?;
+    writeln!(writer, "element vertex {}", mesh.positions.len())?;
+    for property in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(writer, "property float {}", property)?;
+    }
+    if mesh.colors.is_some() {
+        for property in ["red", "green", "blue"] {
+            writeln!(writer, "property uchar {}", property)?;
+        }
+    }
+    writeln!(writer, "element face {}", mesh.triangles_count())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for (i, (position, normal)) in mesh.positions.iter().zip(&mesh.normals).enumerate() {
+        for value in position.iter().chain(normal) {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        if let Some(colors) = &mesh.colors {
+            writer.write_all(&super::color_to_rgb8(colors[i]))?;
+        }
+    }
+
+    for triangle in mesh.triangles() {
+        writer.write_all(&[3u8])?;
+        for index in triangle {
+            writer.write_all(&index.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use cgmath::{InnerSpace, Vector3};
+use poms_common::models::mesh::SurfaceMesh;
+
+/// Writes the mesh as a binary STL file. Each triangle stores its face normal, vertex attributes are not supported by the format.
+pub fn write_stl(mesh: &SurfaceMesh, writer: &mut impl Write) -> std::io::Result<()> {
+    let mut header = [0u8; 80];
+    let title = b"Molecular surface exported by POMS";
+    header[..title.len()].copy_from_slice(title);
+    writer.write_all(&header)?;
+    writer.write_all(&(mesh.triangles_count() as u32).to_le_bytes())?;
+
+    for triangle in mesh.triangles() {
+        let [a, b, c] = triangle.map(|i| Vector3::from(mesh.positions[i as usize]));
+        let normal = (b - a).cross(c - a);
+        let normal = match normal.magnitude2() > 0.0 {
+            true => normal.normalize(),
+            false => normal,
+        };
+
+        for vector in [normal, a, b, c] {
+            for value in [vector.x, vector.y, vector.z] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        // Attribute byte count, unused.
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,504 @@
+use cgmath::{Point3, Vector3};
+use poms_common::limits::{MAX_DISTANCE_FIELD_RESOLUTION, MIN_DISTANCE_FIELD_RESOLUTION};
+use poms_common::models::atom::{Atom, AtomsWithLookup};
+use poms_common::models::grid::{create_compute_grid_around_molecule, GridUniform};
+use poms_common::resources::df_texture::DF_TEXTURE_FORMAT;
+use poms_common::resources::CommonResources;
+
+use crate::{cpu, ComputeJobs, ComputeParameters};
+
+/// Things required to compute a distance field without a window.
+pub struct HeadlessParameters<'a> {
+    /// The molecule to compute the surface of.
+    pub molecule: &'a [Atom],
+    /// Radius of the probe used to compute the molecular surface.
+    pub probe_radius: f32,
+    /// Resolution of the resulting distance field,
+    /// between `MIN_DISTANCE_FIELD_RESOLUTION` and `MAX_DISTANCE_FIELD_RESOLUTION`.
+    pub target_resolution: u32,
+}
+
+impl HeadlessParameters<'_> {
+    /// Checks that there is a surface to compute and that the target resolution is supported.
+    pub(crate) fn validate(&self) -> Result<(), HeadlessError> {
+        if self.molecule.is_empty() {
+            return Err(HeadlessError::EmptyMolecule);
+        }
+        if !(MIN_DISTANCE_FIELD_RESOLUTION..=MAX_DISTANCE_FIELD_RESOLUTION)
+            .contains(&self.target_resolution)
+        {
+            return Err(HeadlessError::InvalidResolution(self.target_resolution));
+        }
+        Ok(())
+    }
+}
+
+/// Device and queue that are not tied to any surface, along with the info of their adapter.
+pub struct HeadlessDevice {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// Decides whether the compute passes can run on the adapter, see `supports_compute_passes`.
+    pub adapter_info: wgpu::AdapterInfo,
+}
+
+/// Signed distance field of a molecular surface stored on the CPU, e.g. read back from the GPU.
+pub struct DistanceFieldData {
+    /// The grid the distance field was computed on.
+    pub grid: GridUniform,
+    /// Signed distance of each grid point, laid out as `x + y * resolution + z * resolution^2`.
+    pub values: Vec<f32>,
+}
+
+impl DistanceFieldData {
+    /// Returns the signed distance at the given grid point.
+    pub fn value_at(&self, x: u32, y: u32, z: u32) -> f32 {
+        let r = self.grid.resolution as usize;
+        self.values[x as usize + y as usize * r + z as usize * r * r]
+    }
+
+    /// Returns the largest absolute difference between the values of two distance fields.
+    /// Returns `None` if the fields were not computed on grids of the same resolution.
+    ///
+    /// Useful to compare the GPU output against `cpu::compute_distance_field_on_grid` within a tolerance.
+    pub fn max_abs_difference(&self, other: &DistanceFieldData) -> Option<f32> {
+        if self.grid.resolution != other.grid.resolution {
+            return None;
+        }
+        let difference = self
+            .values
+            .iter()
+            .zip(&other.values)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max);
+        Some(difference)
+    }
+}
+
+/// Errors that may occur while computing the distance field headlessly.
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// The molecule has no atoms, so there is no surface to compute.
+    EmptyMolecule,
+    /// The target resolution is outside of the supported range.
+    InvalidResolution(u32),
+    /// No adapter matching the requested options was found.
+    AdapterNotFound,
+    /// The adapter was found, but the device could not be created.
+    DeviceRequestFailed(wgpu::RequestDeviceError),
+    /// The distance field texture could not be read back to the CPU.
+    ReadbackFailed,
+}
+
+impl std::fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMolecule => write!(f, "The molecule does not contain any atoms"),
+            Self::InvalidResolution(resolution) => write!(
+                f,
+                "The resolution has to be between {} and {}, got {}",
+                MIN_DISTANCE_FIELD_RESOLUTION, MAX_DISTANCE_FIELD_RESOLUTION, resolution
+            ),
+            Self::AdapterNotFound => write!(f, "Failed to find a suitable adapter"),
+            Self::DeviceRequestFailed(e) => write!(f, "Failed to create device: {}", e),
+            Self::ReadbackFailed => write!(f, "Failed to read the distance field from the GPU"),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// Requests an adapter that is not tied to any surface.
+/// Set `force_fallback_adapter` to use a software adapter, e.g. on machines without a GPU.
+pub async fn request_adapter(force_fallback_adapter: bool) -> Result<wgpu::Adapter, HeadlessError> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+
+    instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter,
+        })
+        .await
+        .ok_or(HeadlessError::AdapterNotFound)
+}
+
+/// Requests a device and a queue that are not tied to any surface.
+/// Set `force_fallback_adapter` to use a software adapter, e.g. on machines without a GPU.
+pub async fn request_device(force_fallback_adapter: bool) -> Result<HeadlessDevice, HeadlessError> {
+    let adapter = request_adapter(force_fallback_adapter).await?;
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor::default(), None)
+        .await
+        .map_err(HeadlessError::DeviceRequestFailed)?;
+
+    Ok(HeadlessDevice {
+        device,
+        queue,
+        adapter_info: adapter.get_info(),
+    })
+}
+
+/// Returns whether the compute passes produce the whole distance field on the adapter.
+///
+/// The OpenGL backend (e.g. llvmpipe, the usual software adapter on Linux) binds only the first layer of the 3D
+/// storage texture, so the remaining layers are never written. The distance field is computed on the CPU there instead.
+pub fn supports_compute_passes(adapter_info: &wgpu::AdapterInfo) -> bool {
+    adapter_info.backend != wgpu::Backend::Gl
+}
+
+/// Runs the probe and refinement passes to completion and reads the resulting distance field back to the CPU.
+///
+/// Unlike `ComputeJobs` driven by the application, the passes are submitted back to back until the target resolution is reached.
+/// On adapters that cannot run the compute passes (see `supports_compute_passes`), the distance field is computed on the CPU.
+pub async fn compute_distance_field(
+    gpu: &HeadlessDevice,
+    params: HeadlessParameters<'_>,
+) -> Result<DistanceFieldData, HeadlessError> {
+    if !supports_compute_passes(&gpu.adapter_info) {
+        return cpu::compute_distance_field(params);
+    }
+
+    let mut common_resources = CommonResources::new(&gpu.device);
+    let (texture, grid) = compute_distance_field_texture(gpu, &mut common_resources, params)?;
+
+    read_distance_field(&gpu.device, &gpu.queue, &texture, grid).await
+}
+
+/// Runs the probe and refinement passes to completion and returns the distance field texture with its grid,
+/// leaving it on the GPU, e.g. to render the molecular surface without a window.
+/// On adapters that cannot run the compute passes, the distance field is computed on the CPU and uploaded.
+///
+/// The molecule is uploaded to `common_resources`, so they can be shared with the render passes afterwards.
+pub fn compute_distance_field_texture(
+    gpu: &HeadlessDevice,
+    common_resources: &mut CommonResources,
+    params: HeadlessParameters<'_>,
+) -> Result<(wgpu::Texture, GridUniform), HeadlessError> {
+    params.validate()?;
+
+    let HeadlessDevice { device, queue, .. } = gpu;
+    let target_resolution = params.target_resolution;
+
+    let atoms = AtomsWithLookup::new(params.molecule.to_vec(), params.probe_radius);
+    common_resources.atoms_resource.update(queue, &atoms);
+
+    if !supports_compute_passes(&gpu.adapter_info) {
+        let grid = create_compute_grid_around_molecule(
+            params.molecule,
+            target_resolution,
+            params.probe_radius,
+        );
+        let distance_field = cpu::compute_distance_field_on_grid(&atoms, grid);
+        let texture = upload_distance_field(device, queue, &distance_field, Some(&atoms));
+        return Ok((texture, grid));
+    }
+
+    let mut compute_jobs = ComputeJobs::new(
+        device,
+        ComputeParameters {
+            molecule: &atoms.data,
+            common_resources,
+            init_resolution: MIN_DISTANCE_FIELD_RESOLUTION,
+            target_resolution,
+            probe_radius: params.probe_radius,
+        },
+    );
+
+    let mut last_computed_distance_field = None;
+
+    while !compute_jobs.is_finished() {
+        compute_jobs.update_buffers(queue);
+
+        let mut encoder = create_command_encoder(device);
+        compute_jobs.execute(&mut encoder, device, common_resources);
+        queue.submit(Some(encoder.finish()));
+
+        // Intermediate resolutions are replaced by the next ones, only the last one is kept.
+        if let Some(distance_field) = compute_jobs.last_computed_distance_field() {
+            last_computed_distance_field = Some(distance_field);
+        }
+    }
+
+    last_computed_distance_field.ok_or(HeadlessError::ReadbackFailed)
+}
+
+/// Reads a distance field texture computed on the given grid back to the CPU.
+pub async fn read_distance_field(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    grid: GridUniform,
+) -> Result<DistanceFieldData, HeadlessError> {
+    let mut readback = DistanceFieldReadback::start(device, queue, texture, grid);
+
+    // On native, the callback is only invoked when the device is polled. On the web, this is a no-op.
+    device.poll(wgpu::Maintain::Wait);
+
+    (&mut readback.receiver)
+        .await
+        .map_err(|_| HeadlessError::ReadbackFailed)?
+        .map_err(|_| HeadlessError::ReadbackFailed)?;
+
+    Ok(readback.read_mapped())
+}
+
+/// A distance field texture being copied into a mappable buffer, which is read once the GPU has finished the copy.
+///
+/// Unlike `read_distance_field`, it never blocks, so it can be polled once per frame by an application,
+/// e.g. on the web where the main thread cannot wait for the GPU.
+pub struct DistanceFieldReadback {
+    buffer: wgpu::Buffer,
+    /// Resolution of the copied texture.
+    resolution: u32,
+    grid: GridUniform,
+    receiver: futures::channel::oneshot::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl DistanceFieldReadback {
+    /// Submits the copy of the texture computed on the given grid and requests the buffer to be mapped.
+    pub fn start(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        grid: GridUniform,
+    ) -> Self {
+        let resolution = texture.depth_or_array_layers();
+        let padded_bytes_per_row = padded_bytes_per_row(resolution);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("distance_field_readback_buffer"),
+            size: (padded_bytes_per_row * resolution * resolution) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = create_command_encoder(device);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(resolution),
+                },
+            },
+            texture.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                sender.send(result).ok();
+            });
+
+        Self {
+            buffer,
+            resolution,
+            grid,
+            receiver,
+        }
+    }
+
+    /// Returns the distance field once the copy is finished, or `None` while the GPU is still working on it.
+    /// On native platforms, the device has to be polled (e.g. with `wgpu::Maintain::Poll`) for the copy to finish.
+    pub fn try_finish(&mut self) -> Option<Result<DistanceFieldData, HeadlessError>> {
+        let result = match self.receiver.try_recv() {
+            Ok(None) => return None,
+            Ok(Some(result)) => result.map_err(|_| HeadlessError::ReadbackFailed),
+            Err(_) => Err(HeadlessError::ReadbackFailed),
+        };
+        Some(result.map(|()| self.read_mapped()))
+    }
+
+    /// Converts the `.r` channel of each texel of the mapped buffer to `f32`.
+    fn read_mapped(&self) -> DistanceFieldData {
+        let resolution = self.resolution;
+        let unpadded_bytes_per_row = resolution * BYTES_PER_TEXEL;
+        let padded_bytes_per_row = padded_bytes_per_row(resolution);
+
+        let values = {
+            let mapped_data = self.buffer.slice(..).get_mapped_range();
+            mapped_data
+                .chunks_exact(padded_bytes_per_row as usize)
+                .flat_map(|row| {
+                    row[..unpadded_bytes_per_row as usize]
+                        .chunks_exact(BYTES_PER_TEXEL as usize)
+                        .map(|texel| f16_to_f32(u16::from_le_bytes([texel[0], texel[1]])))
+                })
+                .collect()
+        };
+        self.buffer.unmap();
+
+        DistanceFieldData {
+            grid: self.grid,
+            values,
+        }
+    }
+}
+
+/// Convenience wrapper around `request_device` and `compute_distance_field` that blocks the current thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compute_distance_field_blocking(
+    params: HeadlessParameters<'_>,
+    force_fallback_adapter: bool,
+) -> Result<DistanceFieldData, HeadlessError> {
+    futures::executor::block_on(async {
+        let gpu = request_device(force_fallback_adapter).await?;
+        compute_distance_field(&gpu, params).await
+    })
+}
+
+/// Size of a single texel of the distance field texture (four 16-bit floats).
+const BYTES_PER_TEXEL: u32 = 8;
+
+/// Half floats represent integers up to 2048 exactly, so the atom index is split into two parts, see `refinement.wgsl`.
+const ATOM_INDEX_PART: usize = 2048;
+
+/// Uploads a distance field computed on the CPU into a texture the render passes can sample, in the layout written by
+/// `refinement.wgsl`. Used on adapters that cannot run the compute passes, see `supports_compute_passes`.
+///
+/// If `atoms` are provided, the grid points around the surface store their nearest atom, so the surface is colored by atoms.
+/// The atoms have to be in the same order as those uploaded to `CommonResources`.
+pub fn upload_distance_field(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    distance_field: &DistanceFieldData,
+    atoms: Option<&AtomsWithLookup>,
+) -> wgpu::Texture {
+    let grid = &distance_field.grid;
+    let resolution = grid.resolution;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("uploaded_distance_field_texture"),
+        size: wgpu::Extent3d {
+            width: resolution,
+            height: resolution,
+            depth_or_array_layers: resolution,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D3,
+        format: DF_TEXTURE_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    // The surface color blends the atoms of the grid points around the surface, the others need none.
+    let max_surface_distance = 2.0 * grid.spacing;
+    let origin = Point3::new(grid.origin[0], grid.origin[1], grid.origin[2]);
+    let r = resolution as usize;
+
+    let texels = distance_field
+        .values
+        .iter()
+        .enumerate()
+        .flat_map(|(index, &distance)| {
+            let nearest_atom = atoms
+                .filter(|_| distance.abs() <= max_surface_distance)
+                .and_then(|atoms| {
+                    let offset = Vector3::new(index % r, (index / r) % r, index / (r * r))
+                        .map(|coordinate| coordinate as f32 * grid.spacing);
+                    atoms.nearest_atom(origin + offset)
+                });
+            let [g, b, a] = match nearest_atom {
+                Some(atom) => [
+                    (atom % ATOM_INDEX_PART) as f32,
+                    (atom / ATOM_INDEX_PART) as f32,
+                    1.0,
+                ],
+                None => [0.0; 3],
+            };
+            [distance, g, b, a].map(|value| f32_to_f16(value).to_le_bytes())
+        })
+        .flatten()
+        .collect::<Vec<u8>>();
+
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(resolution * BYTES_PER_TEXEL),
+            rows_per_image: Some(resolution),
+        },
+        texture.size(),
+    );
+
+    texture
+}
+
+/// Rows copied from a texture have to be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn padded_bytes_per_row(resolution: u32) -> u32 {
+    (resolution * BYTES_PER_TEXEL).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+fn create_command_encoder(device: &wgpu::Device) -> wgpu::CommandEncoder {
+    device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("headless_command_encoder"),
+    })
+}
+
+/// Converts `f32` to a half-precision float, rounding to the nearest representable value.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    // Infinity or NaN, NaN keeps a non-zero mantissa.
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    // Re-bias the exponent from 127 to 15.
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        // Too large, rounds to infinity.
+        return sign | 0x7c00;
+    }
+    if exponent <= 0 {
+        // Too small even for a subnormal number, rounds to zero.
+        if exponent < -10 {
+            return sign;
+        }
+        // Subnormal number, the value is `mantissa * 2^-24` including the implicit leading bit.
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let rounding = (mantissa >> (shift - 1)) & 1;
+        return sign | ((mantissa >> shift) + rounding) as u16;
+    }
+
+    // A carry out of the mantissa while rounding correctly increments the exponent.
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let rounding = (mantissa >> 12) & 1;
+    sign | (half + rounding) as u16
+}
+
+/// Converts a half-precision float (as stored in `Rgba16Float` textures) to `f32`.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+
+    let bits = match exponent {
+        // Zero or subnormal number, the value is `mantissa * 2^-24`.
+        0 => {
+            let magnitude = mantissa as f32 * f32::powi(2.0, -24);
+            return if sign == 0 { magnitude } else { -magnitude };
+        }
+        // Infinity or NaN.
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        // Normal number, re-bias the exponent from 15 to 127.
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+
+    f32::from_bits(bits)
+}
@@ -1,3 +1,8 @@
+pub mod cpu;
+pub mod export;
+pub mod headless;
+pub mod measure;
+pub mod mesh;
 mod passes;
 mod resources;
 mod state;
@@ -144,6 +149,11 @@ impl ComputeJobs {
         self.state.progress()
     }
 
+    /// Returns `true` once the distance field of the target resolution has been computed.
+    pub fn is_finished(&self) -> bool {
+        self.state.current_phase == ComputePhase::Finished
+    }
+
     /// Called after a refinement phase to swap the texture with upscaled resolution
     /// while keeping the old texture for potential rendering
     fn swap_out_df_texture(&mut self, device: &wgpu::Device) {
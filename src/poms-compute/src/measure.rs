@@ -0,0 +1,138 @@
+//! Area and volume of the solvent-accessible surface (SAS), the surface traced by the center of the probe rolling over the atoms.
+//!
+//! Uses the Shrake-Rupley algorithm: points are spread evenly over the sphere of each atom inflated by the probe radius,
+//! and the points not buried within any other inflated sphere are accessible. Each accessible point stands for an equal
+//! share of the sphere area, and the volume follows from the same points by the divergence theorem.
+//!
+//! The area and volume of the solvent-excluded surface are measured on its mesh instead, see `SurfaceMesh::area`.
+
+use std::collections::HashMap;
+
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+use poms_common::models::atom::{calculate_center, Atom};
+
+/// Number of points spread over the sphere of each atom, as in the original algorithm.
+const POINTS_PER_SPHERE: usize = 960;
+
+/// Area and volume enclosed by a surface, in Å² and Å³.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SurfaceMeasures {
+    pub area: f32,
+    pub volume: f32,
+}
+
+/// Measures the solvent-accessible surface of the atoms, whose spheres are inflated by the probe radius.
+pub fn measure_solvent_accessible_surface(atoms: &[Atom], probe_radius: f32) -> SurfaceMeasures {
+    if atoms.is_empty() {
+        return SurfaceMeasures::default();
+    }
+
+    // Positions are taken relative to the center, so the volume terms of distant molecules do not lose precision.
+    let center = calculate_center(atoms);
+    let spheres = atoms
+        .iter()
+        .map(|atom| Sphere {
+            center: Point3::from(atom.position) - center.to_vec(),
+            radius: atom.radius + probe_radius,
+        })
+        .collect::<Vec<_>>();
+    let unit_points = fibonacci_sphere(POINTS_PER_SPHERE);
+    let neighbors = NeighborGrid::new(&spheres);
+
+    let (mut area, mut volume) = (0.0f64, 0.0f64);
+    let mut nearby = Vec::new();
+    for (index, sphere) in spheres.iter().enumerate() {
+        nearby.clear();
+        nearby.extend(
+            neighbors
+                .around(sphere.center)
+                .filter(|&other| other != index && sphere.overlaps(&spheres[other])),
+        );
+
+        let point_area =
+            4.0 * std::f64::consts::PI * (sphere.radius as f64).powi(2) / POINTS_PER_SPHERE as f64;
+        for normal in &unit_points {
+            let point = sphere.center + normal * sphere.radius;
+            let is_buried = nearby.iter().any(|&other| spheres[other].contains(point));
+            if !is_buried {
+                area += point_area;
+                // The volume is a third of the flux of the position through the surface.
+                volume += point_area * point.to_vec().dot(*normal) as f64 / 3.0;
+            }
+        }
+    }
+
+    SurfaceMeasures {
+        area: area as f32,
+        volume: volume as f32,
+    }
+}
+
+struct Sphere {
+    center: Point3<f32>,
+    radius: f32,
+}
+
+impl Sphere {
+    fn overlaps(&self, other: &Sphere) -> bool {
+        (self.center - other.center).magnitude2() < (self.radius + other.radius).powi(2)
+    }
+
+    fn contains(&self, point: Point3<f32>) -> bool {
+        (point - self.center).magnitude2() < self.radius * self.radius
+    }
+}
+
+/// Spreads the given number of points evenly over the unit sphere along the golden spiral.
+fn fibonacci_sphere(count: usize) -> Vec<Vector3<f32>> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..count)
+        .map(|i| {
+            let z = 1.0 - (2.0 * i as f32 + 1.0) / count as f32;
+            let radius = (1.0 - z * z).sqrt();
+            let angle = golden_angle * i as f32;
+            Vector3::new(radius * angle.cos(), radius * angle.sin(), z)
+        })
+        .collect()
+}
+
+/// Uniform grid of spheres with cells as large as the largest sphere diameter,
+/// so overlapping spheres are always found in the neighboring cells.
+struct NeighborGrid {
+    cell_size: f32,
+    cells: HashMap<[i32; 3], Vec<usize>>,
+}
+
+impl NeighborGrid {
+    fn new(spheres: &[Sphere]) -> Self {
+        let max_radius = spheres
+            .iter()
+            .map(|sphere| sphere.radius)
+            .fold(0.0, f32::max);
+        let cell_size = (2.0 * max_radius).max(f32::EPSILON);
+
+        let mut cells: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+        for (index, sphere) in spheres.iter().enumerate() {
+            cells
+                .entry(Self::cell_of(sphere.center, cell_size))
+                .or_default()
+                .push(index);
+        }
+        Self { cell_size, cells }
+    }
+
+    fn cell_of(position: Point3<f32>, cell_size: f32) -> [i32; 3] {
+        [position.x, position.y, position.z]
+            .map(|coordinate| (coordinate / cell_size).floor() as i32)
+    }
+
+    /// Iterates over the indices of the spheres in the cell of the position and the cells around it.
+    fn around(&self, position: Point3<f32>) -> impl Iterator<Item = usize> + '_ {
+        let [x, y, z] = Self::cell_of(position, self.cell_size);
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [dx, dy, dz])))
+            .filter_map(move |[dx, dy, dz]| self.cells.get(&[x + dx, y + dy, z + dz]))
+            .flatten()
+            .copied()
+    }
+}
@@ -0,0 +1,260 @@
+//! Extraction of a triangle mesh from the signed distance field.
+//!
+//! Uses dual contouring in its simplest form (also known as surface nets): each grid cell crossed by the surface
+//! gets a single vertex placed at the mass point of the edge crossings, and each grid edge crossed by the surface
+//! connects the four cells around it with a quad. The resulting mesh is indexed and shares vertices between triangles.
+
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+use poms_common::models::atom::AtomsWithLookup;
+use poms_common::models::mesh::SurfaceMesh;
+
+use crate::headless::DistanceFieldData;
+
+/// Value of the distance field at which the molecular surface lies.
+const ISO_VALUE: f32 = 0.0;
+
+/// Offsets of the eight corners of a cell.
+const CELL_CORNERS: [[u32; 3]; 8] = [
+    [0, 0, 0],
+    [1, 0, 0],
+    [0, 1, 0],
+    [1, 1, 0],
+    [0, 0, 1],
+    [1, 0, 1],
+    [0, 1, 1],
+    [1, 1, 1],
+];
+
+/// Pairs of corners (indices into `CELL_CORNERS`) forming the twelve edges of a cell.
+const CELL_EDGES: [[usize; 2]; 12] = [
+    [0, 1],
+    [2, 3],
+    [4, 5],
+    [6, 7],
+    [0, 2],
+    [1, 3],
+    [4, 6],
+    [5, 7],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+/// Extracts the molecular surface (the zero level set) from the distance field as an indexed triangle mesh.
+///
+/// Normals are computed from the gradient of the distance field. If `atoms` are provided,
+/// each vertex is colored by the atom nearest to it.
+pub fn extract_surface_mesh(
+    distance_field: &DistanceFieldData,
+    atoms: Option<&AtomsWithLookup>,
+) -> SurfaceMesh {
+    let field = FieldSampler::new(distance_field);
+    let cells_resolution = field.resolution.saturating_sub(1);
+
+    let mut mesh = SurfaceMesh::default();
+
+    // For each cell, the index of the vertex placed within it (if the surface crosses the cell).
+    let mut vertex_by_cell = vec![None; cells_resolution.pow(3) as usize];
+
+    for z in 0..cells_resolution {
+        for y in 0..cells_resolution {
+            for x in 0..cells_resolution {
+                if let Some(position) = field.cell_vertex_position([x, y, z]) {
+                    let cell_index = field.cell_index([x, y, z]);
+                    vertex_by_cell[cell_index] = Some(mesh.positions.len() as u32);
+
+                    mesh.normals.push(field.normal_at(position).into());
+                    mesh.positions.push(field.to_world_space(position).into());
+                }
+            }
+        }
+    }
+
+    // Connect the vertices of the four cells sharing each edge crossed by the surface.
+    for z in 1..cells_resolution {
+        for y in 1..cells_resolution {
+            for x in 1..cells_resolution {
+                for axis in 0..3 {
+                    if let Some(quad) = field.edge_quad([x, y, z], axis, &vertex_by_cell) {
+                        mesh.indices.extend_from_slice(&[
+                            quad[0], quad[1], quad[2], quad[0], quad[2], quad[3],
+                        ]);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(atoms) = atoms {
+        mesh.colors = Some(color_by_nearest_atom(&mesh.positions, atoms));
+    }
+
+    mesh
+}
+
+/// Assigns each vertex the color of the atom nearest to it. Vertices with no atom nearby are colored white.
+fn color_by_nearest_atom(positions: &[[f32; 3]], atoms: &AtomsWithLookup) -> Vec<[f32; 4]> {
+    const DEFAULT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    positions
+        .iter()
+        .map(|&position| {
+            atoms
+                .nearest_atom(Point3::from(position))
+                .map_or(DEFAULT_COLOR, |index| atoms.data[index].color)
+        })
+        .collect()
+}
+
+/// Helper for sampling the distance field at grid points and at arbitrary positions in grid space.
+struct FieldSampler<'a> {
+    distance_field: &'a DistanceFieldData,
+    resolution: u32,
+}
+
+impl<'a> FieldSampler<'a> {
+    fn new(distance_field: &'a DistanceFieldData) -> Self {
+        Self {
+            distance_field,
+            resolution: distance_field.grid.resolution,
+        }
+    }
+
+    fn value_at(&self, [x, y, z]: [u32; 3]) -> f32 {
+        self.distance_field.value_at(x, y, z)
+    }
+
+    fn cell_index(&self, [x, y, z]: [u32; 3]) -> usize {
+        let r = (self.resolution - 1) as usize;
+        x as usize + y as usize * r + z as usize * r * r
+    }
+
+    /// Places the vertex of a cell at the mass point of the crossings of its edges, in grid space.
+    /// Returns `None` if the surface does not cross the cell.
+    fn cell_vertex_position(&self, cell: [u32; 3]) -> Option<Point3<f32>> {
+        let corners = CELL_CORNERS.map(|[dx, dy, dz]| [cell[0] + dx, cell[1] + dy, cell[2] + dz]);
+        let values = corners.map(|corner| self.value_at(corner));
+
+        let mut crossings_sum = Vector3::new(0.0, 0.0, 0.0);
+        let mut crossings_count = 0;
+
+        for [a, b] in CELL_EDGES {
+            if (values[a] < ISO_VALUE) == (values[b] < ISO_VALUE) {
+                continue;
+            }
+            // Linearly interpolate the position of the crossing along the edge.
+            let t = (ISO_VALUE - values[a]) / (values[b] - values[a]);
+            let start = Vector3::from(corners[a].map(|c| c as f32));
+            let end = Vector3::from(corners[b].map(|c| c as f32));
+            crossings_sum += start + (end - start) * t;
+            crossings_count += 1;
+        }
+
+        match crossings_count {
+            0 => None,
+            _ => Some(Point3::from_vec(crossings_sum / crossings_count as f32)),
+        }
+    }
+
+    /// If the surface crosses the grid edge starting at `point` along `axis`, returns the quad connecting
+    /// the four cells around the edge, wound counter-clockwise when viewed from outside the surface.
+    fn edge_quad(
+        &self,
+        point: [u32; 3],
+        axis: usize,
+        vertex_by_cell: &[Option<u32>],
+    ) -> Option<[u32; 4]> {
+        let mut end = point;
+        end[axis] += 1;
+        if end[axis] >= self.resolution {
+            return None;
+        }
+
+        let is_start_inside = self.value_at(point) < ISO_VALUE;
+        if is_start_inside == (self.value_at(end) < ISO_VALUE) {
+            return None;
+        }
+
+        // The other two axes, in cyclic order so that (axis, u, v) forms a right-handed system.
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let cell_at = |du: u32, dv: u32| {
+            let mut cell = point;
+            cell[u] -= du;
+            cell[v] -= dv;
+            vertex_by_cell[self.cell_index(cell)]
+        };
+
+        let quad = [
+            cell_at(1, 1)?,
+            cell_at(0, 1)?,
+            cell_at(0, 0)?,
+            cell_at(1, 0)?,
+        ];
+
+        // The quad faces towards positive `axis`, which is outwards only if the surface is left when moving along it.
+        match is_start_inside {
+            true => Some(quad),
+            false => Some([quad[3], quad[2], quad[1], quad[0]]),
+        }
+    }
+
+    /// Computes the normal at a position in grid space from the trilinearly interpolated gradient of the field.
+    fn normal_at(&self, position: Point3<f32>) -> Vector3<f32> {
+        let max = (self.resolution - 1) as f32;
+        let base = [position.x, position.y, position.z].map(|c| c.floor().clamp(0.0, max) as u32);
+        let fraction = Vector3::new(
+            position.x - base[0] as f32,
+            position.y - base[1] as f32,
+            position.z - base[2] as f32,
+        );
+
+        let mut gradient = Vector3::new(0.0, 0.0, 0.0);
+        for [dx, dy, dz] in CELL_CORNERS {
+            let corner = [
+                u32::min(base[0] + dx, self.resolution - 1),
+                u32::min(base[1] + dy, self.resolution - 1),
+                u32::min(base[2] + dz, self.resolution - 1),
+            ];
+            let weight = (if dx == 1 {
+                fraction.x
+            } else {
+                1.0 - fraction.x
+            }) * (if dy == 1 {
+                fraction.y
+            } else {
+                1.0 - fraction.y
+            }) * (if dz == 1 {
+                fraction.z
+            } else {
+                1.0 - fraction.z
+            });
+            gradient += self.gradient_at(corner) * weight;
+        }
+
+        if gradient.magnitude2() > 0.0 {
+            gradient.normalize()
+        } else {
+            gradient
+        }
+    }
+
+    /// Computes the gradient of the field at a grid point using central differences (one-sided at the borders).
+    fn gradient_at(&self, point: [u32; 3]) -> Vector3<f32> {
+        let derivative = |axis: usize| {
+            let mut previous = point;
+            let mut next = point;
+            previous[axis] = previous[axis].saturating_sub(1);
+            next[axis] = u32::min(next[axis] + 1, self.resolution - 1);
+            let steps = (next[axis] - previous[axis]).max(1) as f32;
+            (self.value_at(next) - self.value_at(previous)) / steps
+        };
+        Vector3::new(derivative(0), derivative(1), derivative(2))
+    }
+
+    /// Converts a position in grid space to world space.
+    fn to_world_space(&self, position: Point3<f32>) -> Point3<f32> {
+        let grid = &self.distance_field.grid;
+        let origin = Point3::new(grid.origin[0], grid.origin[1], grid.origin[2]);
+        origin + position.to_vec() * grid.spacing
+    }
+}
@@ -3,7 +3,8 @@ use poms_common::resources::df_texture::{create_distance_field_texture, DF_TEXTU
 use wgpu::util::DeviceExt;
 
 /// The signed distance field produced by the probe and refinement steps.
-/// Each voxel in the distance field is a signed distance to the nearest surface.
+/// Each voxel in the distance field is a signed distance to the nearest surface (`r` channel),
+/// along with the index of the atom nearest to the grid point split across the `g` and `b` channels (`a` is zero if there is none).
 pub struct DistanceField {
     pub grid_buffer: wgpu::Buffer,
     pub texture: wgpu::Texture,
@@ -4,6 +4,8 @@ use wgpu::util::DeviceExt;
 /// This resource also stores the index offset in the buffer since the computation on grid points is split across multiple frames.
 pub struct GridPointsResource {
     pub grid_point_memory_buffer: wgpu::Buffer,
+    /// Index of the atom nearest to each grid point, found during the probe step and written to the texture during refinement.
+    pub grid_point_atom_buffer: wgpu::Buffer,
     pub grid_point_index_offset_buffer: wgpu::Buffer,
 
     pub bind_group_layout: wgpu::BindGroupLayout,
@@ -24,6 +26,12 @@ impl GridPointsResource {
                 usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
             });
 
+        let grid_point_atom_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("grid_point_atom_storage_buffer"),
+            contents: bytemuck::cast_slice(&vec![0u32; grid_points_memory_size]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
         let grid_point_index_offset_buffer =
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("grid_point_index_offset_uniform_buffer"),
@@ -44,12 +52,17 @@ impl GridPointsResource {
                     binding: 1,
                     resource: grid_point_index_offset_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: grid_point_atom_buffer.as_entire_binding(),
+                },
             ],
             label: Some("grid_points_bind_group"),
         });
 
         Self {
             grid_point_memory_buffer,
+            grid_point_atom_buffer,
             grid_point_index_offset_buffer,
             bind_group_layout,
             bind_group,
@@ -88,6 +101,16 @@ const LAYOUT_DESCRIPTOR: wgpu::BindGroupLayoutDescriptor<'static> =
                 },
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
         label: Some("grid_points_bind_group_layout"),
     };
@@ -22,6 +22,7 @@ struct AtomSegment {
 // Distance Field Grid Points Resource
 @group(0) @binding(0) var<storage, read_write> df_grid_point_memory: array<u32>;
 @group(0) @binding(1) var<uniform> df_grid_point_index_offset: u32;
+@group(0) @binding(2) var<storage, read_write> df_grid_point_atom: array<u32>;
 
 // Distance Field Resource
 @group(1) @binding(0) var<uniform> df_grid: GridUniform;
@@ -36,6 +37,9 @@ const EXTERIOR_GRID_POINT: u32 = 0u;
 const INTERIOR_GRID_POINT: u32 = 1u;
 const BOUNDARY_GRID_POINT: u32 = 2u;
 
+const NO_ATOM: u32 = 0xffffffffu;
+const HUGE_DISTANCE: f32 = 100000.0;
+
 
 @compute @workgroup_size(64)
 fn main(
@@ -49,6 +53,7 @@ fn main(
 
     // Initialize the grid point memory to EXTERIOR
     df_grid_point_memory[grid_point_index] = EXTERIOR_GRID_POINT;
+    df_grid_point_atom[grid_point_index] = NO_ATOM;
 
     // Compute the grid position
     let grid_point: vec3<f32> = df_grid.origin.xyz + vec3<f32>(
@@ -67,6 +72,9 @@ fn main(
         i32(offset_grid_point.y / atoms_lookup_grid.spacing) * res +
         i32(offset_grid_point.z / atoms_lookup_grid.spacing) * res * res;
 
+    // Distance from the grid point to the van der Waals surface of the nearest atom found so far
+    var nearest_atom_distance: f32 = HUGE_DISTANCE;
+
     // Check all 27 neighboring voxels
     for (var x: i32 = -1; x <= 1; x += 1) {
         for (var y: i32 = -1; y <= 1; y += 1) {
@@ -82,17 +90,25 @@ fn main(
                 // Classify the grid point based on the atoms in the neighboring voxels
                 for (var i: u32 = 0; i < atom_segment.atoms_count; i += 1u) {
 
-                    let atom: Atom = atoms_sorted[atom_segment.first_atom_index + i];
+                    let atom_index: u32 = atom_segment.first_atom_index + i;
+                    let atom: Atom = atoms_sorted[atom_index];
                     let distance: f32 = length(grid_point - atom.position);
 
                     if (distance < atom.radius - df_grid.spacing) {
                         // The grid point is inside an atom, no need to check the other atoms
                         df_grid_point_memory[grid_point_index] = INTERIOR_GRID_POINT;
+                        df_grid_point_atom[grid_point_index] = atom_index;
                         return;
                     }
                     if (distance < atom.radius + df_grid.probe_radius) {
                         // The grid point is within the probe radius of an atom, it is a boundary point
                         df_grid_point_memory[grid_point_index] = BOUNDARY_GRID_POINT;
+
+                        // Remember the atom whose surface is the closest, it determines the color of the molecular surface here
+                        if (distance - atom.radius < nearest_atom_distance) {
+                            nearest_atom_distance = distance - atom.radius;
+                            df_grid_point_atom[grid_point_index] = atom_index;
+                        }
                     }
                 }
             }
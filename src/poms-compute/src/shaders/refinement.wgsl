@@ -11,6 +11,7 @@ struct GridUniform {
 // Distance Field Grid Points Resource
 @group(0) @binding(0) var<storage, read_write> df_grid_point_memory: array<u32>;
 @group(0) @binding(1) var<uniform> df_grid_point_index_offset: u32;
+@group(0) @binding(2) var<storage, read_write> df_grid_point_atom: array<u32>;
 
 // Distance Field Resource
 @group(1) @binding(0) var<uniform> df_grid: GridUniform;
@@ -24,6 +25,19 @@ const BOUNDARY_GRID_POINT: u32 = 2u;
 const HUGE_DISTANCE: f32 = 100000.0;
 const PREDECESSOR_OFFSET: u32 = 3u;
 
+const NO_ATOM: u32 = 0xffffffffu;
+// Half floats represent integers up to 2048 exactly, so the atom index is split into two 11-bit parts.
+const ATOM_INDEX_PART: u32 = 2048u;
+
+// Encodes the nearest atom of a grid point into the `gba` channels of the texel. The `a` channel marks whether there is any.
+fn encode_atom(grid_point_index: u32) -> vec3<f32> {
+    let atom_index: u32 = df_grid_point_atom[grid_point_index];
+    if (atom_index == NO_ATOM) {
+        return vec3<f32>(0.0);
+    }
+    return vec3<f32>(f32(atom_index % ATOM_INDEX_PART), f32(atom_index / ATOM_INDEX_PART), 1.0);
+}
+
 fn grid_point_index_to_position(grid_point_index: u32) -> vec3<f32> {
     return df_grid.origin.xyz + vec3<f32>(
         f32(grid_point_index % df_grid.resolution),
@@ -48,10 +62,16 @@ fn compute_distance(grid_point_index: u32) -> f32 {
                     continue;
                 }
 
+                // Interior and boundary points without a predecessor yet do not know their closest exterior point.
+                let neighbor_memory: u32 = df_grid_point_memory[neighbor_index];
+                if neighbor_memory == INTERIOR_GRID_POINT || neighbor_memory == BOUNDARY_GRID_POINT {
+                    continue;
+                }
+
                 let neighbor_point_pos: vec3<f32> = grid_point_index_to_position(neighbor_index);
                 var distance: f32 = HUGE_DISTANCE;
 
-                switch df_grid_point_memory[neighbor_index] {
+                switch neighbor_memory {
                     case EXTERIOR_GRID_POINT: {
                         // If the neighbor is an exterior point, we can directly compute the distance.
                         distance = length(neighbor_point_pos - grid_point_pos);
@@ -59,7 +79,7 @@ fn compute_distance(grid_point_index: u32) -> f32 {
                     default: {
                         // Compare the distance to the predecessor of the neighbor.
                         // This represents the distance to the closest exterior point of the neighbor.
-                        let predecessor_index: u32 = df_grid_point_memory[neighbor_index] - PREDECESSOR_OFFSET;
+                        let predecessor_index: u32 = neighbor_memory - PREDECESSOR_OFFSET;
                         let predecessor_point_pos: vec3<f32> = grid_point_index_to_position(predecessor_index);
                         distance = length(predecessor_point_pos - grid_point_pos);
                     }
@@ -68,7 +88,7 @@ fn compute_distance(grid_point_index: u32) -> f32 {
                 if distance < min_distance {
                     min_distance = distance;
 
-                    switch df_grid_point_memory[neighbor_index] {
+                    switch neighbor_memory {
                         case EXTERIOR_GRID_POINT: {
                             // The neighbor is an exterior point.
                             // Store the neighbor as the predecessor of the grid point.
@@ -76,14 +96,14 @@ fn compute_distance(grid_point_index: u32) -> f32 {
                         }
                         default: {
                             // The predecessor of the grid point is the predecessor of the neighbor.
-                            df_grid_point_memory[grid_point_index] = df_grid_point_memory[neighbor_index];
+                            df_grid_point_memory[grid_point_index] = neighbor_memory;
                         }
                     }
                 }
             }
         }
     }
-    if min_distance < HUGE_DISTANCE + 1.0 {
+    if min_distance < HUGE_DISTANCE {
         return df_grid.probe_radius - min_distance;
     }
     // No exterior point found.
@@ -107,16 +127,19 @@ fn main(
         i32(grid_point_index / (df_grid.resolution * df_grid.resolution))
     );
 
+    // The distance is stored in the `r` channel, the nearest atom in the remaining ones.
+    let atom = encode_atom(grid_point_index);
+
     switch (df_grid_point_memory[grid_point_index]) {
         case EXTERIOR_GRID_POINT: {
-            textureStore(df_texture, texture_index, vec4<f32>(df_grid.probe_radius));
+            textureStore(df_texture, texture_index, vec4<f32>(df_grid.probe_radius, atom));
         }
         case INTERIOR_GRID_POINT: {
-            textureStore(df_texture, texture_index, vec4<f32>(-df_grid.offset));
+            textureStore(df_texture, texture_index, vec4<f32>(-df_grid.offset, atom));
         }
         default: {
             let distance = compute_distance(grid_point_index);
-            textureStore(df_texture, texture_index, vec4<f32>(distance));
+            textureStore(df_texture, texture_index, vec4<f32>(distance, atom));
         }
     }
 }
@@ -0,0 +1,112 @@
+//! Differential tests of the probe and refinement passes on the GPU against the CPU reference implementation,
+//! computed on the bundled molecules in `data/pdb` at each resolution the distance field is refined to.
+//!
+//! The tests are skipped if no adapter is available, or if it cannot run the compute passes,
+//! as the distance field would then be computed on the CPU as well.
+
+use std::path::PathBuf;
+
+use poms_common::limits::{MAX_DISTANCE_FIELD_RESOLUTION, MIN_DISTANCE_FIELD_RESOLUTION};
+use poms_common::models::atom::{Atom, AtomsWithLookup};
+use poms_compute::cpu;
+use poms_compute::headless::{self, HeadlessDevice, HeadlessParameters};
+use poms_io::molecule_parser::{load_options::LoadOptions, parse_atoms_from_pdb_file};
+use poms_io::RawFile;
+
+const MOLECULES: [&str; 7] = [
+    "103d.pdb",
+    "1cqw.pdb",
+    "5mxb.pdb",
+    "7qyp.pdb",
+    "7x8y.pdb",
+    "8e99.pdb",
+    "small.pdb",
+];
+
+const PROBE_RADIUS: f32 = 1.4;
+
+/// Largest allowed difference in grid spacings. The GPU refines grid points concurrently, so a predecessor may
+/// reach a grid point a cycle later than on the CPU, and the distances are stored as half floats.
+const TOLERANCE_IN_GRID_SPACINGS: f32 = 1.0;
+
+#[test]
+fn gpu_matches_cpu_on_bundled_molecules() {
+    let Some(gpu) = request_gpu() else {
+        return;
+    };
+
+    // The resolution is doubled from the minimum after each refinement, as in the viewer.
+    let resolutions = std::iter::successors(Some(MIN_DISTANCE_FIELD_RESOLUTION), |resolution| {
+        Some(resolution * 2)
+    })
+    .take_while(|resolution| *resolution <= MAX_DISTANCE_FIELD_RESOLUTION)
+    .collect::<Vec<_>>();
+
+    for name in MOLECULES {
+        let atoms = read_atoms(name);
+        for &resolution in &resolutions {
+            let gpu_distance_field = futures::executor::block_on(headless::compute_distance_field(
+                &gpu,
+                HeadlessParameters {
+                    molecule: &atoms,
+                    probe_radius: PROBE_RADIUS,
+                    target_resolution: resolution,
+                },
+            ))
+            .unwrap_or_else(|error| panic!("{} at {}: {}", name, resolution, error));
+
+            let atoms = AtomsWithLookup::new(atoms.clone(), PROBE_RADIUS);
+            let cpu_distance_field =
+                cpu::compute_distance_field_on_grid(&atoms, gpu_distance_field.grid);
+
+            let difference = gpu_distance_field
+                .max_abs_difference(&cpu_distance_field)
+                .expect("both fields are computed on the same grid");
+            let tolerance = TOLERANCE_IN_GRID_SPACINGS * gpu_distance_field.grid.spacing;
+            assert!(
+                difference <= tolerance,
+                "{} at {}: the distance fields differ by {}, more than the tolerance of {}",
+                name,
+                resolution,
+                difference,
+                tolerance
+            );
+        }
+    }
+}
+
+/// Returns the device the compute passes run on, or `None` if the test has to be skipped.
+fn request_gpu() -> Option<HeadlessDevice> {
+    let gpu = match futures::executor::block_on(headless::request_device(false)) {
+        Ok(gpu) => gpu,
+        Err(error) => {
+            eprintln!("Skipped, no adapter is available: {}", error);
+            return None;
+        }
+    };
+    if !headless::supports_compute_passes(&gpu.adapter_info) {
+        eprintln!(
+            "Skipped, the {} adapter ({:?}) cannot run the compute passes",
+            gpu.adapter_info.name, gpu.adapter_info.backend
+        );
+        return None;
+    }
+    Some(gpu)
+}
+
+/// Reads the atoms of the first model of a bundled PDB file with the reader of the viewer, which also sets their radii.
+fn read_atoms(name: &str) -> Vec<Atom> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../data/pdb")
+        .join(name);
+    let file = RawFile {
+        name: name.to_string(),
+        content: std::fs::read(&path)
+            .unwrap_or_else(|error| panic!("{}: {}", path.display(), error)),
+    };
+
+    parse_atoms_from_pdb_file(file, LoadOptions::default())
+        .unwrap_or_else(|error| panic!("{}: {}", name, error))
+        .swap_remove(0)
+        .atoms
+}
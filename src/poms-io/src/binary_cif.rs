@@ -0,0 +1,401 @@
+//! Decoder of BinaryCIF, the MessagePack encoding of mmCIF served by RCSB (e.g. the ModelServer).
+//!
+//! Each column of a category is stored as a byte array with a chain of encodings applied to it
+//! (e.g. delta, run-length and integer packing), which are reverted in the opposite order when decoding.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::cif::CifRow;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct File {
+    data_blocks: Vec<DataBlock>,
+}
+
+#[derive(Deserialize)]
+struct DataBlock {
+    categories: Vec<EncodedCategory>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncodedCategory {
+    /// Name of the category including the leading underscore (e.g. `_atom_site`).
+    name: String,
+    row_count: usize,
+    columns: Vec<EncodedColumn>,
+}
+
+#[derive(Deserialize)]
+struct EncodedColumn {
+    name: String,
+    data: EncodedData,
+    /// Marks values that are not present (`.`) or unknown (`?`), missing if all values are present.
+    mask: Option<EncodedData>,
+}
+
+#[derive(Deserialize)]
+struct EncodedData {
+    encoding: Vec<Encoding>,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all_fields = "camelCase")]
+enum Encoding {
+    ByteArray {
+        #[serde(rename = "type")]
+        data_type: i32,
+    },
+    FixedPoint {
+        factor: f64,
+    },
+    IntervalQuantization {
+        min: f64,
+        max: f64,
+        num_steps: i64,
+    },
+    RunLength {
+        src_size: usize,
+    },
+    Delta {
+        origin: i64,
+    },
+    IntegerPacking {
+        byte_count: usize,
+        is_unsigned: bool,
+        src_size: usize,
+    },
+    StringArray {
+        data_encoding: Vec<Encoding>,
+        string_data: String,
+        offset_encoding: Vec<Encoding>,
+        #[serde(with = "serde_bytes")]
+        offsets: Vec<u8>,
+    },
+}
+
+/// Values of a column at some stage of decoding. The encoded bytes are borrowed from the file.
+enum Values<'a> {
+    Bytes(&'a [u8]),
+    Integers(Vec<i64>),
+    Floats(Vec<f64>),
+    Strings(Vec<String>),
+}
+
+/// A BinaryCIF file, whose categories are decoded on demand.
+pub(crate) struct BinaryCif {
+    file: File,
+}
+
+/// A decoded category, with its columns accessible by item name (without the category prefix).
+pub(crate) struct Category<'a> {
+    pub(crate) row_count: usize,
+    columns: HashMap<String, Column<'a>>,
+}
+
+/// A decoded column of a category.
+pub(crate) struct Column<'a> {
+    values: Values<'a>,
+    mask: Option<Vec<i64>>,
+}
+
+impl BinaryCif {
+    /// Returns `true` if the content looks like BinaryCIF, i.e. a MessagePack map with the `dataBlocks` key.
+    pub(crate) fn is_binary_cif(content: &[u8]) -> bool {
+        let is_map = matches!(content.first(), Some(0x80..=0x8f | 0xde | 0xdf));
+        let head = &content[..content.len().min(256)];
+        is_map && head.windows(10).any(|window| window == b"dataBlocks")
+    }
+
+    pub(crate) fn decode(content: &[u8]) -> anyhow::Result<Self> {
+        let file = rmp_serde::from_slice(content)
+            .map_err(|error| anyhow::anyhow!("Invalid BinaryCIF file: {}", error))?;
+        Ok(Self { file })
+    }
+
+    /// Decodes the `category` (e.g. `_atom_site`) of the first data block, or returns `None` if it is missing.
+    pub(crate) fn category(&self, category: &str) -> anyhow::Result<Option<Category<'_>>> {
+        let Some(encoded) = self
+            .file
+            .data_blocks
+            .first()
+            .and_then(|block| block.categories.iter().find(|c| c.name == category))
+        else {
+            return Ok(None);
+        };
+
+        let mut columns = HashMap::new();
+        for column in &encoded.columns {
+            let decode_column = || -> anyhow::Result<Column> {
+                let values = decode(&column.data)?;
+                let mask = match &column.mask {
+                    Some(mask) => match decode(mask)? {
+                        Values::Integers(mask) => Some(mask),
+                        _ => anyhow::bail!("the mask is not an integer array"),
+                    },
+                    None => None,
+                };
+                Ok(Column { values, mask })
+            };
+            let decoded = decode_column().map_err(|error| {
+                anyhow::anyhow!("Column {}.{}: {}", category, column.name, error)
+            })?;
+            columns.insert(column.name.clone(), decoded);
+        }
+
+        Ok(Some(Category {
+            row_count: encoded.row_count,
+            columns,
+        }))
+    }
+}
+
+impl<'a> Category<'a> {
+    pub(crate) fn column(&self, name: &str) -> Option<&Column<'a>> {
+        self.columns.get(name)
+    }
+
+    /// Converts the values to strings, in the same form as the rows of text mmCIF categories.
+    /// Values that are not present or unknown are left out.
+    pub(crate) fn rows(&self) -> Vec<CifRow> {
+        (0..self.row_count)
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .filter_map(|(name, column)| Some((name.clone(), column.string(row)?)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Column<'_> {
+    /// Returns `false` if the value is marked as not present or unknown.
+    fn is_present(&self, row: usize) -> bool {
+        self.mask
+            .as_ref()
+            .map_or(true, |mask| mask.get(row) == Some(&0))
+    }
+
+    pub(crate) fn string(&self, row: usize) -> Option<String> {
+        if !self.is_present(row) {
+            return None;
+        }
+        match &self.values {
+            Values::Strings(values) => values.get(row).cloned(),
+            Values::Integers(values) => values.get(row).map(i64::to_string),
+            Values::Floats(values) => values.get(row).map(f64::to_string),
+            Values::Bytes(_) => None,
+        }
+    }
+
+    pub(crate) fn float(&self, row: usize) -> Option<f64> {
+        if !self.is_present(row) {
+            return None;
+        }
+        match &self.values {
+            Values::Floats(values) => values.get(row).copied(),
+            Values::Integers(values) => values.get(row).map(|&value| value as f64),
+            Values::Strings(values) => values.get(row).and_then(|value| value.parse().ok()),
+            Values::Bytes(_) => None,
+        }
+    }
+
+    pub(crate) fn integer(&self, row: usize) -> Option<i64> {
+        if !self.is_present(row) {
+            return None;
+        }
+        match &self.values {
+            Values::Integers(values) => values.get(row).copied(),
+            Values::Floats(values) => values.get(row).map(|&value| value as i64),
+            Values::Strings(values) => values.get(row).and_then(|value| value.parse().ok()),
+            Values::Bytes(_) => None,
+        }
+    }
+}
+
+/// Reverts the encodings of the data, starting with the last applied one.
+fn decode(encoded: &EncodedData) -> anyhow::Result<Values<'_>> {
+    decode_chain(Values::Bytes(&encoded.data), &encoded.encoding)
+}
+
+fn decode_chain<'a>(mut values: Values<'a>, encodings: &[Encoding]) -> anyhow::Result<Values<'a>> {
+    for encoding in encodings.iter().rev() {
+        values = decode_step(values, encoding)?;
+    }
+    Ok(values)
+}
+
+fn decode_step<'a>(values: Values<'a>, encoding: &Encoding) -> anyhow::Result<Values<'a>> {
+    let decoded = match (encoding, values) {
+        (Encoding::ByteArray { data_type }, Values::Bytes(bytes)) => {
+            decode_byte_array(bytes, *data_type)?
+        }
+        (Encoding::FixedPoint { factor }, Values::Integers(values)) => {
+            Values::Floats(values.iter().map(|&value| value as f64 / factor).collect())
+        }
+        (
+            Encoding::IntervalQuantization {
+                min,
+                max,
+                num_steps,
+            },
+            Values::Integers(values),
+        ) => {
+            let delta = (max - min) / (num_steps - 1).max(1) as f64;
+            Values::Floats(
+                values
+                    .iter()
+                    .map(|&value| min + delta * value as f64)
+                    .collect(),
+            )
+        }
+        (Encoding::RunLength { src_size }, Values::Integers(values)) => {
+            let mut decoded = Vec::with_capacity(*src_size);
+            for pair in values.chunks_exact(2) {
+                let count = usize::try_from(pair[1])?;
+                decoded.extend(std::iter::repeat(pair[0]).take(count));
+            }
+            Values::Integers(decoded)
+        }
+        (Encoding::Delta { origin }, Values::Integers(values)) => {
+            let mut current = *origin;
+            Values::Integers(
+                values
+                    .iter()
+                    .map(|&value| {
+                        current += value;
+                        current
+                    })
+                    .collect(),
+            )
+        }
+        (
+            Encoding::IntegerPacking {
+                byte_count,
+                is_unsigned,
+                src_size,
+            },
+            Values::Integers(values),
+        ) => Values::Integers(unpack_integers(
+            &values,
+            *byte_count,
+            *is_unsigned,
+            *src_size,
+        )),
+        (
+            Encoding::StringArray {
+                data_encoding,
+                string_data,
+                offset_encoding,
+                offsets,
+            },
+            Values::Bytes(bytes),
+        ) => {
+            let Values::Integers(offsets) = decode_chain(Values::Bytes(offsets), offset_encoding)?
+            else {
+                anyhow::bail!("the string offsets are not integers");
+            };
+            let Values::Integers(indices) = decode_chain(Values::Bytes(bytes), data_encoding)?
+            else {
+                anyhow::bail!("the string indices are not integers");
+            };
+
+            // Offsets count characters, not bytes.
+            let characters = string_data.chars().collect::<Vec<_>>();
+            let strings = offsets
+                .windows(2)
+                .map(|range| {
+                    let start = usize::try_from(range[0])?;
+                    let end = usize::try_from(range[1])?;
+                    let characters = characters
+                        .get(start..end)
+                        .ok_or_else(|| anyhow::anyhow!("string offset out of range"))?;
+                    Ok(characters.iter().collect::<String>())
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            // Negative indices stand for missing values.
+            Values::Strings(
+                indices
+                    .iter()
+                    .map(|&index| {
+                        usize::try_from(index)
+                            .ok()
+                            .and_then(|index| strings.get(index).cloned())
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            )
+        }
+        _ => anyhow::bail!("unexpected input of the encoding"),
+    };
+    Ok(decoded)
+}
+
+/// Interprets little-endian bytes as an array of the given type.
+fn decode_byte_array(bytes: &[u8], data_type: i32) -> anyhow::Result<Values<'static>> {
+    fn integers<const N: usize>(bytes: &[u8], convert: fn([u8; N]) -> i64) -> Values<'static> {
+        Values::Integers(
+            bytes
+                .chunks_exact(N)
+                .map(|chunk| convert(chunk.try_into().expect("chunk has N bytes")))
+                .collect(),
+        )
+    }
+    fn floats<const N: usize>(bytes: &[u8], convert: fn([u8; N]) -> f64) -> Values<'static> {
+        Values::Floats(
+            bytes
+                .chunks_exact(N)
+                .map(|chunk| convert(chunk.try_into().expect("chunk has N bytes")))
+                .collect(),
+        )
+    }
+
+    let values = match data_type {
+        1 => integers::<1>(bytes, |b| i8::from_le_bytes(b) as i64),
+        2 => integers::<2>(bytes, |b| i16::from_le_bytes(b) as i64),
+        3 => integers::<4>(bytes, |b| i32::from_le_bytes(b) as i64),
+        4 => integers::<1>(bytes, |b| b[0] as i64),
+        5 => integers::<2>(bytes, |b| u16::from_le_bytes(b) as i64),
+        6 => integers::<4>(bytes, |b| u32::from_le_bytes(b) as i64),
+        32 => floats::<4>(bytes, |b| f32::from_le_bytes(b) as f64),
+        33 => floats::<8>(bytes, f64::from_le_bytes),
+        _ => anyhow::bail!("unknown data type {}", data_type),
+    };
+    Ok(values)
+}
+
+/// Unpacks integers that were packed into 8 or 16 bits. Values outside of the range are stored as sums
+/// of the boundary values followed by the remainder, so the boundary values are accumulated until another value appears.
+fn unpack_integers(
+    values: &[i64],
+    byte_count: usize,
+    is_unsigned: bool,
+    src_size: usize,
+) -> Vec<i64> {
+    let (upper, lower) = match (byte_count, is_unsigned) {
+        (1, true) => (0xff, 0),
+        (1, false) => (0x7f, -0x80),
+        (_, true) => (0xffff, 0),
+        (_, false) => (0x7fff, -0x8000),
+    };
+
+    let mut unpacked = Vec::with_capacity(src_size);
+    let mut sum = 0;
+    for &value in values {
+        sum += value;
+        // Zero is a regular value for unsigned packing, only the upper boundary continues the sum.
+        let is_boundary = value == upper || (!is_unsigned && value == lower);
+        if !is_boundary {
+            unpacked.push(sum);
+            sum = 0;
+        }
+    }
+    unpacked
+}
@@ -0,0 +1,509 @@
+//! Biological assemblies generated from the `REMARK 350` records of PDB files,
+//! or the `_pdbx_struct_assembly_gen` and `_pdbx_struct_oper_list` categories of mmCIF and BinaryCIF files.
+//!
+//! An assembly is built from the asymmetric unit by applying each of its transformations to the listed chains.
+
+use std::collections::{HashMap, HashSet};
+
+use cgmath::{Matrix4, Point3, SquareMatrix, Transform};
+use poms_common::models::atom::{Atom, AtomMetadata};
+
+use super::structure_file::StructureFile;
+
+/// Rotation rows followed by the translation, as listed in the files.
+type TransformRows = [[f32; 4]; 3];
+
+/// Which identifiers of the atoms the chains of an assembly refer to.
+#[derive(Clone, Copy)]
+enum ChainIdentifier {
+    /// The chain identifiers of PDB files, listed by `REMARK 350`.
+    Author,
+    /// The `label_asym_id` of mmCIF and BinaryCIF files, listed by `_pdbx_struct_assembly_gen`.
+    Label,
+}
+
+/// Copies of the `chain_ids` placed by each of the `transforms`.
+#[derive(Clone)]
+struct AssemblyGenerator {
+    chain_ids: HashSet<String>,
+    chain_identifier: ChainIdentifier,
+    transforms: Vec<Matrix4<f32>>,
+}
+
+impl AssemblyGenerator {
+    fn contains(&self, atom: &AtomMetadata) -> bool {
+        match self.chain_identifier {
+            ChainIdentifier::Author => self.chain_ids.contains(&atom.chain_id),
+            ChainIdentifier::Label => atom
+                .label_asym_id
+                .as_ref()
+                .is_some_and(|label| self.chain_ids.contains(label)),
+        }
+    }
+}
+
+/// A biological assembly defined in a file, which can be built from the atoms of the asymmetric unit.
+#[derive(Clone)]
+pub struct BiologicalAssembly {
+    /// Label shown to the user (e.g. `Assembly 1 (dimeric)`).
+    pub name: String,
+    generators: Vec<AssemblyGenerator>,
+}
+
+impl BiologicalAssembly {
+    /// Reads the assemblies from the file. Returns no assemblies if the file does not define any.
+    pub(crate) fn from_file(file: &StructureFile) -> Vec<Self> {
+        match file {
+            StructureFile::Pdb(content) => parse_pdb_records(content),
+            _ => parse_cif_categories(file),
+        }
+    }
+
+    /// Returns the number of atoms the assembly built from the given asymmetric unit would have.
+    pub fn num_atoms(&self, metadata: &[AtomMetadata]) -> usize {
+        self.generators
+            .iter()
+            .map(|generator| {
+                let num_chain_atoms = metadata
+                    .iter()
+                    .filter(|atom| generator.contains(atom))
+                    .count();
+                num_chain_atoms * generator.transforms.len()
+            })
+            .sum()
+    }
+
+    /// Builds the assembly from the atoms of the asymmetric unit. Copies of a chain keep its identifier.
+    pub fn build(
+        &self,
+        atoms: &[Atom],
+        metadata: &[AtomMetadata],
+    ) -> (Vec<Atom>, Vec<AtomMetadata>) {
+        let mut assembled_atoms = Vec::with_capacity(self.num_atoms(metadata));
+        let mut assembled_metadata = Vec::with_capacity(assembled_atoms.capacity());
+
+        for generator in &self.generators {
+            let chain_atoms = atoms
+                .iter()
+                .zip(metadata)
+                .filter(|(_, metadata)| generator.contains(metadata));
+
+            for transform in &generator.transforms {
+                for (atom, metadata) in chain_atoms.clone() {
+                    let position = transform.transform_point(Point3::from(atom.position));
+                    assembled_atoms.push(Atom {
+                        position: position.into(),
+                        ..*atom
+                    });
+                    assembled_metadata.push(metadata.clone());
+                }
+            }
+        }
+
+        (assembled_atoms, assembled_metadata)
+    }
+}
+
+/// Creates the transformation matrix from the rows of a rotation matrix with the translation in the last column.
+fn matrix_from_rows(rows: TransformRows) -> Matrix4<f32> {
+    let [r0, r1, r2] = rows;
+    // cgmath matrices are created column by column.
+    Matrix4::new(
+        r0[0], r1[0], r2[0], 0.0, //
+        r0[1], r1[1], r2[1], 0.0, //
+        r0[2], r1[2], r2[2], 0.0, //
+        r0[3], r1[3], r2[3], 1.0,
+    )
+}
+
+/// Formats the name of an assembly, e.g. `Assembly 1 (dimeric)`.
+fn assembly_name(id: &str, oligomeric_details: Option<&str>) -> String {
+    match oligomeric_details.filter(|details| !details.is_empty() && *details != "?") {
+        Some(details) => format!("Assembly {} ({})", id, details.to_lowercase()),
+        None => format!("Assembly {}", id),
+    }
+}
+
+/// A `BIOMOLECULE` of `REMARK 350`, with the chains and the rows of the transformations applied to them.
+struct ParsedBiomolecule {
+    id: String,
+    oligomeric_details: Option<String>,
+    generators: Vec<(HashSet<String>, Vec<TransformRows>)>,
+}
+
+/// Reads the `REMARK 350` records, in which each `BIOMOLECULE` is followed by the chains to apply
+/// the `BIOMT` transformations to. A biomolecule may list several groups of chains with different transformations.
+fn parse_pdb_records(content: &str) -> Vec<BiologicalAssembly> {
+    const REMARK_TEXT_START: usize = 10;
+
+    let mut parsed: Vec<ParsedBiomolecule> = Vec::new();
+
+    for line in content
+        .lines()
+        .filter(|line| line.starts_with("REMARK 350"))
+    {
+        let text = line.get(REMARK_TEXT_START..).unwrap_or_default().trim();
+
+        if let Some(id) = text.strip_prefix("BIOMOLECULE:") {
+            parsed.push(ParsedBiomolecule {
+                id: id.trim().to_string(),
+                oligomeric_details: None,
+                generators: Vec::new(),
+            });
+            continue;
+        }
+        let Some(ParsedBiomolecule {
+            oligomeric_details,
+            generators,
+            ..
+        }) = parsed.last_mut()
+        else {
+            continue;
+        };
+
+        if let Some((_, oligomer)) = text
+            .split_once("DETERMINED BIOLOGICAL UNIT:")
+            .or_else(|| text.split_once("DETERMINED QUATERNARY STRUCTURE:"))
+        {
+            // Prefer the author determined unit, which comes first.
+            oligomeric_details.get_or_insert_with(|| oligomer.trim().to_string());
+        } else if let Some(chains) = text.strip_prefix("APPLY THE FOLLOWING TO CHAINS:") {
+            generators.push((parse_chain_list(chains), Vec::new()));
+        } else if let Some(chains) = text.strip_prefix("AND CHAINS:") {
+            if let Some((chain_ids, _)) = generators.last_mut() {
+                chain_ids.extend(parse_chain_list(chains));
+            }
+        } else if let Some(biomt) = text.strip_prefix("BIOMT") {
+            let Some((_, transforms)) = generators.last_mut() else {
+                continue;
+            };
+            // Each of the three rows (`BIOMT1` to `BIOMT3`) has the serial number, rotation and translation.
+            let fields = biomt.split_whitespace().collect::<Vec<_>>();
+            let (Some(row), Some(values)) = (
+                fields.first().and_then(|row| row.parse::<usize>().ok()),
+                fields.get(2..6).and_then(|values| {
+                    values
+                        .iter()
+                        .map(|v| v.parse::<f32>().ok())
+                        .collect::<Option<Vec<_>>>()
+                }),
+            ) else {
+                continue;
+            };
+            if row == 1 {
+                transforms.push([[0.0; 4]; 3]);
+            }
+            if let (Some(rows), 1..=3) = (transforms.last_mut(), row) {
+                rows[row - 1].copy_from_slice(&values);
+            }
+        }
+    }
+
+    parsed
+        .into_iter()
+        .map(|biomolecule| BiologicalAssembly {
+            name: assembly_name(&biomolecule.id, biomolecule.oligomeric_details.as_deref()),
+            generators: biomolecule
+                .generators
+                .into_iter()
+                .map(|(chain_ids, transforms)| AssemblyGenerator {
+                    chain_ids,
+                    chain_identifier: ChainIdentifier::Author,
+                    transforms: transforms.into_iter().map(matrix_from_rows).collect(),
+                })
+                .collect(),
+        })
+        .filter(|assembly| !assembly.generators.is_empty())
+        .collect()
+}
+
+fn parse_chain_list(chains: &str) -> HashSet<String> {
+    chains
+        .split(',')
+        .map(str::trim)
+        .filter(|chain| !chain.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads the assemblies from the mmCIF categories of both text and binary files.
+fn parse_cif_categories(file: &StructureFile) -> Vec<BiologicalAssembly> {
+    let operators = file
+        .cif_category("_pdbx_struct_oper_list")
+        .iter()
+        .filter_map(|row| {
+            let value = |name: String| row.get(&name)?.parse::<f32>().ok();
+            let mut rows: TransformRows = [[0.0; 4]; 3];
+            for (i, matrix_row) in rows.iter_mut().enumerate() {
+                for (j, value_in_row) in matrix_row.iter_mut().take(3).enumerate() {
+                    *value_in_row = value(format!("matrix[{}][{}]", i + 1, j + 1))?;
+                }
+                matrix_row[3] = value(format!("vector[{}]", i + 1))?;
+            }
+            Some((row.get("id")?.clone(), matrix_from_rows(rows)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let details = file
+        .cif_category("_pdbx_struct_assembly")
+        .iter()
+        .filter_map(|row| {
+            Some((
+                row.get("id")?.clone(),
+                row.get("oligomeric_details")?.clone(),
+            ))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut assemblies: Vec<(String, Vec<AssemblyGenerator>)> = Vec::new();
+    for row in file.cif_category("_pdbx_struct_assembly_gen").iter() {
+        let (Some(id), Some(expression), Some(asym_ids)) = (
+            row.get("assembly_id"),
+            row.get("oper_expression"),
+            row.get("asym_id_list"),
+        ) else {
+            continue;
+        };
+        let Some(transforms) = parse_operator_expression(expression, &operators) else {
+            continue;
+        };
+
+        let generator = AssemblyGenerator {
+            chain_ids: parse_chain_list(asym_ids),
+            chain_identifier: ChainIdentifier::Label,
+            transforms,
+        };
+
+        match assemblies
+            .iter_mut()
+            .find(|(assembly_id, _)| assembly_id == id)
+        {
+            Some((_, generators)) => generators.push(generator),
+            None => assemblies.push((id.clone(), vec![generator])),
+        }
+    }
+
+    assemblies
+        .into_iter()
+        .map(|(id, generators)| BiologicalAssembly {
+            name: assembly_name(&id, details.get(&id).map(String::as_str)),
+            generators,
+        })
+        .collect()
+}
+
+/// Evaluates an operator expression, e.g. `1`, `1,2,5-8` or `(1-60)(61-62)`. Parenthesized groups form
+/// a cartesian product, in which the operators of the rightmost group are applied first.
+/// Returns `None` if the expression refers to an unknown operator.
+fn parse_operator_expression(
+    expression: &str,
+    operators: &HashMap<String, Matrix4<f32>>,
+) -> Option<Vec<Matrix4<f32>>> {
+    let groups = expression
+        .split(['(', ')'])
+        .map(str::trim)
+        .filter(|group| !group.is_empty());
+
+    let mut transforms = vec![Matrix4::identity()];
+    for group in groups {
+        let mut group_operators = Vec::new();
+        for item in group.split(',').map(str::trim) {
+            let range = item.split_once('-').and_then(|(first, last)| {
+                Some((first.parse::<i64>().ok()?, last.parse::<i64>().ok()?))
+            });
+            match range {
+                Some((first, last)) => {
+                    for id in first..=last {
+                        group_operators.push(*operators.get(&id.to_string())?);
+                    }
+                }
+                None => group_operators.push(*operators.get(item)?),
+            }
+        }
+
+        transforms = transforms
+            .iter()
+            .flat_map(|transform| {
+                group_operators
+                    .iter()
+                    .map(move |operator| transform * operator)
+            })
+            .collect();
+    }
+    Some(transforms)
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, Vector3};
+
+    use super::*;
+    use crate::cif::CifCategories;
+
+    /// Operators `1` to `60` translate along `x` by their number, `61` rotates by 90° around `z`, `62` is the identity.
+    fn operators() -> HashMap<String, Matrix4<f32>> {
+        let mut operators = (1..=60)
+            .map(|id| {
+                let translation = Vector3::new(id as f32, 0.0, 0.0);
+                (id.to_string(), Matrix4::from_translation(translation))
+            })
+            .collect::<HashMap<_, _>>();
+        operators.insert("61".to_string(), Matrix4::from_angle_z(Deg(90.0)));
+        operators.insert("62".to_string(), Matrix4::identity());
+        operators
+    }
+
+    /// Applies the transforms to `(1, 0, 0)`, rounding the coordinates.
+    fn transformed_points(transforms: &[Matrix4<f32>]) -> Vec<[i32; 3]> {
+        transforms
+            .iter()
+            .map(|transform| {
+                let point = transform.transform_point(Point3::new(1.0, 0.0, 0.0));
+                [point.x, point.y, point.z].map(|coordinate| coordinate.round() as i32)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_lists_and_ranges() {
+        let transforms = parse_operator_expression("1", &operators()).unwrap();
+        assert_eq!(transformed_points(&transforms), [[2, 0, 0]]);
+
+        let transforms = parse_operator_expression("1,2,5-8", &operators()).unwrap();
+        assert_eq!(
+            transformed_points(&transforms),
+            [
+                [2, 0, 0],
+                [3, 0, 0],
+                [6, 0, 0],
+                [7, 0, 0],
+                [8, 0, 0],
+                [9, 0, 0]
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_the_rightmost_group_first() {
+        let transforms = parse_operator_expression("(1-60)(61-62)", &operators()).unwrap();
+        assert_eq!(transforms.len(), 120);
+        // The point is rotated by `61` before it is translated by `1`.
+        assert_eq!(
+            transformed_points(&transforms[..4]),
+            [[1, 1, 0], [2, 0, 0], [2, 1, 0], [3, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_operators() {
+        assert!(parse_operator_expression("1,63", &operators()).is_none());
+        assert!(parse_operator_expression("(1-2)(X0)", &operators()).is_none());
+    }
+
+    fn atoms_of_chains(chains: &[(&str, Option<&str>)]) -> (Vec<Atom>, Vec<AtomMetadata>) {
+        let atoms = vec![
+            Atom {
+                position: [1.0, 0.0, 0.0],
+                radius: 1.5,
+                color: [1.0; 4],
+            };
+            chains.len()
+        ];
+        let metadata = chains
+            .iter()
+            .map(|(chain_id, label_asym_id)| AtomMetadata {
+                chain_id: chain_id.to_string(),
+                label_asym_id: label_asym_id.map(str::to_string),
+                ..Default::default()
+            })
+            .collect();
+        (atoms, metadata)
+    }
+
+    #[test]
+    fn reads_remark_350_with_continued_chain_lists() {
+        let content = "\
+REMARK 350 BIOMOLECULE: 1
+REMARK 350 AUTHOR DETERMINED BIOLOGICAL UNIT: HEXAMERIC
+REMARK 350 SOFTWARE DETERMINED QUATERNARY STRUCTURE: DODECAMERIC
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: A, B,
+REMARK 350                    AND CHAINS: C
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000
+REMARK 350   BIOMT1   2 -1.000000  0.000000  0.000000       10.00000
+REMARK 350   BIOMT2   2  0.000000 -1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   2  0.000000  0.000000  1.000000        0.00000
+REMARK 350 BIOMOLECULE: 2
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: D
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000
+";
+        let assemblies = BiologicalAssembly::from_file(&StructureFile::Pdb(content));
+        let names = assemblies
+            .iter()
+            .map(|assembly| assembly.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Assembly 1 (hexameric)", "Assembly 2"]);
+
+        // Atoms are matched by their author chain, as BIOMT lists those.
+        let (atoms, metadata) = atoms_of_chains(&[
+            ("A", None),
+            ("B", None),
+            ("C", None),
+            ("D", None),
+            ("E", None),
+        ]);
+        assert_eq!(assemblies[0].num_atoms(&metadata), 6);
+        let (assembled_atoms, assembled_metadata) = assemblies[0].build(&atoms, &metadata);
+        let chain_ids = assembled_metadata
+            .iter()
+            .map(|atom| atom.chain_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(chain_ids, ["A", "B", "C", "A", "B", "C"]);
+        assert_eq!(assembled_atoms[3].position, [9.0, 0.0, 0.0]);
+        assert_eq!(assemblies[1].num_atoms(&metadata), 1);
+    }
+
+    #[test]
+    fn matches_cif_generators_on_label_chains() {
+        let content = "\
+data_TEST
+_pdbx_struct_assembly.id 1
+_pdbx_struct_assembly.oligomeric_details monomeric
+_pdbx_struct_assembly_gen.assembly_id 1
+_pdbx_struct_assembly_gen.oper_expression 1
+_pdbx_struct_assembly_gen.asym_id_list A,C
+loop_
+_pdbx_struct_oper_list.id
+_pdbx_struct_oper_list.matrix[1][1]
+_pdbx_struct_oper_list.matrix[1][2]
+_pdbx_struct_oper_list.matrix[1][3]
+_pdbx_struct_oper_list.vector[1]
+_pdbx_struct_oper_list.matrix[2][1]
+_pdbx_struct_oper_list.matrix[2][2]
+_pdbx_struct_oper_list.matrix[2][3]
+_pdbx_struct_oper_list.vector[2]
+_pdbx_struct_oper_list.matrix[3][1]
+_pdbx_struct_oper_list.matrix[3][2]
+_pdbx_struct_oper_list.matrix[3][3]
+_pdbx_struct_oper_list.vector[3]
+1 1 0 0 0 0 1 0 0 0 0 1 0
+";
+        let file = StructureFile::Cif(CifCategories::parse(content));
+        let assemblies = BiologicalAssembly::from_file(&file);
+        assert_eq!(assemblies.len(), 1);
+        assert_eq!(assemblies[0].name, "Assembly 1 (monomeric)");
+
+        // The ligand of author chain `A` has its own label chain `C`, while author chain `C` is labeled `D`.
+        let (atoms, metadata) = atoms_of_chains(&[
+            ("A", Some("A")),
+            ("A", Some("C")),
+            ("B", Some("B")),
+            ("C", Some("D")),
+        ]);
+        let (_, assembled_metadata) = assemblies[0].build(&atoms, &metadata);
+        assert_eq!(assembled_metadata, metadata[..2]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Minimal reader of mmCIF categories, used for data that `pdbtbx` does not expose (e.g. secondary structure).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A single row of an mmCIF category, mapping item names (without the category prefix) to their values.
+pub(crate) type CifRow = HashMap<String, String>;
+
+/// Identifies a residue by its author chain, residue number and insertion code, like the atoms read by `pdbtbx`.
+pub(crate) type ResidueKey = (String, isize, Option<String>);
+
+/// Rows of all categories of an mmCIF file, so that the file is tokenized only once for all readers.
+pub(crate) struct CifCategories {
+    categories: HashMap<String, Vec<CifRow>>,
+    /// Label chain identifier (`label_asym_id`) of each residue of `_atom_site`, which `pdbtbx` does not expose.
+    label_asym_ids: HashMap<ResidueKey, String>,
+}
+
+impl CifCategories {
+    /// Categories that are not stored, as they are large and already read by `pdbtbx`.
+    const SKIPPED: [&'static str; 1] = ["_atom_site"];
+
+    /// Reads the categories (e.g. `_struct_conf`) of the mmCIF `content`.
+    /// Both the `loop_` form and the single row key-value form are supported.
+    pub(crate) fn parse(content: &str) -> Self {
+        let tokens = tokenize(content);
+
+        let mut categories: HashMap<String, Vec<CifRow>> = HashMap::new();
+        let mut single_rows: HashMap<String, CifRow> = HashMap::new();
+        let mut label_asym_ids = HashMap::new();
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let token = &tokens[index];
+
+            if token.is_keyword("loop_") {
+                index += 1;
+                let Some(category) = tokens.get(index).and_then(Token::category) else {
+                    continue;
+                };
+                // Collect the item names of the loop.
+                let mut items = Vec::new();
+                while let Some(tag) = tokens.get(index).filter(|t| t.category() == Some(category)) {
+                    items.push(&tag.value[category.len() + 1..]);
+                    index += 1;
+                }
+                // The values follow until the next tag or keyword.
+                let values_start = index;
+                while tokens.get(index).is_some_and(Token::is_value) {
+                    index += 1;
+                }
+                if category == "_atom_site" {
+                    read_label_asym_ids(&items, &tokens[values_start..index], &mut label_asym_ids);
+                }
+                if Self::SKIPPED.contains(&category) {
+                    continue;
+                }
+                let rows = categories.entry(category.to_string()).or_default();
+                for chunk in tokens[values_start..index].chunks_exact(items.len()) {
+                    let row = items
+                        .iter()
+                        .zip(chunk)
+                        .map(|(item, value)| (item.to_string(), value.value.to_string()))
+                        .collect();
+                    rows.push(row);
+                }
+                continue;
+            }
+
+            if let Some(category) = token.category() {
+                if let Some(value) = tokens.get(index + 1).filter(|t| t.is_value()) {
+                    single_rows.entry(category.to_string()).or_default().insert(
+                        token.value[category.len() + 1..].to_string(),
+                        value.value.to_string(),
+                    );
+                    index += 1;
+                }
+            }
+            index += 1;
+        }
+
+        for (category, row) in single_rows {
+            categories.entry(category).or_default().push(row);
+        }
+        Self {
+            categories,
+            label_asym_ids,
+        }
+    }
+
+    /// Returns the rows of `category` (e.g. `_struct_conf`), or no rows if the category is missing.
+    pub(crate) fn rows(&self, category: &str) -> &[CifRow] {
+        self.categories.get(category).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the label chain identifier of the residue, or `None` if the residue is not in `_atom_site`.
+    pub(crate) fn label_asym_id(&self, residue: &ResidueKey) -> Option<&str> {
+        self.label_asym_ids.get(residue).map(String::as_str)
+    }
+}
+
+/// Reads the label chain identifier of each residue from the values of the `_atom_site` loop with the given items.
+/// Consecutive atoms mostly belong to the same residue, so only the first atom of each residue is looked up.
+fn read_label_asym_ids(
+    items: &[&str],
+    values: &[Token],
+    label_asym_ids: &mut HashMap<ResidueKey, String>,
+) {
+    let item = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| items.iter().position(|item| item == name))
+    };
+    let (Some(label_asym_id), Some(chain_id), Some(residue_number)) = (
+        item(&["label_asym_id"]),
+        item(&["auth_asym_id", "label_asym_id"]),
+        item(&["auth_seq_id", "label_seq_id"]),
+    ) else {
+        return;
+    };
+    let insertion_code = item(&["pdbx_PDB_ins_code"]);
+
+    let mut previous: Option<&[Token]> = None;
+    for row in values.chunks_exact(items.len()) {
+        let is_same_residue = previous.is_some_and(|previous| {
+            [label_asym_id, chain_id, residue_number]
+                .into_iter()
+                .chain(insertion_code)
+                .all(|item| previous[item].value == row[item].value)
+        });
+        previous = Some(row);
+        if is_same_residue {
+            continue;
+        }
+
+        let Ok(number) = row[residue_number].value.parse::<isize>() else {
+            continue;
+        };
+        let insertion_code = insertion_code
+            .map(|item| &row[item].value)
+            .filter(|code| !matches!(code.as_ref(), "?" | "."))
+            .map(|code| code.to_string());
+        label_asym_ids.insert(
+            (row[chain_id].value.to_string(), number, insertion_code),
+            row[label_asym_id].value.to_string(),
+        );
+    }
+}
+
+struct Token<'a> {
+    value: Cow<'a, str>,
+    /// Quoted strings and text fields are always values, even if they look like tags or keywords.
+    is_quoted: bool,
+}
+
+impl Token<'_> {
+    /// Returns the category of a tag (e.g. `_struct_conf` of `_struct_conf.id`), or `None` if the token is not a tag.
+    fn category(&self) -> Option<&str> {
+        if self.is_quoted || !self.value.starts_with('_') {
+            return None;
+        }
+        self.value.split_once('.').map(|(category, _)| category)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        !self.is_quoted && self.value.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_value(&self) -> bool {
+        self.is_quoted
+            || !(self.value.starts_with('_')
+                || self.value.starts_with("data_")
+                || self.value.starts_with("save_")
+                || self.value.eq_ignore_ascii_case("loop_"))
+    }
+}
+
+/// Splits the content into whitespace separated tokens, handling quoted strings, `;` delimited text fields and comments.
+fn tokenize(content: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        // Text fields span multiple lines, starting and ending with a line beginning with `;`.
+        if let Some(first_line) = line.strip_prefix(';') {
+            let mut text = first_line.to_string();
+            for line in lines.by_ref() {
+                if line.starts_with(';') {
+                    break;
+                }
+                text.push('\n');
+                text.push_str(line);
+            }
+            tokens.push(Token {
+                value: Cow::Owned(text.trim().to_string()),
+                is_quoted: true,
+            });
+            continue;
+        }
+
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            if rest.starts_with('#') {
+                break;
+            }
+
+            let (token, remainder) = match rest.chars().next() {
+                Some(quote @ ('\'' | '"')) => split_quoted(rest, quote),
+                _ => {
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                    (
+                        Token {
+                            value: Cow::Borrowed(&rest[..end]),
+                            is_quoted: false,
+                        },
+                        &rest[end..],
+                    )
+                }
+            };
+            tokens.push(token);
+            rest = remainder.trim_start();
+        }
+    }
+
+    tokens
+}
+
+/// Splits off a quoted string. The closing quote has to be followed by whitespace or the end of the line.
+fn split_quoted(text: &str, quote: char) -> (Token<'_>, &str) {
+    let bytes = text.as_bytes();
+    let mut end = 1;
+    while end < text.len() {
+        let is_closing = bytes[end] == quote as u8
+            && bytes
+                .get(end + 1)
+                .map_or(true, |next| next.is_ascii_whitespace());
+        if is_closing {
+            let token = Token {
+                value: Cow::Borrowed(&text[1..end]),
+                is_quoted: true,
+            };
+            return (token, &text[end + 1..]);
+        }
+        end += 1;
+    }
+
+    // Unterminated quote, take the rest of the line.
+    let token = Token {
+        value: Cow::Borrowed(&text[1..]),
+        is_quoted: true,
+    };
+    (token, "")
+}
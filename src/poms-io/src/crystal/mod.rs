@@ -0,0 +1,645 @@
+//! Crystal symmetry read from the `CRYST1` and `REMARK 290` records of PDB files, or the `_cell`, `_symmetry`
+//! and `_space_group_symop` categories of mmCIF and BinaryCIF files.
+//!
+//! Symmetry mates are copies of the asymmetric unit placed by the space group operators and lattice translations.
+//! Packing them around the asymmetric unit shows the crystal contacts.
+
+mod space_group;
+
+use std::collections::HashMap;
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix3, Matrix4, Point3, SquareMatrix, Vector3};
+use poms_common::limits::MAX_NUM_ATOMS;
+use poms_common::models::atom::{Atom, AtomMetadata};
+
+use super::structure_file::StructureFile;
+use space_group::{space_group_operators, SymmetryOperator};
+
+/// How the asymmetric unit is packed with its symmetry mates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CrystalPacking {
+    /// Only the asymmetric unit is displayed.
+    #[default]
+    None,
+    /// The contents of `count` unit cells along each axis. Each symmetry mate is placed into the cells by its center.
+    UnitCells { count: u32 },
+    /// The asymmetric unit with all symmetry mates that have an atom within `radius` (in Å) of it.
+    Radius { radius: f32 },
+}
+
+impl CrystalPacking {
+    pub const ALL: [CrystalPacking; 3] = [
+        Self::None,
+        Self::UnitCells { count: 1 },
+        Self::Radius { radius: 8.0 },
+    ];
+
+    pub const MAX_UNIT_CELLS: u32 = 3;
+    pub const MAX_RADIUS: f32 = 20.0;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::UnitCells { .. } => "Unit cells",
+            Self::Radius { .. } => "Radius",
+        }
+    }
+
+    /// Returns `true` if both packings are of the same kind, regardless of their parameters.
+    pub fn is_same_kind(&self, other: &CrystalPacking) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Dimensions of the unit cell, with lengths in Å and angles in degrees.
+#[derive(Debug, Clone)]
+pub struct UnitCell {
+    lengths: [f64; 3],
+    angles: [f64; 3],
+}
+
+impl UnitCell {
+    /// Creates the unit cell, or returns `None` if the dimensions do not describe a valid cell.
+    /// The `1 1 1` cell written to files of structures not determined by crystallography is not valid either.
+    fn new(lengths: [f64; 3], angles: [f64; 3]) -> Option<Self> {
+        let cell = Self { lengths, angles };
+        let is_placeholder = lengths.iter().all(|&length| (length - 1.0).abs() < 1e-3);
+        let is_valid = lengths.iter().all(|&length| length > 0.0)
+            && angles.iter().all(|&angle| angle > 0.0 && angle < 180.0)
+            && cell.volume_factor() > 1e-6;
+        (is_valid && !is_placeholder).then_some(cell)
+    }
+
+    /// Ratio of the cell volume to the product of its lengths.
+    fn volume_factor(&self) -> f64 {
+        let [cos_alpha, cos_beta, cos_gamma] = self.angles.map(|angle| angle.to_radians().cos());
+        (1.0 - cos_alpha.powi(2) - cos_beta.powi(2) - cos_gamma.powi(2)
+            + 2.0 * cos_alpha * cos_beta * cos_gamma)
+            .max(0.0)
+            .sqrt()
+    }
+
+    /// Returns `true` if the cell has the shape of the hexagonal lattice (`a = b`, `γ = 120°`).
+    fn is_hexagonal(&self) -> bool {
+        (self.lengths[0] - self.lengths[1]).abs() < 1e-2 && (self.angles[2] - 120.0).abs() < 1e-2
+    }
+
+    /// Matrix converting fractional coordinates to Cartesian ones, with the `a` axis along `x`
+    /// and the `b` axis in the `xy` plane, following the PDB convention.
+    fn orthogonalization(&self) -> Matrix3<f64> {
+        let [a, b, c] = self.lengths;
+        let [cos_alpha, cos_beta, cos_gamma] = self.angles.map(|angle| angle.to_radians().cos());
+        let sin_gamma = self.angles[2].to_radians().sin();
+
+        // cgmath matrices are created column by column.
+        Matrix3::new(
+            a,
+            0.0,
+            0.0,
+            b * cos_gamma,
+            b * sin_gamma,
+            0.0,
+            c * cos_beta,
+            c * (cos_alpha - cos_beta * cos_gamma) / sin_gamma,
+            c * self.volume_factor() / sin_gamma,
+        )
+    }
+
+    /// Returns the twelve edges of the unit cell at the origin, in Cartesian coordinates.
+    pub fn edges(&self) -> Vec<[Point3<f32>; 2]> {
+        let orthogonalization = self.orthogonalization();
+        let corner = |fractional: [f64; 3]| {
+            let position = orthogonalization * Vector3::from(fractional);
+            Point3::from_vec(
+                position
+                    .cast::<f32>()
+                    .expect("the cell dimensions are finite"),
+            )
+        };
+
+        let mut edges = Vec::with_capacity(12);
+        for axis in 0..3 {
+            for (i, j) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+                // The edge goes along `axis`, the other two coordinates are `i` and `j`.
+                let mut start = [0.0; 3];
+                start[(axis + 1) % 3] = i;
+                start[(axis + 2) % 3] = j;
+                let mut end = start;
+                end[axis] = 1.0;
+                edges.push([corner(start), corner(end)]);
+            }
+        }
+        edges
+    }
+}
+
+/// Unit cell and space group of a crystal structure.
+#[derive(Clone)]
+pub struct CrystalSymmetry {
+    pub unit_cell: UnitCell,
+    /// Hermann-Mauguin symbol of the space group, as written in the file.
+    space_group: String,
+    /// Symmetry operators in fractional coordinates, empty if the space group is not known.
+    operators: Vec<SymmetryOperator>,
+}
+
+impl CrystalSymmetry {
+    /// Reads the crystal symmetry from the file. Returns `None` if the file does not define a unit cell.
+    pub(crate) fn from_file(file: &StructureFile) -> Option<Self> {
+        match file {
+            StructureFile::Pdb(content) => parse_pdb_records(content),
+            _ => parse_cif_categories(file),
+        }
+    }
+
+    /// Packs the asymmetric unit given by `atoms` with its symmetry mates. Copies of atoms keep their metadata.
+    /// Returns an error if the space group is not known or if there would be too many atoms.
+    pub fn pack(
+        &self,
+        atoms: &[Atom],
+        metadata: &[AtomMetadata],
+        packing: CrystalPacking,
+    ) -> anyhow::Result<(Vec<Atom>, Vec<AtomMetadata>)> {
+        if packing == CrystalPacking::None || atoms.is_empty() {
+            return Ok((atoms.to_vec(), metadata.to_vec()));
+        }
+        if self.operators.is_empty() {
+            anyhow::bail!(
+                "The symmetry operators of the space group {} are not known",
+                self.space_group
+            );
+        }
+
+        let placements = match packing {
+            CrystalPacking::None => unreachable!("the asymmetric unit is returned above"),
+            CrystalPacking::UnitCells { count } => self.unit_cell_placements(atoms, count),
+            CrystalPacking::Radius { radius } => self.radius_placements(atoms, radius as f64),
+        };
+
+        let num_atoms = placements.len() * atoms.len();
+        if num_atoms > MAX_NUM_ATOMS {
+            anyhow::bail!(
+                "The packed crystal would have {} atoms, which exceeds the limit ({})",
+                num_atoms,
+                MAX_NUM_ATOMS
+            );
+        }
+
+        let mut packed_atoms = Vec::with_capacity(num_atoms);
+        let mut packed_metadata = Vec::with_capacity(num_atoms);
+        for placement in placements {
+            let transform = self.to_cartesian(&placement);
+            for (atom, metadata) in atoms.iter().zip(metadata) {
+                let position = transform * Point3::from(atom.position).to_homogeneous();
+                packed_atoms.push(Atom {
+                    position: [position.x, position.y, position.z],
+                    ..*atom
+                });
+                packed_metadata.push(metadata.clone());
+            }
+        }
+        Ok((packed_atoms, packed_metadata))
+    }
+
+    /// Places each symmetry mate (the asymmetric unit included) into the unit cell at the origin by its center,
+    /// and then into the following cells along each axis.
+    fn unit_cell_placements(&self, atoms: &[Atom], count: u32) -> Vec<SymmetryOperator> {
+        let center = self.fractional_center(atoms);
+        let count = count.max(1) as i32;
+
+        let mut placements = Vec::new();
+        for operator in &self.operators {
+            let shift = -operator.apply(center).map(f64::floor);
+            for i in 0..count {
+                for j in 0..count {
+                    for k in 0..count {
+                        placements.push(SymmetryOperator {
+                            translation: operator.translation
+                                + shift
+                                + Vector3::new(i, j, k).cast().expect("small integers"),
+                            ..*operator
+                        });
+                    }
+                }
+            }
+        }
+        placements
+    }
+
+    /// Finds the symmetry mates that have an atom within `radius` of any atom of the asymmetric unit,
+    /// which comes first.
+    fn radius_placements(&self, atoms: &[Atom], radius: f64) -> Vec<SymmetryOperator> {
+        let orthogonalization = self.unit_cell.orthogonalization();
+        let fractionalization = orthogonalization
+            .invert()
+            .expect("the unit cell has a non-zero volume");
+
+        let positions = atoms
+            .iter()
+            .map(|atom| Vector3::from(atom.position).cast::<f64>().expect("finite"))
+            .collect::<Vec<_>>();
+        let bounds = Bounds::of(&positions);
+        let neighbors = NeighborGrid::new(&positions, radius);
+
+        // Lattice translations are tried far enough for a mate to reach the asymmetric unit from any side.
+        let shortest_cell_length = self
+            .unit_cell
+            .lengths
+            .iter()
+            .copied()
+            .fold(f64::MAX, f64::min);
+        let reach = ((bounds.size() + radius) / shortest_cell_length).ceil() as i32 + 1;
+
+        let center = fractionalization * bounds.center();
+        let mut placements = vec![SymmetryOperator::identity()];
+        for operator in &self.operators {
+            // Move the mate next to the asymmetric unit first, so nearby translations are the small ones.
+            let base_translation = (center - operator.apply(center)).map(f64::round);
+            let mate = SymmetryOperator {
+                translation: operator.translation + base_translation,
+                ..*operator
+            };
+            let mate_positions = positions
+                .iter()
+                .map(|&position| orthogonalization * mate.apply(fractionalization * position))
+                .collect::<Vec<_>>();
+            let mate_bounds = Bounds::of(&mate_positions);
+
+            for i in -reach..=reach {
+                for j in -reach..=reach {
+                    for k in -reach..=reach {
+                        let lattice_translation =
+                            Vector3::new(i, j, k).cast().expect("small integers");
+                        let placement = SymmetryOperator {
+                            translation: mate.translation + lattice_translation,
+                            ..mate
+                        };
+                        if placement.is_identity() {
+                            continue;
+                        }
+
+                        let shift = orthogonalization * lattice_translation;
+                        let is_in_reach = mate_bounds.shifted(shift).is_within(&bounds, radius)
+                            && mate_positions
+                                .iter()
+                                .any(|&position| neighbors.has_neighbor(position + shift));
+                        if is_in_reach {
+                            placements.push(placement);
+                        }
+                    }
+                }
+            }
+        }
+        placements
+    }
+
+    /// Returns the center of the atoms in fractional coordinates.
+    fn fractional_center(&self, atoms: &[Atom]) -> Vector3<f64> {
+        let sum = atoms
+            .iter()
+            .map(|atom| Vector3::from(atom.position).cast::<f64>().expect("finite"))
+            .fold(Vector3::new(0.0, 0.0, 0.0), |sum, position| sum + position);
+        let fractionalization = self
+            .unit_cell
+            .orthogonalization()
+            .invert()
+            .expect("the unit cell has a non-zero volume");
+        fractionalization * (sum / atoms.len() as f64)
+    }
+
+    /// Converts the operator acting on fractional coordinates into a transformation of Cartesian coordinates.
+    fn to_cartesian(&self, operator: &SymmetryOperator) -> Matrix4<f32> {
+        let orthogonalization = self.unit_cell.orthogonalization();
+        let fractionalization = orthogonalization
+            .invert()
+            .expect("the unit cell has a non-zero volume");
+
+        let rotation = orthogonalization * operator.rotation * fractionalization;
+        let translation = orthogonalization * operator.translation;
+        let transform = Matrix4::from_translation(translation) * Matrix4::from(rotation);
+        transform.cast().expect("the transformation is finite")
+    }
+}
+
+/// Axis aligned bounding box of Cartesian positions.
+struct Bounds {
+    min: Vector3<f64>,
+    max: Vector3<f64>,
+}
+
+impl Bounds {
+    fn of(positions: &[Vector3<f64>]) -> Self {
+        let mut bounds = Self {
+            min: Vector3::new(f64::MAX, f64::MAX, f64::MAX),
+            max: Vector3::new(f64::MIN, f64::MIN, f64::MIN),
+        };
+        for position in positions {
+            for axis in 0..3 {
+                bounds.min[axis] = bounds.min[axis].min(position[axis]);
+                bounds.max[axis] = bounds.max[axis].max(position[axis]);
+            }
+        }
+        bounds
+    }
+
+    fn center(&self) -> Vector3<f64> {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Length of the diagonal.
+    fn size(&self) -> f64 {
+        (self.max - self.min).magnitude()
+    }
+
+    fn shifted(&self, shift: Vector3<f64>) -> Self {
+        Self {
+            min: self.min + shift,
+            max: self.max + shift,
+        }
+    }
+
+    /// Returns `true` if the boxes are at most `distance` apart along each axis.
+    fn is_within(&self, other: &Self, distance: f64) -> bool {
+        (0..3).all(|axis| {
+            self.min[axis] <= other.max[axis] + distance
+                && other.min[axis] <= self.max[axis] + distance
+        })
+    }
+}
+
+/// Positions hashed into cubic cells as large as the searched distance, so neighbors are found in the adjacent cells.
+struct NeighborGrid<'a> {
+    positions: &'a [Vector3<f64>],
+    cells: HashMap<[i64; 3], Vec<usize>>,
+    distance: f64,
+}
+
+impl<'a> NeighborGrid<'a> {
+    fn new(positions: &'a [Vector3<f64>], distance: f64) -> Self {
+        let distance = distance.max(1.0);
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (index, &position) in positions.iter().enumerate() {
+            cells
+                .entry(Self::cell(position, distance))
+                .or_default()
+                .push(index);
+        }
+        Self {
+            positions,
+            cells,
+            distance,
+        }
+    }
+
+    fn cell(position: Vector3<f64>, distance: f64) -> [i64; 3] {
+        [position.x, position.y, position.z]
+            .map(|coordinate| (coordinate / distance).floor() as i64)
+    }
+
+    /// Returns `true` if any of the positions is within the distance of `position`.
+    fn has_neighbor(&self, position: Vector3<f64>) -> bool {
+        let [x, y, z] = Self::cell(position, self.distance);
+        (x - 1..=x + 1).any(|i| {
+            (y - 1..=y + 1).any(|j| {
+                (z - 1..=z + 1).any(|k| {
+                    self.cells.get(&[i, j, k]).is_some_and(|indices| {
+                        indices.iter().any(|&index| {
+                            (self.positions[index] - position).magnitude2()
+                                <= self.distance * self.distance
+                        })
+                    })
+                })
+            })
+        })
+    }
+}
+
+/// Reads the unit cell and space group from the `CRYST1` record, and the symmetry operators from the `SMTRY`
+/// records of `REMARK 290`. Without them, the operators are generated from the space group.
+fn parse_pdb_records(content: &str) -> Option<CrystalSymmetry> {
+    const REMARK_TEXT_START: usize = 10;
+
+    let cryst1 = content.lines().find(|line| line.starts_with("CRYST1"))?;
+    let field = |start: usize, end: usize| {
+        cryst1
+            .get(start..end.min(cryst1.len()))
+            .and_then(|field| field.trim().parse::<f64>().ok())
+    };
+    let unit_cell = UnitCell::new(
+        [field(6, 15)?, field(15, 24)?, field(24, 33)?],
+        [field(33, 40)?, field(40, 47)?, field(47, 54)?],
+    )?;
+    let space_group = cryst1
+        .get(55..66.min(cryst1.len()))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    // Each of the three rows (`SMTRY1` to `SMTRY3`) has the serial number, rotation and translation in Cartesian coordinates.
+    let mut rows: Vec<[[f64; 4]; 3]> = Vec::new();
+    for line in content
+        .lines()
+        .filter(|line| line.starts_with("REMARK 290"))
+    {
+        let text = line.get(REMARK_TEXT_START..).unwrap_or_default().trim();
+        let Some(smtry) = text.strip_prefix("SMTRY") else {
+            continue;
+        };
+        let fields = smtry.split_whitespace().collect::<Vec<_>>();
+        let (Some(row), Some(values)) = (
+            fields.first().and_then(|row| row.parse::<usize>().ok()),
+            fields.get(2..6).and_then(|values| {
+                values
+                    .iter()
+                    .map(|v| v.parse::<f64>().ok())
+                    .collect::<Option<Vec<_>>>()
+            }),
+        ) else {
+            continue;
+        };
+        if row == 1 {
+            rows.push([[0.0; 4]; 3]);
+        }
+        if let (Some(operator_rows), 1..=3) = (rows.last_mut(), row) {
+            operator_rows[row - 1].copy_from_slice(&values);
+        }
+    }
+
+    let operators = match rows.is_empty() {
+        true => space_group_operators(&space_group, unit_cell.is_hexagonal()).unwrap_or_default(),
+        false => {
+            let orthogonalization = unit_cell.orthogonalization();
+            let fractionalization = orthogonalization.invert()?;
+            rows.into_iter()
+                .map(|[r0, r1, r2]| {
+                    // cgmath matrices are created column by column.
+                    let rotation = Matrix3::new(
+                        r0[0], r1[0], r2[0], //
+                        r0[1], r1[1], r2[1], //
+                        r0[2], r1[2], r2[2],
+                    );
+                    SymmetryOperator {
+                        rotation: fractionalization * rotation * orthogonalization,
+                        translation: fractionalization * Vector3::new(r0[3], r1[3], r2[3]),
+                    }
+                })
+                .collect()
+        }
+    };
+
+    Some(CrystalSymmetry {
+        unit_cell,
+        space_group,
+        operators,
+    })
+}
+
+/// Reads the crystal symmetry from the mmCIF categories of both text and binary files.
+/// The symmetry operators are taken from the file if listed, otherwise they are generated from the space group.
+fn parse_cif_categories(file: &StructureFile) -> Option<CrystalSymmetry> {
+    let cell = file.cif_category("_cell");
+    let cell = cell.first()?;
+    let value = |name: &str| cell.get(name)?.parse::<f64>().ok();
+    let unit_cell = UnitCell::new(
+        [value("length_a")?, value("length_b")?, value("length_c")?],
+        [
+            value("angle_alpha")?,
+            value("angle_beta")?,
+            value("angle_gamma")?,
+        ],
+    )?;
+
+    let space_group = [
+        ("_symmetry", "space_group_name_H-M"),
+        ("_space_group", "name_H-M_alt"),
+    ]
+    .into_iter()
+    .find_map(|(category, item)| file.cif_category(category).first()?.get(item).cloned())
+    .unwrap_or_default();
+
+    let listed_operators = [
+        ("_space_group_symop", "operation_xyz"),
+        ("_symmetry_equiv", "pos_as_xyz"),
+    ]
+    .into_iter()
+    .map(|(category, item)| {
+        file.cif_category(category)
+            .iter()
+            .filter_map(|row| SymmetryOperator::parse(row.get(item)?))
+            .collect::<Vec<_>>()
+    })
+    .find(|operators| !operators.is_empty());
+    let operators = listed_operators
+        .or_else(|| space_group_operators(&space_group, unit_cell.is_hexagonal()))
+        .unwrap_or_default();
+
+    Some(CrystalSymmetry {
+        unit_cell,
+        space_group,
+        operators,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Matrix;
+
+    use super::*;
+
+    #[test]
+    fn operators_preserve_distances_in_their_cell() {
+        for (symbol, lengths, angles) in [
+            ("P 1", [30.0, 40.0, 50.0], [80.0, 95.0, 110.0]),
+            ("C 2", [50.0, 30.0, 40.0], [90.0, 105.0, 90.0]),
+            ("P 21 21 21", [20.0, 30.0, 40.0], [90.0, 90.0, 90.0]),
+            ("I 41 2 2", [60.0, 60.0, 90.0], [90.0, 90.0, 90.0]),
+            ("P 65 2 2", [70.0, 70.0, 150.0], [90.0, 90.0, 120.0]),
+            ("R 3 2", [90.0, 90.0, 110.0], [90.0, 90.0, 120.0]),
+            ("R 3 2", [60.0, 60.0, 60.0], [80.0, 80.0, 80.0]),
+            ("P 21 3", [80.0, 80.0, 80.0], [90.0, 90.0, 90.0]),
+        ] {
+            let unit_cell = UnitCell::new(lengths, angles).unwrap();
+            let orthogonalization = unit_cell.orthogonalization();
+            let fractionalization = orthogonalization.invert().unwrap();
+
+            let operators = space_group_operators(symbol, unit_cell.is_hexagonal()).unwrap();
+            for operator in operators {
+                // In Cartesian coordinates, the rotation of an operator mapping the cell onto itself is orthogonal.
+                let rotation = orthogonalization * operator.rotation * fractionalization;
+                let difference = rotation.transpose() * rotation - Matrix3::identity();
+                assert!(
+                    (0..3).all(|i| difference[i].magnitude() < 1e-9),
+                    "{} in the cell {:?} {:?}",
+                    symbol,
+                    lengths,
+                    angles
+                );
+            }
+        }
+    }
+
+    /// A single atom at the fractional coordinates `(0.1, 0.1, 0.1)` of a `P 21 21 21` crystal.
+    fn single_atom_crystal() -> (CrystalSymmetry, Vec<Atom>, Vec<AtomMetadata>) {
+        let content = "\
+CRYST1   20.000   30.000   40.000  90.00  90.00  90.00 P 21 21 21    4
+ATOM      1  O   HOH A   1       2.000   3.000   4.000  1.00  0.00           O
+";
+        let atom = Atom {
+            position: [2.0, 3.0, 4.0],
+            radius: 1.5,
+            color: [1.0; 4],
+        };
+        (
+            parse_pdb_records(content).unwrap(),
+            vec![atom],
+            vec![AtomMetadata::default()],
+        )
+    }
+
+    fn packed_positions(radius: f32) -> Vec<[i32; 3]> {
+        let (crystal, atoms, metadata) = single_atom_crystal();
+        let (packed, packed_metadata) = crystal
+            .pack(&atoms, &metadata, CrystalPacking::Radius { radius })
+            .unwrap();
+        assert_eq!(packed.len(), packed_metadata.len());
+
+        let mut positions = packed
+            .iter()
+            .map(|atom| atom.position.map(|coordinate| coordinate.round() as i32))
+            .collect::<Vec<_>>();
+        positions.sort();
+        positions
+    }
+
+    #[test]
+    fn radius_packing_finds_mates_within_the_radius() {
+        // The nearest mates are those of `x+1/2,-y+1/2,-z`, 15.7 Å away along both directions of `a`.
+        assert_eq!(packed_positions(8.0), [[2, 3, 4]]);
+        assert_eq!(
+            packed_positions(16.0),
+            [[-8, 12, -4], [2, 3, 4], [12, 12, -4]]
+        );
+        // Then those of `-x,y+1/2,-z+1/2` (19.6 Å) and the lattice translations along `a` (20 Å).
+        assert_eq!(
+            packed_positions(20.5),
+            [
+                [-18, 3, 4],
+                [-8, 12, -4],
+                [-2, -12, 16],
+                [-2, 18, 16],
+                [2, 3, 4],
+                [12, 12, -4],
+                [22, 3, 4]
+            ]
+        );
+    }
+
+    #[test]
+    fn unit_cell_packing_places_a_copy_per_operator() {
+        let (crystal, atoms, metadata) = single_atom_crystal();
+        let (packed, _) = crystal
+            .pack(&atoms, &metadata, CrystalPacking::UnitCells { count: 2 })
+            .unwrap();
+        assert_eq!(packed.len(), 4 * 2 * 2 * 2);
+    }
+}
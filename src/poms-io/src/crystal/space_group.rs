@@ -0,0 +1,352 @@
+//! Symmetry operators of the space groups proteins crystallize in (those without inversion centers or mirror planes).
+//! Each group is generated from a few operators written as coordinate triplets (e.g. `-x,y+1/2,-z`) in its standard setting,
+//! combined with the centering translations of its lattice.
+
+use cgmath::{Matrix, Matrix3, SquareMatrix, Vector3, Zero};
+
+/// A symmetry operator acting on fractional coordinates, rotating them and then adding the translation.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct SymmetryOperator {
+    pub(super) rotation: Matrix3<f64>,
+    pub(super) translation: Vector3<f64>,
+}
+
+impl SymmetryOperator {
+    const TOLERANCE: f64 = 1e-4;
+
+    pub(super) fn identity() -> Self {
+        Self {
+            rotation: Matrix3::identity(),
+            translation: Vector3::zero(),
+        }
+    }
+
+    /// Applies the operator to the fractional coordinates.
+    pub(super) fn apply(&self, coordinates: Vector3<f64>) -> Vector3<f64> {
+        self.rotation * coordinates + self.translation
+    }
+
+    /// Returns the operator applying `other` first and then `self`.
+    fn compose(&self, other: &Self) -> Self {
+        Self {
+            rotation: self.rotation * other.rotation,
+            translation: self.apply(other.translation),
+        }
+    }
+
+    /// Moves the translation to the unit cell at the origin, as operators differing by whole cells are equivalent.
+    fn normalized(self) -> Self {
+        let wrap = |value: f64| {
+            let value = value.rem_euclid(1.0);
+            match 1.0 - value < Self::TOLERANCE {
+                true => 0.0,
+                false => value,
+            }
+        };
+        Self {
+            translation: self.translation.map(wrap),
+            ..self
+        }
+    }
+
+    fn approx_eq(&self, other: &Self) -> bool {
+        let rotation_difference = self.rotation - other.rotation;
+        let translation_difference = self.translation - other.translation;
+        (0..3).all(|i| {
+            (0..3).all(|j| rotation_difference[i][j].abs() < Self::TOLERANCE)
+                && translation_difference[i].abs() < Self::TOLERANCE
+        })
+    }
+
+    /// Returns `true` if the operator leaves all coordinates in place.
+    pub(super) fn is_identity(&self) -> bool {
+        self.approx_eq(&Self::identity())
+    }
+
+    /// Parses a coordinate triplet, e.g. `-x,y+1/2,-z` or `X,Y,Z+0.5`, as used by mmCIF and the International Tables.
+    pub(super) fn parse(triplet: &str) -> Option<Self> {
+        let expressions = triplet.split(',').collect::<Vec<_>>();
+        let [x, y, z] = expressions.as_slice() else {
+            return None;
+        };
+
+        let mut rows = [Vector3::zero(); 3];
+        let mut translation = Vector3::zero();
+        for (i, expression) in [x, y, z].into_iter().enumerate() {
+            (rows[i], translation[i]) = parse_expression(expression)?;
+        }
+
+        Some(Self {
+            // cgmath matrices are created column by column.
+            rotation: Matrix3::from_cols(rows[0], rows[1], rows[2]).transpose(),
+            translation,
+        })
+    }
+}
+
+/// Parses a single coordinate of a triplet into the row of the rotation and the translation.
+fn parse_expression(expression: &str) -> Option<(Vector3<f64>, f64)> {
+    let expression = expression
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    // Split into signed terms, e.g. `-x+1/2` into `-x` and `+1/2`.
+    let mut terms = Vec::new();
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        if (c == '+' || c == '-') && i > start {
+            terms.push(&expression[start..i]);
+            start = i;
+        }
+    }
+    terms.push(&expression[start..]);
+
+    let mut row = Vector3::zero();
+    let mut translation = 0.0;
+    for term in terms {
+        let (sign, value) = match term.strip_prefix('-') {
+            Some(value) => (-1.0, value),
+            None => (1.0, term.strip_prefix('+').unwrap_or(term)),
+        };
+        match value {
+            "x" => row.x += sign,
+            "y" => row.y += sign,
+            "z" => row.z += sign,
+            _ => {
+                let number = match value.split_once('/') {
+                    Some((numerator, denominator)) => {
+                        numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?
+                    }
+                    None => value.parse::<f64>().ok()?,
+                };
+                translation += sign * number;
+            }
+        }
+    }
+    Some((row, translation))
+}
+
+/// Space groups by their Hermann-Mauguin symbol without spaces, with the generators of their standard setting.
+/// The lattice centering is given by the first letter of the symbol.
+#[rustfmt::skip]
+const SPACE_GROUPS: &[(&str, &[&str])] = &[
+    // Triclinic
+    ("P1", &[]),
+    // Monoclinic
+    ("P121", &["-x,y,-z"]),
+    ("P1211", &["-x,y+1/2,-z"]),
+    ("C121", &["-x,y,-z"]),
+    ("I121", &["-x,y,-z"]),
+    ("P112", &["-x,-y,z"]),
+    ("P1121", &["-x,-y,z+1/2"]),
+    // Orthorhombic
+    ("P222", &["-x,-y,z", "-x,y,-z"]),
+    ("P2221", &["-x,-y,z+1/2", "-x,y,-z+1/2"]),
+    ("P2122", &["x+1/2,-y,-z", "-x,y,-z"]),
+    ("P2212", &["-x,y+1/2,-z", "-x,-y,z"]),
+    ("P21212", &["-x,-y,z", "-x+1/2,y+1/2,-z"]),
+    ("P21221", &["-x,y,-z", "-x+1/2,-y,z+1/2"]),
+    ("P22121", &["x,-y,-z", "-x,-y+1/2,z+1/2"]),
+    ("P212121", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2"]),
+    ("C2221", &["-x,-y,z+1/2", "-x,y,-z+1/2"]),
+    ("C222", &["-x,-y,z", "-x,y,-z"]),
+    ("F222", &["-x,-y,z", "-x,y,-z"]),
+    ("I222", &["-x,-y,z", "-x,y,-z"]),
+    ("I212121", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2"]),
+    // Tetragonal
+    ("P4", &["-y,x,z"]),
+    ("P41", &["-y,x,z+1/4"]),
+    ("P42", &["-y,x,z+1/2"]),
+    ("P43", &["-y,x,z+3/4"]),
+    ("I4", &["-y,x,z"]),
+    ("I41", &["-y,x+1/2,z+1/4"]),
+    ("P422", &["-y,x,z", "-x,y,-z"]),
+    ("P4212", &["-y+1/2,x+1/2,z", "-x+1/2,y+1/2,-z"]),
+    ("P4122", &["-y,x,z+1/4", "-x,y,-z"]),
+    ("P41212", &["-y+1/2,x+1/2,z+1/4", "-x+1/2,y+1/2,-z+1/4"]),
+    ("P4222", &["-y,x,z+1/2", "-x,y,-z"]),
+    ("P42212", &["-y+1/2,x+1/2,z+1/2", "-x+1/2,y+1/2,-z+1/2"]),
+    ("P4322", &["-y,x,z+3/4", "-x,y,-z"]),
+    ("P43212", &["-y+1/2,x+1/2,z+3/4", "-x+1/2,y+1/2,-z+3/4"]),
+    ("I422", &["-y,x,z", "-x,y,-z"]),
+    ("I4122", &["-y,x+1/2,z+1/4", "-x+1/2,y,-z+3/4"]),
+    // Trigonal, rhombohedral groups in the hexagonal setting
+    ("P3", &["-y,x-y,z"]),
+    ("P31", &["-y,x-y,z+1/3"]),
+    ("P32", &["-y,x-y,z+2/3"]),
+    ("H3", &["-y,x-y,z"]),
+    ("P312", &["-y,x-y,z", "-y,-x,-z"]),
+    ("P321", &["-y,x-y,z", "y,x,-z"]),
+    ("P3112", &["-y,x-y,z+1/3", "-y,-x,-z+2/3"]),
+    ("P3121", &["-y,x-y,z+1/3", "y,x,-z"]),
+    ("P3212", &["-y,x-y,z+2/3", "-y,-x,-z+1/3"]),
+    ("P3221", &["-y,x-y,z+2/3", "y,x,-z"]),
+    ("H32", &["-y,x-y,z", "y,x,-z"]),
+    // Hexagonal
+    ("P6", &["x-y,x,z"]),
+    ("P61", &["x-y,x,z+1/6"]),
+    ("P65", &["x-y,x,z+5/6"]),
+    ("P62", &["x-y,x,z+1/3"]),
+    ("P64", &["x-y,x,z+2/3"]),
+    ("P63", &["x-y,x,z+1/2"]),
+    ("P622", &["x-y,x,z", "y,x,-z"]),
+    ("P6122", &["x-y,x,z+1/6", "y,x,-z+1/3"]),
+    ("P6522", &["x-y,x,z+5/6", "y,x,-z+2/3"]),
+    ("P6222", &["x-y,x,z+1/3", "y,x,-z+2/3"]),
+    ("P6422", &["x-y,x,z+2/3", "y,x,-z+1/3"]),
+    ("P6322", &["x-y,x,z+1/2", "y,x,-z"]),
+    // Cubic
+    ("P23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("F23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("I23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("P213", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y"]),
+    ("I213", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y"]),
+    ("P432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    ("P4232", &["-x,-y,z", "-x,y,-z", "z,x,y", "y+1/2,x+1/2,-z+1/2"]),
+    ("F432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    ("F4132", &["-x,-y+1/2,z+1/2", "-x+1/2,y+1/2,-z", "z,x,y", "y+3/4,x+1/4,-z+3/4"]),
+    ("I432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    ("P4332", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y", "y+1/4,x+3/4,-z+3/4"]),
+    ("P4132", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y", "y+3/4,x+1/4,-z+1/4"]),
+    ("I4132", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y", "y+3/4,x+1/4,-z+1/4"]),
+];
+
+/// Generators of the rhombohedral groups in the rhombohedral setting, used when the cell is not hexagonal.
+#[rustfmt::skip]
+const RHOMBOHEDRAL_SPACE_GROUPS: &[(&str, &[&str])] = &[
+    ("R3", &["z,x,y"]),
+    ("R32", &["z,x,y", "-z,-y,-x"]),
+];
+
+/// Returns all symmetry operators of the space group given by its Hermann-Mauguin symbol (e.g. `P 21 21 21`),
+/// or `None` if the group is not known. Rhombohedral groups (`R 3`, `R 3 2`) are in the hexagonal setting
+/// if the cell is hexagonal (`is_hexagonal_cell`), as in the PDB, otherwise in the rhombohedral one.
+pub(super) fn space_group_operators(
+    symbol: &str,
+    is_hexagonal_cell: bool,
+) -> Option<Vec<SymmetryOperator>> {
+    let symbol = symbol
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    // Monoclinic groups are often written in the short form.
+    let symbol = match symbol.as_str() {
+        "P2" => "P121",
+        "P21" => "P1211",
+        "C2" => "C121",
+        "I2" => "I121",
+        "R3" if is_hexagonal_cell => "H3",
+        "R32" if is_hexagonal_cell => "H32",
+        symbol => symbol,
+    };
+
+    let (_, generators) = SPACE_GROUPS
+        .iter()
+        .chain(RHOMBOHEDRAL_SPACE_GROUPS)
+        .find(|(name, _)| *name == symbol)?;
+    let generators = generators
+        .iter()
+        .map(|triplet| SymmetryOperator::parse(triplet))
+        .collect::<Option<Vec<_>>>()?;
+
+    // Combine the operators until no new ones appear, which gives the whole group.
+    let mut operators = vec![SymmetryOperator::identity()];
+    let mut index = 0;
+    while let Some(&operator) = operators.get(index) {
+        for generator in &generators {
+            let product = generator.compose(&operator).normalized();
+            if !operators.iter().any(|known| known.approx_eq(&product)) {
+                operators.push(product);
+            }
+        }
+        index += 1;
+    }
+
+    let centering: &[[f64; 3]] = match symbol.chars().next() {
+        Some('A') => &[[0.0, 0.5, 0.5]],
+        Some('B') => &[[0.5, 0.0, 0.5]],
+        Some('C') => &[[0.5, 0.5, 0.0]],
+        Some('I') => &[[0.5, 0.5, 0.5]],
+        Some('F') => &[[0.0, 0.5, 0.5], [0.5, 0.0, 0.5], [0.5, 0.5, 0.0]],
+        Some('H') => &[
+            [2.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+            [1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
+        ],
+        _ => &[],
+    };
+    let centered_operators = centering.iter().flat_map(|&translation| {
+        operators.iter().map(move |operator| {
+            SymmetryOperator {
+                translation: operator.translation + Vector3::from(translation),
+                ..*operator
+            }
+            .normalized()
+        })
+    });
+
+    Some(
+        operators
+            .iter()
+            .copied()
+            .chain(centered_operators)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    #[test]
+    fn generates_all_operators_of_the_group() {
+        for (symbol, count) in [
+            ("P 1", 1),
+            ("P 21 21 21", 4),
+            ("C 2", 4),
+            ("P 65 2 2", 12),
+            ("I 41 2 2", 16),
+        ] {
+            let operators = space_group_operators(symbol, false).unwrap();
+            assert_eq!(operators.len(), count, "{}", symbol);
+        }
+        assert!(space_group_operators("P 21/c", false).is_none());
+    }
+
+    #[test]
+    fn operators_form_a_group_of_lattice_symmetries() {
+        for (symbol, _) in SPACE_GROUPS.iter().chain(RHOMBOHEDRAL_SPACE_GROUPS) {
+            let operators = space_group_operators(symbol, false).unwrap();
+            for operator in &operators {
+                // Lattice points are mapped onto lattice points, without changing the handedness.
+                let rotation = operator.rotation;
+                assert!((0..3).all(|i| (0..3).all(|j| rotation[i][j].fract() == 0.0)));
+                assert_eq!(rotation.determinant(), 1.0, "{}", symbol);
+                assert!((0..3).all(|i| (0.0..1.0).contains(&operator.translation[i])));
+
+                for other in &operators {
+                    let product = operator.compose(other).normalized();
+                    assert!(
+                        operators.iter().any(|known| known.approx_eq(&product)),
+                        "{} is not closed",
+                        symbol
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_coordinate_triplets() {
+        let operator = SymmetryOperator::parse("-y+1/2, X-Y, z+0.25").unwrap();
+        let coordinates = operator.apply(Vector3::new(0.1, 0.2, 0.3));
+        let expected = Vector3::new(0.3, -0.1, 0.55);
+        assert!((coordinates - expected).magnitude() < 1e-9);
+        assert!(SymmetryOperator::parse("x,y").is_none());
+        assert!(SymmetryOperator::parse("x,y,w").is_none());
+    }
+}
@@ -0,0 +1,28 @@
+//! Reading of molecule files shared by the viewer and the command-line tool, so both load the same atoms
+//! with the same radii from a file, along with the data read besides the atoms (e.g. assemblies or trajectories).
+
+pub mod biological_assembly;
+pub mod crystal;
+pub mod molecule_parser;
+pub mod radius_set;
+pub mod trajectory;
+
+mod binary_cif;
+mod cif;
+mod model_confidence;
+mod secondary_structure;
+mod structure_file;
+
+/// Holds the raw content of a loaded file.
+pub struct RawFile {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+/// Returns `true` for residue names commonly used for water molecules.
+pub fn is_water(residue_name: &str) -> bool {
+    matches!(
+        residue_name,
+        "HOH" | "WAT" | "H2O" | "DOD" | "TIP" | "TIP3" | "SOL"
+    )
+}
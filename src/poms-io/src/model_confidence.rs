@@ -0,0 +1,80 @@
+//! Per-residue confidence (pLDDT) of predicted models from the `_ma_qa_metric_local` category of ModelCIF files,
+//! e.g. from ModelArchive. AlphaFold DB files also store it in the B-factor column, where it is kept for other files.
+
+use std::collections::HashMap;
+
+use poms_common::models::atom::AtomMetadata;
+
+use super::structure_file::StructureFile;
+
+/// pLDDT of each residue, keyed by the chain identifier and residue number used by the atoms.
+pub(crate) struct ResidueConfidences(HashMap<(String, isize), f32>);
+
+impl ResidueConfidences {
+    /// Reads the pLDDT of the residues from the file. Returns no values if the file does not specify any.
+    pub(crate) fn from_file(file: &StructureFile) -> Self {
+        Self(parse_cif_categories(file))
+    }
+
+    /// Sets `b_factor` of each atom in `metadata` to the pLDDT of its residue. Atoms of residues without a value are left as is.
+    pub(crate) fn assign(&self, metadata: &mut [AtomMetadata]) {
+        if self.0.is_empty() {
+            return;
+        }
+
+        for atom in metadata.iter_mut() {
+            if let Some(&plddt) = self.0.get(&(atom.chain_id.clone(), atom.residue_number)) {
+                atom.b_factor = plddt;
+            }
+        }
+    }
+}
+
+fn parse_cif_categories(file: &StructureFile) -> HashMap<(String, isize), f32> {
+    // The local pLDDT metric, which may also be given as a fraction instead of a percentage.
+    let Some((metric_id, scale)) = file
+        .cif_category("_ma_qa_metric")
+        .iter()
+        .filter(|row| row.get("mode").is_some_and(|mode| mode == "local"))
+        .find_map(|row| {
+            let scale = match row.get("type")?.as_str() {
+                "pLDDT" => 1.0,
+                "pLDDT in [0,1]" => 100.0,
+                _ => return None,
+            };
+            Some((row.get("id")?.clone(), scale))
+        })
+    else {
+        return HashMap::new();
+    };
+
+    // Values refer to residues by their label identifiers, while atoms use the author ones if the file has them.
+    let author_residues = file
+        .cif_category("_pdbx_poly_seq_scheme")
+        .iter()
+        .filter_map(|row| {
+            let label = (row.get("asym_id")?.clone(), row.get("seq_id")?.clone());
+            let number = ["pdb_seq_num", "auth_seq_num"]
+                .iter()
+                .find_map(|item| row.get(*item)?.parse::<isize>().ok())?;
+            Some((label, (row.get("pdb_strand_id")?.clone(), number)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    file.cif_category("_ma_qa_metric_local")
+        .iter()
+        .filter(|row| row.get("metric_id") == Some(&metric_id))
+        .filter_map(|row| {
+            let label = (
+                row.get("label_asym_id")?.clone(),
+                row.get("label_seq_id")?.clone(),
+            );
+            let residue = match author_residues.get(&label) {
+                Some(residue) => residue.clone(),
+                None => (label.0, label.1.parse().ok()?),
+            };
+            let plddt = row.get("metric_value")?.parse::<f32>().ok()? * scale;
+            Some((residue, plddt))
+        })
+        .collect()
+}
@@ -0,0 +1,440 @@
+mod bcif;
+mod gro;
+pub mod load_options;
+mod mol2;
+mod pqr;
+mod sdf;
+mod simple_parser;
+mod xyz;
+
+use std::io::{BufReader, Cursor, Read};
+
+use crate::binary_cif::BinaryCif;
+use crate::biological_assembly::BiologicalAssembly;
+use crate::crystal::CrystalSymmetry;
+use crate::model_confidence::ResidueConfidences;
+use crate::secondary_structure::SecondaryStructureRanges;
+use crate::structure_file::StructureFile;
+use crate::trajectory::Trajectory;
+use crate::RawFile;
+use load_options::LoadOptions;
+use poms_common::limits::MAX_NUM_ATOMS;
+use poms_common::models::atom::{Atom, AtomMetadata, SecondaryStructure};
+use poms_common::models::element::jmol_color;
+
+/// A parsed molecule from any of the supported [`MoleculeFormat`]s. Each model of a multi-model file is a separate molecule.
+pub struct ParsedMolecule {
+    pub filename: String,
+    pub atoms: Vec<Atom>,
+    /// Metadata of each atom, in the same order as `atoms`.
+    pub metadata: Vec<AtomMetadata>,
+    /// Trajectory providing positions of `atoms` in each frame, if the molecule was opened with one.
+    pub trajectory: Option<Trajectory>,
+    /// Biological assemblies defined in the file, which can be built from `atoms` (the asymmetric unit).
+    pub assemblies: Vec<BiologicalAssembly>,
+    /// Unit cell and space group of the crystal, if the structure was determined by crystallography.
+    pub crystal: Option<CrystalSymmetry>,
+    /// Options the atoms were filtered by when the file was loaded.
+    pub load_options: LoadOptions,
+    /// Whether each atom of the model in the file is kept by `load_options`, in the original order.
+    /// Frames of `trajectory` contain the positions of all of them.
+    pub kept_atoms: Vec<bool>,
+}
+
+/// Atoms of a single model of a file, before they are turned into a [`ParsedMolecule`].
+struct ParsedModel {
+    serial_number: usize,
+    atoms: Vec<Atom>,
+    metadata: Vec<AtomMetadata>,
+}
+
+impl ParsedModel {
+    fn new(serial_number: usize) -> Self {
+        Self {
+            serial_number,
+            atoms: Vec::new(),
+            metadata: Vec::new(),
+        }
+    }
+}
+
+/// Supported formats of molecule files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoleculeFormat {
+    Pdb,
+    Cif,
+    BinaryCif,
+    Pqr,
+    Gro,
+    Xyz,
+    Mol2,
+    Sdf,
+}
+
+impl MoleculeFormat {
+    /// File extensions of all supported formats.
+    pub const EXTENSIONS: [&'static str; 11] = [
+        "pdb", "ent", "cif", "mmcif", "bcif", "pqr", "gro", "xyz", "mol2", "sdf", "mol",
+    ];
+
+    /// Determines the format from the extension of the file name, or from the content if the extension is not known.
+    /// Files that are not recognized are treated as PDB, as that is the most common format.
+    pub fn detect(file_name: &str, content: &[u8]) -> Self {
+        Self::from_file_name(file_name)
+            .or_else(|| Self::sniff(content))
+            .unwrap_or(Self::Pdb)
+    }
+
+    /// Determines the format from the extension of the file name, ignoring the `.gz` extension of compressed files.
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        let file_name = file_name.to_ascii_lowercase();
+        let file_name = file_name.strip_suffix(".gz").unwrap_or(&file_name);
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension {
+            "pdb" | "ent" => Some(Self::Pdb),
+            "cif" | "mmcif" => Some(Self::Cif),
+            "bcif" => Some(Self::BinaryCif),
+            "pqr" => Some(Self::Pqr),
+            "gro" => Some(Self::Gro),
+            "xyz" => Some(Self::Xyz),
+            "mol2" => Some(Self::Mol2),
+            "sdf" | "mol" => Some(Self::Sdf),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first lines of the content.
+    fn sniff(content: &[u8]) -> Option<Self> {
+        if BinaryCif::is_binary_cif(content) {
+            return Some(Self::BinaryCif);
+        }
+
+        // Only the beginning of the file is needed, which may end in the middle of a multi-byte character.
+        let head = &content[..content.len().min(4096)];
+        let head = match std::str::from_utf8(head) {
+            Ok(head) => head,
+            Err(error) => std::str::from_utf8(&head[..error.valid_up_to()]).ok()?,
+        };
+        let lines = head.lines().collect::<Vec<_>>();
+        let is_count =
+            |line: Option<&&str>| line.is_some_and(|line| line.trim().parse::<usize>().is_ok());
+
+        if head.contains("@<TRIPOS>") {
+            Some(Self::Mol2)
+        } else if lines
+            .get(3)
+            .is_some_and(|line| line.contains("V2000") || line.contains("V3000"))
+        {
+            Some(Self::Sdf)
+        } else if lines.iter().any(|line| line.starts_with("data_")) {
+            Some(Self::Cif)
+        } else if lines.iter().any(|line| {
+            ["ATOM  ", "HETATM", "HEADER", "MODEL ", "CRYST1"]
+                .iter()
+                .any(|record| line.starts_with(record))
+        }) {
+            Some(Self::Pdb)
+        } else if is_count(lines.first()) {
+            Some(Self::Xyz)
+        } else if is_count(lines.get(1)) {
+            Some(Self::Gro)
+        } else {
+            None
+        }
+    }
+
+    /// What a single model of a multi-model file is called in this format.
+    fn model_name(self) -> &'static str {
+        match self {
+            Self::Pdb | Self::Cif | Self::BinaryCif | Self::Pqr => "model",
+            Self::Gro | Self::Xyz => "frame",
+            Self::Mol2 | Self::Sdf => "molecule",
+        }
+    }
+}
+
+/// Attempts to parse a molecule file as bytes into [`ParsedMolecule`]s, one for each model in the file.
+/// Gzip compressed files are decompressed first, then the format is detected by [`MoleculeFormat::detect`].
+/// Models of multi-model files (e.g. NMR ensembles, MD snapshots or frames of an XYZ file) can then be animated like separate files.
+/// Atoms excluded by `load_options` (e.g. other alternate locations) are removed from each model.
+pub fn parse_atoms_from_pdb_file(
+    mut file: RawFile,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    file.content = decompress(file.content)?;
+    let format = MoleculeFormat::detect(&file.name, &file.content);
+
+    // BinaryCIF files are decoded once, for the atoms as well as the other data read from the file.
+    let mut binary_cif = None;
+    let models: Vec<ParsedModel> = match format {
+        MoleculeFormat::Pdb | MoleculeFormat::Cif => {
+            let buffer = BufReader::new(Cursor::new(&file.content));
+            match pdbtbx::open_raw(buffer, pdbtbx::StrictnessLevel::Loose) {
+                Ok((pdb, _)) => pdb.models().map(convert_to_internal_model).collect(),
+                // If pdbtbx fails to parse the file (e.g. due to missing header), fallback to a simple parser.
+                Err(_) => simple_parser::try_parse_pdb(&file.content)?,
+            }
+        }
+        MoleculeFormat::BinaryCif => {
+            bcif::try_parse_bcif(binary_cif.insert(BinaryCif::decode(&file.content)?))?
+        }
+        MoleculeFormat::Pqr => pqr::try_parse_pqr(&file.content)?,
+        MoleculeFormat::Gro => gro::try_parse_gro(&file.content)?,
+        MoleculeFormat::Xyz => xyz::try_parse_xyz(&file.content)?,
+        MoleculeFormat::Mol2 => mol2::try_parse_mol2(&file.content)?,
+        MoleculeFormat::Sdf => sdf::try_parse_sdf(&file.content)?,
+    };
+    let models = models
+        .into_iter()
+        .map(|mut model| {
+            let kept_atoms = load_options.apply(&mut model);
+            (model, kept_atoms)
+        })
+        .filter(|(model, _)| !model.atoms.is_empty())
+        .collect::<Vec<_>>();
+    if models.is_empty() {
+        anyhow::bail!("No atoms found in file");
+    }
+
+    // Data not provided by pdbtbx (e.g. secondary structure) is read from the file separately, parsed once for all readers.
+    let structure_file = match binary_cif {
+        Some(binary_cif) => Some(StructureFile::BinaryCif(binary_cif)),
+        None => StructureFile::parse(format, &file.content),
+    };
+    let secondary_structure = structure_file
+        .as_ref()
+        .map(SecondaryStructureRanges::from_file);
+    let (assemblies, crystal, confidences) = match (format, &structure_file) {
+        (MoleculeFormat::Pdb | MoleculeFormat::Cif | MoleculeFormat::BinaryCif, Some(file)) => (
+            BiologicalAssembly::from_file(file),
+            CrystalSymmetry::from_file(file),
+            Some(ResidueConfidences::from_file(file)),
+        ),
+        _ => (Vec::new(), None, None),
+    };
+    let is_multi_model = models.len() > 1;
+
+    models
+        .into_iter()
+        .map(|(mut model, kept_atoms)| {
+            if model.atoms.len() > MAX_NUM_ATOMS {
+                return Err(anyhow::Error::msg(format!(
+                    "Number of atoms in the file exceeds the limit ({}).",
+                    MAX_NUM_ATOMS
+                )));
+            }
+            if let Some(secondary_structure) = &secondary_structure {
+                secondary_structure.assign(&mut model.metadata);
+            }
+            // `pdbtbx` only reads the author chains of mmCIF files, while their assemblies refer to the label chains.
+            if let Some(StructureFile::Cif(categories)) = &structure_file {
+                for metadata in &mut model.metadata {
+                    let residue = (
+                        metadata.chain_id.clone(),
+                        metadata.residue_number,
+                        metadata.insertion_code.clone(),
+                    );
+                    metadata.label_asym_id = categories.label_asym_id(&residue).map(str::to_string);
+                }
+            }
+            // The pLDDT of predicted models is shown in place of the B-factor, e.g. by the pLDDT color scheme.
+            if let Some(confidences) = &confidences {
+                confidences.assign(&mut model.metadata);
+            }
+
+            let filename = match is_multi_model {
+                true => format!(
+                    "{} ({} {})",
+                    file.name,
+                    format.model_name(),
+                    model.serial_number
+                ),
+                false => file.name.clone(),
+            };
+            Ok(ParsedMolecule {
+                filename,
+                atoms: model.atoms,
+                metadata: model.metadata,
+                trajectory: None,
+                assemblies: assemblies.clone(),
+                crystal: crystal.clone(),
+                load_options,
+                kept_atoms,
+            })
+        })
+        .collect()
+}
+
+/// Decompresses the content if it starts with the gzip magic bytes, otherwise returns it unchanged.
+fn decompress(content: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+    if !content.starts_with(&GZIP_MAGIC) {
+        return Ok(content);
+    }
+
+    // Files may consist of multiple concatenated gzip members, which are decompressed one after another.
+    let mut decompressed = Vec::new();
+    flate2::read::MultiGzDecoder::new(content.as_slice())
+        .read_to_end(&mut decompressed)
+        .map_err(|error| anyhow::anyhow!("Decompressing the file failed: {}", error))?;
+    Ok(decompressed)
+}
+
+/// A convenience function to parse multiple molecule files at once. Multi-model files yield multiple molecules.
+pub fn parse_multiple_files(
+    loaded_files: Vec<RawFile>,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    let parsed_files = loaded_files
+        .into_iter()
+        .map(|file| parse_atoms_from_pdb_file(file, load_options))
+        .collect::<anyhow::Result<Vec<Vec<ParsedMolecule>>>>()?;
+    Ok(parsed_files.into_iter().flatten().collect())
+}
+
+/// Parses the topology file and attaches the trajectories to it. Each trajectory becomes a separate molecule,
+/// sharing the atoms and their metadata from the topology, positioned as in the first frame of the trajectory.
+/// Atoms of the topology excluded by `load_options` are left out, along with their positions in the frames.
+pub fn attach_trajectories(
+    topology_files: Vec<RawFile>,
+    trajectories: Vec<Trajectory>,
+    load_options: LoadOptions,
+) -> anyhow::Result<Vec<ParsedMolecule>> {
+    let mut topologies = parse_multiple_files(topology_files, load_options)?;
+    if topologies.len() != 1 {
+        anyhow::bail!(
+            "Trajectories have to be opened together with exactly one topology file (e.g. PDB) containing a single model"
+        );
+    }
+    let topology = topologies.remove(0);
+
+    trajectories
+        .into_iter()
+        .map(|mut trajectory| {
+            // The trajectories have positions of all atoms in the file, including the removed ones.
+            if trajectory.num_atoms() != topology.kept_atoms.len() {
+                anyhow::bail!(
+                    "The trajectory {} has {} atoms, but the topology {} has {}",
+                    trajectory.filename,
+                    trajectory.num_atoms(),
+                    topology.filename,
+                    topology.kept_atoms.len()
+                );
+            }
+
+            let mut atoms = topology.atoms.clone();
+            let positions = trajectory.read_frame(0)?;
+            for (atom, position) in atoms.iter_mut().zip(kept(positions, &topology.kept_atoms)) {
+                atom.position = *position;
+            }
+            Ok(ParsedMolecule {
+                filename: trajectory.filename.clone(),
+                atoms,
+                metadata: topology.metadata.clone(),
+                trajectory: Some(trajectory),
+                assemblies: topology.assemblies.clone(),
+                crystal: topology.crystal.clone(),
+                load_options: topology.load_options,
+                kept_atoms: topology.kept_atoms.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Selects the values of the atoms kept by the load options, e.g. their positions in a trajectory frame.
+pub fn kept<'a, T>(values: &'a [T], kept_atoms: &'a [bool]) -> impl Iterator<Item = &'a T> {
+    values
+        .iter()
+        .zip(kept_atoms)
+        .filter_map(|(value, &is_kept)| is_kept.then_some(value))
+}
+
+/// Converts all atoms of `pdbtbx::Model` to our internal `Atom` structs, along with the metadata of each atom.
+fn convert_to_internal_model(model: &pdbtbx::Model) -> ParsedModel {
+    let mut atoms = Vec::new();
+    let mut metadata = Vec::new();
+
+    // Walk the hierarchy in the same order as `model.atoms()` to keep track of the chain, residue and conformer of each atom.
+    for chain in model.chains() {
+        for residue in chain.residues() {
+            for conformer in residue.conformers() {
+                for atom in conformer.atoms() {
+                    atoms.push(convert_to_internal_atom(atom));
+                    metadata.push(AtomMetadata {
+                        chain_id: chain.id().to_string(),
+                        label_asym_id: None,
+                        residue_name: conformer.name().to_string(),
+                        residue_number: residue.serial_number(),
+                        insertion_code: residue.insertion_code().map(str::to_string),
+                        atom_name: atom.name().to_string(),
+                        element: atom.element().map(|e| e.symbol().to_string()),
+                        b_factor: atom.b_factor() as f32,
+                        occupancy: atom.occupancy() as f32,
+                        alt_loc: conformer.alternative_location().map(str::to_string),
+                        hetero: atom.hetero(),
+                        charge: None,
+                        secondary_structure: SecondaryStructure::default(),
+                    });
+                }
+            }
+        }
+    }
+
+    ParsedModel {
+        serial_number: model.serial_number(),
+        atoms,
+        metadata,
+    }
+}
+
+/// Converts `pdbtbx::Atom` to our internal `Atom` struct.
+fn convert_to_internal_atom(atom: &pdbtbx::Atom) -> Atom {
+    Atom {
+        position: {
+            let (x, y, z) = atom.pos();
+            [x as f32, y as f32, z as f32]
+        },
+        radius: get_vdw_radius(atom.element()),
+        color: get_jmol_color(atom.element()),
+    }
+}
+
+fn get_vdw_radius(element: Option<&pdbtbx::Element>) -> f32 {
+    const DEFAULT_RADIUS: f64 = 1.0;
+    element
+        .map(|e| e.atomic_radius().van_der_waals.unwrap_or(DEFAULT_RADIUS))
+        .unwrap_or(DEFAULT_RADIUS) as f32
+}
+
+/// Returns the CPK color of the element as used by Jmol.
+pub fn get_jmol_color(element: Option<&pdbtbx::Element>) -> [f32; 4] {
+    jmol_color(element.map_or("", |element| element.symbol()))
+}
+
+/// Creates an atom with the radius and color of its element, used by formats that do not provide radii.
+fn atom_of_element(position: [f32; 3], element: Option<&pdbtbx::Element>) -> Atom {
+    Atom {
+        position,
+        radius: get_vdw_radius(element),
+        color: get_jmol_color(element),
+    }
+}
+
+/// Infers the element for formats without an element column from the atom name, skipping leading digits
+/// (e.g. `1HB`). Monoatomic ions (e.g. `NA`, `CL` or `ZN`) are named after their element, as is their residue.
+fn infer_element(atom_name: &str, residue_name: &str) -> Option<pdbtbx::Element> {
+    let letters = atom_name
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+
+    if atom_name.eq_ignore_ascii_case(residue_name) {
+        if let Ok(element) = pdbtbx::Element::try_from(letters) {
+            return Some(element);
+        }
+    }
+    letters
+        .get(..1)
+        .and_then(|symbol| pdbtbx::Element::try_from(symbol).ok())
+}
@@ -0,0 +1,119 @@
+//! Reader of the `atom_site` category of BinaryCIF files, splitting the atoms into models by `pdbx_PDB_model_num`.
+
+use super::{atom_of_element, infer_element, AtomMetadata, ParsedModel, SecondaryStructure};
+use crate::binary_cif::{BinaryCif, Column};
+
+/// Parses the atoms of a BinaryCIF file. Author-defined names and numbers are preferred, as `pdbtbx` does for mmCIF.
+pub(super) fn try_parse_bcif(file: &BinaryCif) -> anyhow::Result<Vec<ParsedModel>> {
+    let Some(atom_site) = file.category("_atom_site")? else {
+        anyhow::bail!("The BinaryCIF file has no atom_site category");
+    };
+
+    let column = |names: &[&str]| names.iter().find_map(|name| atom_site.column(name));
+    let position = |name: &str| {
+        atom_site
+            .column(name)
+            .ok_or_else(|| anyhow::anyhow!("The atom_site category has no {} column", name))
+    };
+    let (x, y, z) = (
+        position("Cartn_x")?,
+        position("Cartn_y")?,
+        position("Cartn_z")?,
+    );
+
+    let columns = AtomSiteColumns {
+        model_number: column(&["pdbx_PDB_model_num"]),
+        group: column(&["group_PDB"]),
+        element: column(&["type_symbol"]),
+        atom_name: column(&["auth_atom_id", "label_atom_id"]),
+        residue_name: column(&["auth_comp_id", "label_comp_id"]),
+        chain_id: column(&["auth_asym_id", "label_asym_id"]),
+        label_asym_id: column(&["label_asym_id"]),
+        residue_number: column(&["auth_seq_id", "label_seq_id"]),
+        insertion_code: column(&["pdbx_PDB_ins_code"]),
+        alt_loc: column(&["label_alt_id"]),
+        b_factor: column(&["B_iso_or_equiv"]),
+        occupancy: column(&["occupancy"]),
+    };
+
+    let mut models: Vec<ParsedModel> = vec![];
+    for row in 0..atom_site.row_count {
+        let serial_number = columns
+            .model_number
+            .and_then(|c| c.integer(row))
+            .and_then(|number| usize::try_from(number).ok())
+            .unwrap_or(1);
+        if models
+            .last()
+            .map_or(true, |model| model.serial_number != serial_number)
+        {
+            models.push(ParsedModel::new(serial_number));
+        }
+        let model = models.last_mut().expect("a model was just added");
+
+        let coordinate = |column: &Column| {
+            column.float(row).map(|value| value as f32).ok_or_else(|| {
+                anyhow::anyhow!("Atom {} of the BinaryCIF file has no position", row + 1)
+            })
+        };
+        let position = [coordinate(x)?, coordinate(y)?, coordinate(z)?];
+
+        let metadata = columns.metadata(row);
+        let element = metadata
+            .element
+            .as_deref()
+            .and_then(|symbol| pdbtbx::Element::try_from(symbol).ok())
+            .or_else(|| infer_element(&metadata.atom_name, &metadata.residue_name));
+        model
+            .atoms
+            .push(atom_of_element(position, element.as_ref()));
+        model.metadata.push(AtomMetadata {
+            element: element.map(|e| e.symbol().to_string()),
+            ..metadata
+        });
+    }
+
+    Ok(models)
+}
+
+/// Columns of the `atom_site` category that are read, if present.
+struct AtomSiteColumns<'a> {
+    model_number: Option<&'a Column<'a>>,
+    group: Option<&'a Column<'a>>,
+    element: Option<&'a Column<'a>>,
+    atom_name: Option<&'a Column<'a>>,
+    residue_name: Option<&'a Column<'a>>,
+    chain_id: Option<&'a Column<'a>>,
+    label_asym_id: Option<&'a Column<'a>>,
+    residue_number: Option<&'a Column<'a>>,
+    insertion_code: Option<&'a Column<'a>>,
+    alt_loc: Option<&'a Column<'a>>,
+    b_factor: Option<&'a Column<'a>>,
+    occupancy: Option<&'a Column<'a>>,
+}
+
+impl AtomSiteColumns<'_> {
+    fn metadata(&self, row: usize) -> AtomMetadata {
+        let string = |column: Option<&Column>| column.and_then(|c| c.string(row));
+        let float = |column: Option<&Column>| column.and_then(|c| c.float(row));
+
+        AtomMetadata {
+            chain_id: string(self.chain_id).unwrap_or_default(),
+            label_asym_id: string(self.label_asym_id),
+            residue_name: string(self.residue_name).unwrap_or_default(),
+            residue_number: self
+                .residue_number
+                .and_then(|c| c.integer(row))
+                .unwrap_or_default() as isize,
+            insertion_code: string(self.insertion_code).filter(|code| !code.is_empty()),
+            atom_name: string(self.atom_name).unwrap_or_default(),
+            element: string(self.element),
+            b_factor: float(self.b_factor).unwrap_or_default() as f32,
+            occupancy: float(self.occupancy).unwrap_or(1.0) as f32,
+            alt_loc: string(self.alt_loc).filter(|alt_loc| !alt_loc.is_empty()),
+            hetero: string(self.group).is_some_and(|group| group == "HETATM"),
+            charge: None,
+            secondary_structure: SecondaryStructure::default(),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Reader of GROMACS GRO files. Each frame has a title line, the number of atoms, one fixed-column line per atom
+//! and the box vectors. Positions are in nanometers, the precision is given by the spacing of their decimal points.
+
+use std::ops::Range;
+
+use super::{atom_of_element, infer_element, AtomMetadata, ParsedModel};
+use crate::trajectory::NANOMETERS_TO_ANGSTROMS;
+
+const LINE_RESIDUE_NUMBER: Range<usize> = 0..5;
+const LINE_RESIDUE_NAME: Range<usize> = 5..10;
+const LINE_ATOM_NAME: Range<usize> = 10..15;
+const LINE_POSITION_START: usize = 20;
+
+/// Parses all frames of a GRO file, each becoming a separate model.
+pub(super) fn try_parse_gro(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    // Trailing empty lines would otherwise be read as the title of another frame.
+    let content = std::str::from_utf8(content)?.trim_end();
+    let mut lines = content.lines().enumerate().skip(1);
+
+    while let Some((line_index, line)) = lines.next() {
+        let num_atoms = line.trim().parse::<usize>().map_err(|_| {
+            anyhow::anyhow!(
+                "Line {} of the GRO file: invalid number of atoms `{}`",
+                line_index + 1,
+                line.trim()
+            )
+        })?;
+
+        let mut model = ParsedModel::new(models.len() + 1);
+        let mut field_width = None;
+        for _ in 0..num_atoms {
+            let Some((line_index, line)) = lines.next() else {
+                anyhow::bail!(
+                    "The GRO file ends in the middle of frame {}",
+                    models.len() + 1
+                );
+            };
+            let result = match field_width {
+                Some(width) => parse_atom(line, width),
+                None => position_field_width(line).and_then(|width| {
+                    field_width = Some(width);
+                    parse_atom(line, width)
+                }),
+            };
+            let (position, metadata) = result.map_err(|error| {
+                anyhow::anyhow!("Line {} of the GRO file: {}", line_index + 1, error)
+            })?;
+
+            let element = infer_element(&metadata.atom_name, &metadata.residue_name);
+            model
+                .atoms
+                .push(atom_of_element(position, element.as_ref()));
+            model.metadata.push(AtomMetadata {
+                element: element.map(|e| e.symbol().to_string()),
+                ..metadata
+            });
+        }
+        models.push(model);
+
+        // Skip the box vectors and the title of the next frame.
+        lines.next();
+        lines.next();
+    }
+
+    Ok(models)
+}
+
+/// Determines the width of position fields from the distance between the decimal points of the first two.
+fn position_field_width(line: &str) -> anyhow::Result<usize> {
+    let positions = line
+        .get(LINE_POSITION_START..)
+        .ok_or_else(|| anyhow::anyhow!("the line is too short"))?;
+    let first = positions.find('.');
+    let second = first.and_then(|first| Some(first + 1 + positions[first + 1..].find('.')?));
+    match (first, second) {
+        (Some(first), Some(second)) => Ok(second - first),
+        _ => anyhow::bail!("missing positions"),
+    }
+}
+
+/// Parses a line of an atom into its position (in Å) and metadata. The element is filled in by the caller.
+fn parse_atom(line: &str, field_width: usize) -> anyhow::Result<([f32; 3], AtomMetadata)> {
+    let field = |range: Range<usize>| {
+        line.get(range)
+            .map(str::trim)
+            .ok_or_else(|| anyhow::anyhow!("the line is too short"))
+    };
+
+    let mut position = [0.0; 3];
+    for (axis, coordinate) in position.iter_mut().enumerate() {
+        let start = LINE_POSITION_START + axis * field_width;
+        let value = field(start..start + field_width)?;
+        *coordinate = value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid position `{}`", value))?
+            * NANOMETERS_TO_ANGSTROMS;
+    }
+
+    let residue_number = field(LINE_RESIDUE_NUMBER)?;
+    let metadata = AtomMetadata {
+        residue_name: field(LINE_RESIDUE_NAME)?.to_string(),
+        residue_number: residue_number
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid residue number `{}`", residue_number))?,
+        atom_name: field(LINE_ATOM_NAME)?.to_string(),
+        occupancy: 1.0,
+        ..Default::default()
+    };
+    Ok((position, metadata))
+}
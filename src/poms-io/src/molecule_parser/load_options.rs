@@ -0,0 +1,403 @@
+//! Options deciding which of the parsed atoms are loaded, e.g. which alternate locations of atoms are kept
+//! or whether waters are stripped.
+
+use std::collections::HashMap;
+
+use super::ParsedModel;
+use crate::is_water;
+use poms_common::models::atom::AtomMetadata;
+
+/// Residue names of common monoatomic ions.
+const IONS: [&str; 27] = [
+    "LI", "NA", "K", "RB", "CS", "MG", "CA", "SR", "BA", "MN", "FE", "FE2", "CO", "NI", "CU",
+    "CU1", "ZN", "CD", "HG", "AL", "F", "CL", "BR", "IOD", "NH4", "3CO", "YB",
+];
+
+/// Residue names of common crystallization and buffer additives, cryoprotectants and detergents.
+const BUFFER_LIGANDS: [&str; 33] = [
+    "SO4", "PO4", "NO3", "SCN", "ACT", "ACY", "FMT", "CIT", "FLC", "TAR", "MLI", "GOL", "EDO",
+    "PEG", "PGE", "PG4", "1PE", "P6G", "PGO", "MPD", "MRD", "DMS", "TRS", "EPE", "MES", "BME",
+    "IMD", "IPA", "EOH", "MOH", "BU3", "BOG", "LDA",
+];
+
+/// Which alternate locations (alt-locs) of atoms are kept. The alternate location is picked per residue,
+/// so atoms of a single conformer are not mixed. Atoms without an alternate location are always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AltLocPolicy {
+    /// The alternate location with the highest mean occupancy, the first listed one on ties.
+    #[default]
+    HighestOccupancy,
+    /// The alternate location with the given identifier. Residues without it fall back to the highest occupancy.
+    Only(char),
+    /// All alternate locations, which overlap each other.
+    All,
+}
+
+impl AltLocPolicy {
+    pub const ALL: [AltLocPolicy; 6] = [
+        Self::HighestOccupancy,
+        Self::All,
+        Self::Only('A'),
+        Self::Only('B'),
+        Self::Only('C'),
+        Self::Only('D'),
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::HighestOccupancy => "Highest occupancy".to_string(),
+            Self::Only(alt_loc) => format!("Only {}", alt_loc),
+            Self::All => "All".to_string(),
+        }
+    }
+}
+
+/// Options applied to the atoms of each model when a file is loaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadOptions {
+    pub alt_loc_policy: AltLocPolicy,
+    /// Atoms with a lower occupancy are removed, zero keeps all of them.
+    pub min_occupancy: f32,
+    /// Hydrogen (and deuterium) atoms are removed.
+    pub remove_hydrogens: bool,
+    /// Water molecules are removed.
+    pub remove_waters: bool,
+    /// Common monoatomic ions (e.g. sodium, chloride or zinc) are removed.
+    pub remove_ions: bool,
+    /// Common buffer ligands and crystallization additives (e.g. sulfate or glycerol) are removed.
+    pub remove_buffer_ligands: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            alt_loc_policy: AltLocPolicy::default(),
+            min_occupancy: 0.0,
+            remove_hydrogens: false,
+            remove_waters: false,
+            remove_ions: false,
+            remove_buffer_ligands: false,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// Options keeping every atom of the file.
+    pub const KEEP_ALL: Self = Self {
+        alt_loc_policy: AltLocPolicy::All,
+        min_occupancy: 0.0,
+        remove_hydrogens: false,
+        remove_waters: false,
+        remove_ions: false,
+        remove_buffer_ligands: false,
+    };
+
+    /// Short description of the options shown to the user, e.g. `Alt-locs: Highest occupancy, occupancy ≥ 0.50, without waters`.
+    pub fn description(&self) -> String {
+        let mut description = format!("Alt-locs: {}", self.alt_loc_policy.name());
+        if self.min_occupancy > 0.0 {
+            description += &format!(", occupancy ≥ {:.2}", self.min_occupancy);
+        }
+
+        let removed = [
+            (self.remove_hydrogens, "hydrogens"),
+            (self.remove_waters, "waters"),
+            (self.remove_ions, "ions"),
+            (self.remove_buffer_ligands, "buffer ligands"),
+        ]
+        .into_iter()
+        .filter_map(|(is_removed, name)| is_removed.then_some(name))
+        .collect::<Vec<_>>();
+        if !removed.is_empty() {
+            description += &format!(", without {}", removed.join(", "));
+        }
+        description
+    }
+
+    /// Removes the atoms of the model that are excluded by the options.
+    /// Returns whether each of the original atoms is kept, e.g. to select the positions of the kept atoms in trajectory frames.
+    pub(super) fn apply(&self, model: &mut ParsedModel) -> Vec<bool> {
+        if *self == Self::KEEP_ALL {
+            return vec![true; model.atoms.len()];
+        }
+
+        let mask = self.mask(&model.metadata);
+        let mut is_kept = mask.iter();
+        model
+            .atoms
+            .retain(|_| *is_kept.next().expect("one flag per atom"));
+        let mut is_kept = mask.iter();
+        model
+            .metadata
+            .retain(|_| *is_kept.next().expect("one flag per atom"));
+
+        mask
+    }
+
+    /// Returns `true` for each atom that is kept.
+    fn mask(&self, metadata: &[AtomMetadata]) -> Vec<bool> {
+        let alt_locs = match self.alt_loc_policy {
+            AltLocPolicy::All => HashMap::new(),
+            policy => pick_alt_locs(metadata, policy),
+        };
+
+        metadata
+            .iter()
+            .map(|atom| {
+                let is_alt_loc_kept = match (&atom.alt_loc, self.alt_loc_policy) {
+                    (None, _) | (_, AltLocPolicy::All) => true,
+                    (Some(alt_loc), _) => {
+                        alt_locs.get(&residue_key(atom)) == Some(&alt_loc.as_str())
+                    }
+                };
+                let residue_name = atom.residue_name.as_str();
+                let is_removed = (self.remove_hydrogens && is_hydrogen(atom))
+                    || (self.remove_waters && is_water(residue_name))
+                    || (self.remove_ions && IONS.contains(&residue_name))
+                    || (self.remove_buffer_ligands && BUFFER_LIGANDS.contains(&residue_name));
+                is_alt_loc_kept && atom.occupancy >= self.min_occupancy && !is_removed
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` for hydrogen and deuterium atoms. Without the element, it is guessed from the atom name
+/// (e.g. `HA` or `1HB`), unless the atom is a single atom residue, such as a mercury ion (`HG`).
+fn is_hydrogen(atom: &AtomMetadata) -> bool {
+    match &atom.element {
+        Some(element) => element.eq_ignore_ascii_case("H") || element.eq_ignore_ascii_case("D"),
+        None => {
+            atom.atom_name != atom.residue_name
+                && atom
+                    .atom_name
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .starts_with(['H', 'D'])
+        }
+    }
+}
+
+/// Identifies the residue of an atom: the chain, residue number and insertion code.
+/// The residue name is left out, as it may differ between alternate locations.
+type ResidueKey<'a> = (&'a str, isize, Option<&'a str>);
+
+fn residue_key(atom: &AtomMetadata) -> ResidueKey {
+    (
+        &atom.chain_id,
+        atom.residue_number,
+        atom.insertion_code.as_deref(),
+    )
+}
+
+/// Picks the alternate location kept in each residue that has any.
+fn pick_alt_locs(metadata: &[AtomMetadata], policy: AltLocPolicy) -> HashMap<ResidueKey, &str> {
+    // Sum and count of the occupancies of each alternate location, in the order they are listed.
+    let mut occupancies: HashMap<ResidueKey, Vec<(&str, f32, usize)>> = HashMap::new();
+    for atom in metadata {
+        let Some(alt_loc) = atom.alt_loc.as_deref() else {
+            continue;
+        };
+        let residue = occupancies.entry(residue_key(atom)).or_default();
+        match residue.iter_mut().find(|(id, ..)| *id == alt_loc) {
+            Some((_, sum, count)) => {
+                *sum += atom.occupancy;
+                *count += 1;
+            }
+            None => residue.push((alt_loc, atom.occupancy, 1)),
+        }
+    }
+
+    occupancies
+        .into_iter()
+        .map(|(residue, alt_locs)| {
+            let requested = match policy {
+                AltLocPolicy::Only(requested) => alt_locs
+                    .iter()
+                    .find(|(id, ..)| id.chars().eq(std::iter::once(requested))),
+                _ => None,
+            };
+            let mean = |(_, sum, count): &(&str, f32, usize)| sum / *count as f32;
+            let mut highest_occupancy = &alt_locs[0];
+            for alt_loc in &alt_locs[1..] {
+                if mean(alt_loc) > mean(highest_occupancy) {
+                    highest_occupancy = alt_loc;
+                }
+            }
+            let (alt_loc, ..) = requested.unwrap_or(highest_occupancy);
+            (residue, *alt_loc)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An atom of chain `A` in the given residue, with an alternate location and occupancy.
+    fn atom(
+        residue_number: isize,
+        atom_name: &str,
+        alt_loc: Option<&str>,
+        occupancy: f32,
+    ) -> AtomMetadata {
+        AtomMetadata {
+            chain_id: "A".to_string(),
+            residue_name: "SER".to_string(),
+            residue_number,
+            atom_name: atom_name.to_string(),
+            element: Some(atom_name[..1].to_string()),
+            occupancy,
+            alt_loc: alt_loc.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn alt_loc_mask(alt_loc_policy: AltLocPolicy, metadata: &[AtomMetadata]) -> Vec<bool> {
+        LoadOptions {
+            alt_loc_policy,
+            ..LoadOptions::default()
+        }
+        .mask(metadata)
+    }
+
+    #[test]
+    fn ties_keep_the_first_listed_alt_loc() {
+        let metadata = [
+            atom(1, "N", None, 1.0),
+            atom(1, "OG", Some("B"), 0.5),
+            atom(1, "OG", Some("A"), 0.5),
+        ];
+        assert_eq!(
+            alt_loc_mask(AltLocPolicy::HighestOccupancy, &metadata),
+            [true, true, false]
+        );
+    }
+
+    #[test]
+    fn requested_alt_loc_falls_back_per_residue() {
+        let metadata = [
+            atom(1, "OG", Some("A"), 0.6),
+            atom(1, "OG", Some("B"), 0.4),
+            // The residue has no `B`, so its highest occupancy alternate location is kept.
+            atom(2, "OG", Some("A"), 0.3),
+            atom(2, "OG", Some("C"), 0.7),
+            atom(3, "OG", None, 1.0),
+        ];
+        assert_eq!(
+            alt_loc_mask(AltLocPolicy::Only('B'), &metadata),
+            [false, true, false, true, true]
+        );
+        assert_eq!(alt_loc_mask(AltLocPolicy::All, &metadata), [true; 5]);
+    }
+
+    #[test]
+    fn alt_loc_is_picked_per_residue() {
+        // `B` has the higher occupancy at `CB`, but `A` has the higher mean occupancy in the residue.
+        let metadata = [
+            atom(1, "CA", Some("A"), 0.7),
+            atom(1, "CA", Some("B"), 0.3),
+            atom(1, "CB", Some("A"), 0.45),
+            atom(1, "CB", Some("B"), 0.55),
+            atom(2, "CB", Some("A"), 0.45),
+            atom(2, "CB", Some("B"), 0.55),
+        ];
+        assert_eq!(
+            alt_loc_mask(AltLocPolicy::HighestOccupancy, &metadata),
+            [true, false, true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn atoms_below_min_occupancy_are_removed() {
+        let metadata = [
+            atom(1, "N", None, 1.0),
+            atom(1, "CA", None, 0.5),
+            atom(1, "CB", None, 0.49),
+            atom(1, "OG", None, 0.0),
+        ];
+        let options = LoadOptions {
+            min_occupancy: 0.5,
+            ..LoadOptions::KEEP_ALL
+        };
+        assert_eq!(options.mask(&metadata), [true, true, false, false]);
+    }
+
+    /// An atom of the given residue, without an alternate location.
+    fn residue_atom(residue_name: &str, atom_name: &str, element: Option<&str>) -> AtomMetadata {
+        AtomMetadata {
+            residue_name: residue_name.to_string(),
+            atom_name: atom_name.to_string(),
+            element: element.map(str::to_string),
+            occupancy: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn strips_waters_ions_and_buffer_ligands() {
+        let metadata = ["ALA", "HOH", "WAT", "NA", "ZN", "SO4", "GOL", "HEM"]
+            .map(|residue_name| residue_atom(residue_name, "X", None));
+        let removed = |set: fn(&mut LoadOptions)| {
+            let mut options = LoadOptions::KEEP_ALL;
+            set(&mut options);
+            options
+                .mask(&metadata)
+                .iter()
+                .zip(&metadata)
+                .filter(|(is_kept, _)| !**is_kept)
+                .map(|(_, atom)| atom.residue_name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert!(removed(|_| ()).is_empty());
+        assert_eq!(removed(|o| o.remove_waters = true), ["HOH", "WAT"]);
+        assert_eq!(removed(|o| o.remove_ions = true), ["NA", "ZN"]);
+        assert_eq!(removed(|o| o.remove_buffer_ligands = true), ["SO4", "GOL"]);
+    }
+
+    #[test]
+    fn hydrogens_are_guessed_from_atom_names_without_elements() {
+        let metadata = [
+            residue_atom("ALA", "1HB", None),
+            residue_atom("ALA", "HA", None),
+            residue_atom("ALA", "D", None),
+            residue_atom("ALA", "CA", None),
+            residue_atom("ARG", "NH1", None),
+            // A mercury ion, not a hydrogen named `HG`.
+            residue_atom("HG", "HG", None),
+            residue_atom("SER", "HG", Some("H")),
+            residue_atom("HG", "HG", Some("HG")),
+        ];
+        let options = LoadOptions {
+            remove_hydrogens: true,
+            ..LoadOptions::KEEP_ALL
+        };
+        assert_eq!(
+            options.mask(&metadata),
+            [false, false, false, true, true, true, false, true]
+        );
+    }
+
+    #[test]
+    fn atoms_are_removed_before_the_atom_limit_is_checked() {
+        use poms_common::limits::MAX_NUM_ATOMS;
+
+        use crate::molecule_parser::parse_atoms_from_pdb_file;
+        use crate::RawFile;
+
+        // A carbon atom surrounded by more hydrogen atoms than can be loaded.
+        let num_atoms = MAX_NUM_ATOMS + 1;
+        let mut content = format!("{}\ncomment\nC 0.0 0.0 0.0\n", num_atoms);
+        content.push_str(&"H 0.0 0.0 0.0\n".repeat(num_atoms - 1));
+        let file = || RawFile {
+            name: "hydrogens.xyz".to_string(),
+            content: content.as_bytes().to_vec(),
+        };
+
+        assert!(parse_atoms_from_pdb_file(file(), LoadOptions::KEEP_ALL).is_err());
+        let options = LoadOptions {
+            remove_hydrogens: true,
+            ..LoadOptions::KEEP_ALL
+        };
+        let molecules = parse_atoms_from_pdb_file(file(), options).unwrap();
+        assert_eq!(molecules[0].atoms.len(), 1);
+    }
+}
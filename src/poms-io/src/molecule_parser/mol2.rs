@@ -0,0 +1,111 @@
+//! Reader of Tripos MOL2 files. Only the `MOLECULE` and `ATOM` records are read, bonds and other records are ignored.
+//! Each atom line has the atom id, name, position in Å, SYBYL atom type and optionally its substructure and partial charge.
+
+use super::{atom_of_element, Atom, AtomMetadata, ParsedModel};
+
+const RECORD_PREFIX: &str = "@<TRIPOS>";
+
+/// Parses all molecules of a MOL2 file, each becoming a separate model.
+pub(super) fn try_parse_mol2(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    let content = std::str::from_utf8(content)?;
+
+    let mut record = "";
+    // Number of lines read in the current record, needed to find the charge type in the `MOLECULE` record.
+    let mut record_line = 0;
+    let mut has_charges = true;
+
+    for (line_index, line) in content.lines().enumerate() {
+        if let Some(name) = line.trim().strip_prefix(RECORD_PREFIX) {
+            record = name;
+            record_line = 0;
+            if record == "MOLECULE" {
+                models.push(ParsedModel::new(models.len() + 1));
+                has_charges = true;
+            }
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        record_line += 1;
+
+        match record {
+            // The fourth line of the molecule record (after name, counts and type) is the type of charges.
+            "MOLECULE" if record_line == 4 => has_charges = line.trim() != "NO_CHARGES",
+            "ATOM" => {
+                let Some(model) = models.last_mut() else {
+                    anyhow::bail!(
+                        "Line {} of the MOL2 file: atom outside of a molecule",
+                        line_index + 1
+                    );
+                };
+                let parsed = parse_atom(line, has_charges).map_err(|error| {
+                    anyhow::anyhow!("Line {} of the MOL2 file: {}", line_index + 1, error)
+                })?;
+                if let Some((atom, metadata)) = parsed {
+                    model.atoms.push(atom);
+                    model.metadata.push(metadata);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(models)
+}
+
+/// Parses a line of an atom. Lone pairs are not atoms, so `None` is returned for them.
+fn parse_atom(line: &str, has_charges: bool) -> anyhow::Result<Option<(Atom, AtomMetadata)>> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    if fields.len() < 6 {
+        anyhow::bail!("expected at least 6 fields, found {}", fields.len());
+    }
+
+    // The element is the part of the SYBYL atom type before the dot (e.g. `C` of `C.ar`).
+    let atom_type = fields[5];
+    let symbol = atom_type.split('.').next().unwrap_or_default();
+    if symbol.eq_ignore_ascii_case("LP") {
+        return Ok(None);
+    }
+
+    let parse = |name: &str, value: &str| {
+        value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid {} `{}`", name, value))
+    };
+    let position = [
+        parse("x", fields[2])?,
+        parse("y", fields[3])?,
+        parse("z", fields[4])?,
+    ];
+    let charge = match (has_charges, fields.get(8)) {
+        (true, Some(charge)) => Some(parse("charge", charge)?),
+        _ => None,
+    };
+
+    // Substructures of proteins are residues, named by the residue name followed by its number (e.g. `ALA12`).
+    let residue_name = fields
+        .get(7)
+        .map(|name| name.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .unwrap_or_default();
+    let residue_number = fields
+        .get(6)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or_default();
+
+    let element = pdbtbx::Element::try_from(symbol).ok();
+    let metadata = AtomMetadata {
+        residue_name: residue_name.to_string(),
+        residue_number,
+        atom_name: fields[1].to_string(),
+        element: element.map(|e| e.symbol().to_string()),
+        occupancy: 1.0,
+        charge,
+        ..Default::default()
+    };
+    Ok(Some((
+        atom_of_element(position, element.as_ref()),
+        metadata,
+    )))
+}
@@ -0,0 +1,98 @@
+//! Reader of PQR files (e.g. written by PDB2PQR), which replace the occupancy and B-factor of PDB records
+//! with the partial charge and radius of each atom. Unlike in PDB, the fields are separated by whitespace.
+
+use super::{get_jmol_color, infer_element, Atom, AtomMetadata, ParsedModel, SecondaryStructure};
+
+/// Parses the `ATOM` and `HETATM` records of a PQR file, splitting them into models by the `MODEL` records.
+pub(super) fn try_parse_pqr(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    let mut model = ParsedModel::new(1);
+    let content = std::str::from_utf8(content)?;
+
+    for (line_index, line) in content.lines().enumerate() {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        match fields.first() {
+            Some(&"MODEL") => {
+                let serial_number = fields
+                    .get(1)
+                    .and_then(|serial| serial.parse().ok())
+                    .unwrap_or(models.len() + 1);
+                let previous = std::mem::replace(&mut model, ParsedModel::new(serial_number));
+                if !previous.atoms.is_empty() {
+                    models.push(previous);
+                }
+            }
+            Some(&"ATOM") | Some(&"HETATM") => {
+                let (atom, metadata) = parse_atom(&fields).map_err(|error| {
+                    anyhow::anyhow!("Line {} of the PQR file: {}", line_index + 1, error)
+                })?;
+                model.atoms.push(atom);
+                model.metadata.push(metadata);
+            }
+            _ => {}
+        }
+    }
+    if !model.atoms.is_empty() {
+        models.push(model);
+    }
+
+    match models.len() {
+        0 => anyhow::bail!("No atoms found in file"),
+        _ => Ok(models),
+    }
+}
+
+/// Parses the fields of a record: record name, serial number, atom name, residue name, chain (optional),
+/// residue number, coordinates, charge and radius.
+fn parse_atom(fields: &[&str]) -> anyhow::Result<(Atom, AtomMetadata)> {
+    let (chain_id, rest) = match fields.len() {
+        11 => (fields[4], &fields[5..]),
+        10 => ("", &fields[4..]),
+        count => anyhow::bail!("expected 10 or 11 fields, found {}", count),
+    };
+    let [residue_number, x, y, z, charge, radius]: [&str; 6] = rest.try_into()?;
+
+    let parse = |name: &str, value: &str| {
+        value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid {} `{}`", name, value))
+    };
+    let position = [parse("x", x)?, parse("y", y)?, parse("z", z)?];
+    let charge = parse("charge", charge)?;
+    let radius = parse("radius", radius)?;
+
+    // The residue number may be followed by an insertion code (e.g. `52A`).
+    let number_length = residue_number
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+        .map_or(residue_number.len(), |(i, _)| i);
+    let (residue_number, insertion_code) = residue_number.split_at(number_length);
+
+    let atom_name = fields[2];
+    let residue_name = fields[3];
+    let element = infer_element(atom_name, residue_name);
+
+    let atom = Atom {
+        position,
+        radius,
+        color: get_jmol_color(element.as_ref()),
+    };
+    let metadata = AtomMetadata {
+        chain_id: chain_id.to_string(),
+        label_asym_id: None,
+        residue_name: residue_name.to_string(),
+        residue_number: residue_number
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid residue number `{}`", residue_number))?,
+        insertion_code: (!insertion_code.is_empty()).then(|| insertion_code.to_string()),
+        atom_name: atom_name.to_string(),
+        element: element.map(|e| e.symbol().to_string()),
+        b_factor: 0.0,
+        occupancy: 1.0,
+        alt_loc: None,
+        hetero: fields[0] == "HETATM",
+        charge: Some(charge),
+        secondary_structure: SecondaryStructure::default(),
+    };
+    Ok((atom, metadata))
+}
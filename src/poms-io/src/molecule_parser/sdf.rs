@@ -0,0 +1,232 @@
+//! Reader of MDL MOL files and SD files, which are MOL records separated by `$$$$` lines.
+//! Both the fixed-column V2000 and the free-format V3000 connection tables are supported. Bonds and data items are ignored.
+
+use std::ops::Range;
+
+use super::{atom_of_element, Atom, AtomMetadata, ParsedModel};
+
+const RECORD_SEPARATOR: &str = "$$$$";
+/// The counts line follows the header, which consists of the name, program and comment lines.
+const COUNTS_LINE: usize = 3;
+const COUNTS_NUM_ATOMS: Range<usize> = 0..3;
+const LINE_POSITION_X: Range<usize> = 0..10;
+const LINE_POSITION_Y: Range<usize> = 10..20;
+const LINE_POSITION_Z: Range<usize> = 20..30;
+const LINE_ELEMENT_SYMBOL: Range<usize> = 31..34;
+const LINE_CHARGE: Range<usize> = 36..39;
+
+/// Parses all records of an SD (or a single MOL) file, each becoming a separate model.
+pub(super) fn try_parse_sdf(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    let lines = std::str::from_utf8(content)?.lines().collect::<Vec<_>>();
+
+    let mut record_start = 0;
+    for line_index in 0..=lines.len() {
+        let is_record_end = lines
+            .get(line_index)
+            .map_or(true, |line| line.starts_with(RECORD_SEPARATOR));
+        if !is_record_end {
+            continue;
+        }
+
+        let record = &lines[record_start..line_index];
+        if record.iter().any(|line| !line.trim().is_empty()) {
+            let (atoms, metadata) = parse_record(record).map_err(|(line, error)| {
+                anyhow::anyhow!("Line {} of the SD file: {}", record_start + line + 1, error)
+            })?;
+            let mut model = ParsedModel::new(models.len() + 1);
+            model.atoms = atoms;
+            model.metadata = metadata;
+            models.push(model);
+        }
+        record_start = line_index + 1;
+    }
+
+    Ok(models)
+}
+
+/// Errors are reported with the index of the line within the record they occurred on.
+type RecordResult<T> = Result<T, (usize, anyhow::Error)>;
+
+/// Parses the atoms of a single MOL record.
+fn parse_record(lines: &[&str]) -> RecordResult<(Vec<Atom>, Vec<AtomMetadata>)> {
+    let Some(counts) = lines.get(COUNTS_LINE) else {
+        return Err((
+            lines.len(),
+            anyhow::anyhow!("the record ends before the counts line"),
+        ));
+    };
+    if counts.contains("V3000") {
+        return parse_v3000_atoms(lines);
+    }
+
+    let num_atoms = counts
+        .get(COUNTS_NUM_ATOMS)
+        .and_then(|count| count.trim().parse::<usize>().ok())
+        .ok_or_else(|| (COUNTS_LINE, anyhow::anyhow!("invalid number of atoms")))?;
+
+    let mut symbols = Vec::with_capacity(num_atoms);
+    let mut positions = Vec::with_capacity(num_atoms);
+    let mut charges = Vec::with_capacity(num_atoms);
+    for line_index in COUNTS_LINE + 1..COUNTS_LINE + 1 + num_atoms {
+        let Some(line) = lines.get(line_index) else {
+            return Err((
+                line_index,
+                anyhow::anyhow!("the record ends before all atoms"),
+            ));
+        };
+        let (symbol, position, charge) =
+            parse_v2000_atom(line).map_err(|error| (line_index, error))?;
+        symbols.push(symbol);
+        positions.push(position);
+        charges.push(charge);
+    }
+
+    // Charges from the properties block replace all charges of the atom block.
+    let properties = lines[COUNTS_LINE + 1 + num_atoms..]
+        .iter()
+        .enumerate()
+        .take_while(|(_, line)| !line.starts_with("M  END"));
+    let mut has_charge_properties = false;
+    for (offset, line) in properties.filter(|(_, line)| line.starts_with("M  CHG")) {
+        if !has_charge_properties {
+            charges.fill(0.0);
+            has_charge_properties = true;
+        }
+        parse_charge_property(line, &mut charges)
+            .map_err(|error| (COUNTS_LINE + 1 + num_atoms + offset, error))?;
+    }
+
+    Ok(create_atoms(&symbols, positions, &charges))
+}
+
+/// Parses the element symbol, position and charge of an atom line of a V2000 connection table.
+fn parse_v2000_atom(line: &str) -> anyhow::Result<(&str, [f32; 3], f32)> {
+    let field = |range: Range<usize>| {
+        line.get(range)
+            .map(str::trim)
+            .ok_or_else(|| anyhow::anyhow!("the line is too short"))
+    };
+    let parse = |range: Range<usize>| {
+        let value = field(range)?;
+        value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid position `{}`", value))
+    };
+    let position = [
+        parse(LINE_POSITION_X)?,
+        parse(LINE_POSITION_Y)?,
+        parse(LINE_POSITION_Z)?,
+    ];
+
+    // The charge is encoded: 1, 2 and 3 stand for +3, +2 and +1; 5, 6 and 7 for -1, -2 and -3.
+    let charge = match field(LINE_CHARGE).unwrap_or_default() {
+        code @ ("1" | "2" | "3" | "5" | "6" | "7") => 4.0 - code.parse::<f32>()?,
+        _ => 0.0,
+    };
+    Ok((field(LINE_ELEMENT_SYMBOL)?, position, charge))
+}
+
+/// Parses a `M  CHG` line, which lists the number of entries followed by pairs of atom numbers and their charges.
+fn parse_charge_property(line: &str, charges: &mut [f32]) -> anyhow::Result<()> {
+    let values = line
+        .split_whitespace()
+        .skip(3)
+        .map(|value| {
+            value
+                .parse::<i32>()
+                .map_err(|_| anyhow::anyhow!("invalid charge entry `{}`", value))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for pair in values.chunks_exact(2) {
+        let charge = usize::try_from(pair[0] - 1)
+            .ok()
+            .and_then(|index| charges.get_mut(index))
+            .ok_or_else(|| anyhow::anyhow!("invalid atom number {}", pair[0]))?;
+        *charge = pair[1] as f32;
+    }
+    Ok(())
+}
+
+/// Parses the atom block of a V3000 connection table. Its lines start with `M  V30` and may be continued
+/// on the next line if they end with `-`. Each atom has an index, type, position, mapping and optional properties.
+fn parse_v3000_atoms(lines: &[&str]) -> RecordResult<(Vec<Atom>, Vec<AtomMetadata>)> {
+    let mut symbols = Vec::new();
+    let mut positions = Vec::new();
+    let mut charges = Vec::new();
+
+    let mut is_atom_block = false;
+    let mut entry = String::new();
+    for (line_index, line) in lines.iter().enumerate() {
+        let Some(content) = line.strip_prefix("M  V30 ") else {
+            continue;
+        };
+        if let Some(continued) = content.strip_suffix('-') {
+            entry.push_str(continued);
+            continue;
+        }
+        entry.push_str(content);
+
+        match entry.trim() {
+            "BEGIN ATOM" => is_atom_block = true,
+            "END ATOM" => break,
+            atom if is_atom_block => {
+                let (symbol, position, charge) =
+                    parse_v3000_atom(atom).map_err(|error| (line_index, error))?;
+                symbols.push(symbol.to_string());
+                positions.push(position);
+                charges.push(charge);
+            }
+            _ => {}
+        }
+        entry.clear();
+    }
+
+    let symbols = symbols.iter().map(String::as_str).collect::<Vec<_>>();
+    Ok(create_atoms(&symbols, positions, &charges))
+}
+
+/// Parses the element symbol, position and charge of an atom entry of a V3000 connection table.
+fn parse_v3000_atom(entry: &str) -> anyhow::Result<(&str, [f32; 3], f32)> {
+    let fields = entry.split_whitespace().collect::<Vec<_>>();
+    if fields.len() < 5 {
+        anyhow::bail!("expected at least 5 fields, found {}", fields.len());
+    }
+
+    let parse = |value: &str| {
+        value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid value `{}`", value))
+    };
+    let position = [parse(fields[2])?, parse(fields[3])?, parse(fields[4])?];
+    let charge = match fields.iter().find_map(|field| field.strip_prefix("CHG=")) {
+        Some(charge) => parse(charge)?,
+        None => 0.0,
+    };
+    Ok((fields[1], position, charge))
+}
+
+/// Creates atoms and their metadata. MOL records have no atom names, so atoms are named by their element.
+fn create_atoms(
+    symbols: &[&str],
+    positions: Vec<[f32; 3]>,
+    charges: &[f32],
+) -> (Vec<Atom>, Vec<AtomMetadata>) {
+    symbols
+        .iter()
+        .zip(positions)
+        .zip(charges)
+        .map(|((symbol, position), &charge)| {
+            let element = pdbtbx::Element::try_from(*symbol).ok();
+            let metadata = AtomMetadata {
+                atom_name: symbol.to_string(),
+                element: element.as_ref().map(|e| e.symbol().to_string()),
+                occupancy: 1.0,
+                charge: Some(charge),
+                ..Default::default()
+            };
+            (atom_of_element(position, element.as_ref()), metadata)
+        })
+        .unzip()
+}
@@ -0,0 +1,246 @@
+//! Fallback reader of PDB files rejected by pdbtbx, e.g. files without a header written by hand or by other tools.
+//! Such files often trim the trailing columns of records or leave out the element column, so every column
+//! past the coordinates is optional.
+
+use std::ops::Range;
+
+use super::{atom_of_element, infer_element, AtomMetadata, ParsedModel, SecondaryStructure};
+
+const RECORD_NAME: Range<usize> = 0..6;
+const MODEL_SERIAL_NUMBER: Range<usize> = 10..14;
+const ATOM_NAME: Range<usize> = 12..16;
+const ALT_LOC: Range<usize> = 16..17;
+const RESIDUE_NAME: Range<usize> = 17..20;
+const CHAIN_ID: Range<usize> = 21..22;
+const RESIDUE_NUMBER: Range<usize> = 22..26;
+const INSERTION_CODE: Range<usize> = 26..27;
+const POSITION_X: Range<usize> = 30..38;
+const POSITION_Y: Range<usize> = 38..46;
+const POSITION_Z: Range<usize> = 46..54;
+const OCCUPANCY: Range<usize> = 54..60;
+const B_FACTOR: Range<usize> = 60..66;
+const ELEMENT_SYMBOL: Range<usize> = 76..78;
+
+/// Errors are reported with the columns of the line they occurred in.
+type ColumnResult<T> = Result<T, (Range<usize>, anyhow::Error)>;
+
+/// Parses the `ATOM` and `HETATM` records of a PDB file, splitting them into models by the `MODEL` and `ENDMDL` records.
+/// Records after `END` are ignored.
+pub(super) fn try_parse_pdb(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    let mut model = ParsedModel::new(1);
+    let content = std::str::from_utf8(content)?;
+
+    for (line_index, line) in content.lines().enumerate() {
+        match columns(line, RECORD_NAME).trim_end() {
+            "ATOM" | "HETATM" => {
+                let (position, metadata) = parse_atom(line).map_err(|(columns, error)| {
+                    anyhow::anyhow!(
+                        "Line {}, columns {}-{} of the PDB file: {}",
+                        line_index + 1,
+                        columns.start + 1,
+                        columns.end,
+                        error
+                    )
+                })?;
+                let element = metadata
+                    .element
+                    .as_deref()
+                    .and_then(|symbol| pdbtbx::Element::try_from(symbol).ok());
+                model
+                    .atoms
+                    .push(atom_of_element(position, element.as_ref()));
+                model.metadata.push(metadata);
+            }
+            "MODEL" => {
+                let serial_number = columns(line, MODEL_SERIAL_NUMBER)
+                    .trim()
+                    .parse()
+                    .unwrap_or(models.len() + 1);
+                let previous = std::mem::replace(&mut model, ParsedModel::new(serial_number));
+                if !previous.atoms.is_empty() {
+                    models.push(previous);
+                }
+            }
+            "ENDMDL" => {
+                let next = ParsedModel::new(model.serial_number + 1);
+                let previous = std::mem::replace(&mut model, next);
+                if !previous.atoms.is_empty() {
+                    models.push(previous);
+                }
+            }
+            "END" => break,
+            // Chains are told apart by their identifiers, so `TER` records need no handling.
+            _ => {}
+        }
+    }
+    if !model.atoms.is_empty() {
+        models.push(model);
+    }
+
+    match models.len() {
+        0 => anyhow::bail!("No atoms found in file"),
+        _ => Ok(models),
+    }
+}
+
+/// Parses the position and metadata of an `ATOM` or `HETATM` record.
+fn parse_atom(line: &str) -> ColumnResult<([f32; 3], AtomMetadata)> {
+    let position = [
+        parse_coordinate(line, POSITION_X, "x")?,
+        parse_coordinate(line, POSITION_Y, "y")?,
+        parse_coordinate(line, POSITION_Z, "z")?,
+    ];
+
+    // Unlike the position, the metadata is not essential, so missing or malformed fields fall back to defaults.
+    let parse_or = |range: Range<usize>, default: f32| {
+        columns(line, range)
+            .trim()
+            .parse::<f32>()
+            .unwrap_or(default)
+    };
+    let hetero = columns(line, RECORD_NAME).starts_with("HETATM");
+    let residue_name = columns(line, RESIDUE_NAME).trim();
+    let atom_name = columns(line, ATOM_NAME);
+    let element = pdbtbx::Element::try_from(columns(line, ELEMENT_SYMBOL).trim())
+        .ok()
+        .or_else(|| infer_element_from_name(atom_name, residue_name, hetero));
+
+    let metadata = AtomMetadata {
+        chain_id: columns(line, CHAIN_ID).trim().to_string(),
+        label_asym_id: None,
+        residue_name: residue_name.to_string(),
+        residue_number: columns(line, RESIDUE_NUMBER)
+            .trim()
+            .parse()
+            .unwrap_or_default(),
+        insertion_code: optional_field(line, INSERTION_CODE),
+        atom_name: atom_name.trim().to_string(),
+        element: element.map(|e| e.symbol().to_string()),
+        b_factor: parse_or(B_FACTOR, 0.0),
+        occupancy: parse_or(OCCUPANCY, 1.0),
+        alt_loc: optional_field(line, ALT_LOC),
+        hetero,
+        charge: None,
+        secondary_structure: SecondaryStructure::default(),
+    };
+    Ok((position, metadata))
+}
+
+fn parse_coordinate(line: &str, range: Range<usize>, axis: &str) -> ColumnResult<f32> {
+    let value = columns(line, range.clone()).trim();
+    if value.is_empty() {
+        return Err((range, anyhow::anyhow!("missing {} coordinate", axis)));
+    }
+    value.parse::<f32>().map_err(|_| {
+        (
+            range,
+            anyhow::anyhow!("invalid {} coordinate `{}`", axis, value),
+        )
+    })
+}
+
+/// Infers the element from the atom name columns. Element symbols are right-justified in columns 13-14,
+/// so single-letter elements start in column 14 (` CA ` is an alpha carbon) and two-letter ones in column 13 (`FE  `).
+/// Four-character hydrogen names (e.g. `HD21`) and names of files ignoring the alignment start in column 13 as well,
+/// so two-letter elements are only read from column 13 of `HETATM` records and never for names starting with `H`.
+fn infer_element_from_name(
+    atom_name: &str,
+    residue_name: &str,
+    hetero: bool,
+) -> Option<pdbtbx::Element> {
+    let starts_in_column_13 = atom_name.starts_with(|c: char| c.is_ascii_alphabetic());
+    if hetero && starts_in_column_13 && !atom_name.starts_with('H') {
+        let two_letter = atom_name
+            .get(..2)
+            .and_then(|symbol| pdbtbx::Element::try_from(symbol).ok());
+        if two_letter.is_some() {
+            return two_letter;
+        }
+    }
+    infer_element(atom_name.trim(), residue_name)
+}
+
+/// Returns the given columns of the line, cut short (possibly to an empty string) if the line ends before them.
+fn columns(line: &str, range: Range<usize>) -> &str {
+    let end = range.end.min(line.len());
+    line.get(range.start.min(end)..end).unwrap_or_default()
+}
+
+/// Returns the trimmed field, or `None` if it is blank or missing.
+fn optional_field(line: &str, range: Range<usize>) -> Option<String> {
+    let field = columns(line, range).trim();
+    (!field.is_empty()).then(|| field.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_records_shorter_than_the_element_column() {
+        let content = "ATOM      1  CA  ALA A   1      11.104   6.134  -6.504\n";
+        let models = try_parse_pdb(content.as_bytes()).unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].atoms[0].position, [11.104, 6.134, -6.504]);
+
+        let atom = &models[0].metadata[0];
+        assert_eq!(
+            (
+                atom.chain_id.as_str(),
+                atom.residue_number,
+                atom.atom_name.as_str()
+            ),
+            ("A", 1, "CA")
+        );
+        assert_eq!((atom.occupancy, atom.b_factor), (1.0, 0.0));
+        assert!(!atom.hetero);
+    }
+
+    #[test]
+    fn reads_hetatm_records() {
+        let content = "\
+ATOM      1  CA  ALA A   1      11.104   6.134  -6.504
+HETATM    2 FE   HEM A 201       1.000   2.000   3.000  1.00 10.00
+";
+        let models = try_parse_pdb(content.as_bytes()).unwrap();
+        let atom = &models[0].metadata[1];
+        assert_eq!(models[0].atoms[1].position, [1.0, 2.0, 3.0]);
+        assert_eq!(atom.residue_name, "HEM");
+        assert_eq!(atom.b_factor, 10.0);
+        assert!(atom.hetero);
+    }
+
+    #[test]
+    fn infers_missing_elements_from_atom_names() {
+        let content = "\
+ATOM      1  CA  ALA A   1      11.104   6.134  -6.504
+HETATM    2 FE   HEM A 201       1.000   2.000   3.000  1.00 10.00
+HETATM    3 CA    CA A 301       4.000   5.000   6.000  0.50
+ATOM      4 HD21 ASN A   2       7.000   8.000   9.000
+";
+        let models = try_parse_pdb(content.as_bytes()).unwrap();
+        let elements = models[0]
+            .metadata
+            .iter()
+            .map(|atom| atom.element.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(elements, [Some("C"), Some("Fe"), Some("Ca"), Some("H")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_invalid_coordinates() {
+        let content = "\
+ATOM      1  CA  ALA A   1      11.104   6.134  -6.504
+TER
+ATOM      3  N   ALA A   1      11.1x4   6.134  -6.504  1.00  0.00           N
+";
+        let Err(error) = try_parse_pdb(content.as_bytes()) else {
+            panic!("the invalid coordinate is not reported");
+        };
+        assert_eq!(
+            error.to_string(),
+            "Line 3, columns 31-38 of the PDB file: invalid x coordinate `11.1x4`"
+        );
+    }
+}
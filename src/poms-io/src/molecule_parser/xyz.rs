@@ -0,0 +1,66 @@
+//! Reader of XYZ files. Each frame has the number of atoms, a comment line and one line per atom
+//! with its element and position in Å. Any further columns (e.g. of extended XYZ) are ignored.
+
+use super::{atom_of_element, AtomMetadata, ParsedModel};
+
+/// Parses all frames of an XYZ file, each becoming a separate model.
+pub(super) fn try_parse_xyz(content: &[u8]) -> anyhow::Result<Vec<ParsedModel>> {
+    let mut models: Vec<ParsedModel> = vec![];
+    // Trailing empty lines would otherwise be read as the start of another frame.
+    let content = std::str::from_utf8(content)?.trim_end();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((line_index, line)) = lines.next() {
+        let num_atoms = line.trim().parse::<usize>().map_err(|_| {
+            anyhow::anyhow!(
+                "Line {} of the XYZ file: invalid number of atoms `{}`",
+                line_index + 1,
+                line.trim()
+            )
+        })?;
+        // Skip the comment line.
+        lines.next();
+
+        let mut model = ParsedModel::new(models.len() + 1);
+        for _ in 0..num_atoms {
+            let Some((line_index, line)) = lines.next() else {
+                anyhow::bail!(
+                    "The XYZ file ends in the middle of frame {}",
+                    models.len() + 1
+                );
+            };
+            let (symbol, position) = parse_atom(line).map_err(|error| {
+                anyhow::anyhow!("Line {} of the XYZ file: {}", line_index + 1, error)
+            })?;
+
+            let element = pdbtbx::Element::try_from(symbol).ok();
+            model
+                .atoms
+                .push(atom_of_element(position, element.as_ref()));
+            model.metadata.push(AtomMetadata {
+                atom_name: symbol.to_string(),
+                element: element.map(|e| e.symbol().to_string()),
+                occupancy: 1.0,
+                ..Default::default()
+            });
+        }
+        models.push(model);
+    }
+
+    Ok(models)
+}
+
+/// Parses the element symbol and the position of an atom.
+fn parse_atom(line: &str) -> anyhow::Result<(&str, [f32; 3])> {
+    let fields = line.split_whitespace().take(4).collect::<Vec<_>>();
+    let [symbol, x, y, z]: [&str; 4] = fields.try_into().map_err(|fields: Vec<&str>| {
+        anyhow::anyhow!("expected 4 fields, found {}", fields.len())
+    })?;
+
+    let parse = |value: &str| {
+        value
+            .parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("invalid position `{}`", value))
+    };
+    Ok((symbol, [parse(x)?, parse(y)?, parse(z)?]))
+}
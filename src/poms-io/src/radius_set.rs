@@ -0,0 +1,289 @@
+//! Sets of atomic radii used for the spacefill representation and the molecular surface.
+//!
+//! Besides element based sets, united-atom sets assign radii by the atom type within standard amino acids,
+//! with the radii of heavy atoms enlarged to include their implicit hydrogens.
+
+use poms_common::models::atom::AtomMetadata;
+
+/// Determines the radius of each atom.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RadiusSet {
+    /// Radii given in the file (e.g. PQR), otherwise the van der Waals radii of elements.
+    #[default]
+    Default,
+    /// Van der Waals radii of elements by Bondi (1964).
+    Bondi,
+    /// Van der Waals radii of elements by Alvarez (2013).
+    Alvarez,
+    /// United-atom radii by Richards (1974).
+    Richards,
+    /// United-atom radii by Tsai et al. (1999).
+    ProtOr,
+    /// United-atom radii by Chothia (1976), as used by NACCESS.
+    UnitedAtom,
+}
+
+impl RadiusSet {
+    /// All radius sets, in the order they are offered to the user.
+    pub const ALL: [RadiusSet; 6] = [
+        Self::Default,
+        Self::Bondi,
+        Self::Alvarez,
+        Self::Richards,
+        Self::ProtOr,
+        Self::UnitedAtom,
+    ];
+
+    /// Human-readable name of the set.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::Bondi => "Bondi",
+            Self::Alvarez => "Alvarez",
+            Self::Richards => "Richards",
+            Self::ProtOr => "ProtOr",
+            Self::UnitedAtom => "United atom",
+        }
+    }
+
+    /// Computes the radius of each atom described by `metadata`.
+    /// `default_radii` are the radii assigned when the file was parsed, used by the default set and for atoms the set does not cover.
+    pub fn radii(&self, metadata: &[AtomMetadata], default_radii: &[f32]) -> Vec<f32> {
+        metadata
+            .iter()
+            .zip(default_radii)
+            .map(|(atom, &default_radius)| self.radius(atom).unwrap_or(default_radius))
+            .collect()
+    }
+
+    fn radius(&self, atom: &AtomMetadata) -> Option<f32> {
+        let element = atom.element.as_deref();
+        match self {
+            Self::Default => None,
+            Self::Bondi => element.and_then(bondi_radius),
+            Self::Alvarez => element.and_then(alvarez_radius),
+            Self::Richards | Self::ProtOr | Self::UnitedAtom => {
+                // Hydrogens are already included in the radii of the heavy atoms they are bonded to.
+                if element == Some("H") || element == Some("D") {
+                    return Some(0.0);
+                }
+                match atom_type(&atom.residue_name, &atom.atom_name) {
+                    Some(atom_type) => Some(self.united_atom_radius(atom_type)),
+                    // Atoms outside of standard amino acids fall back to the radii of their elements.
+                    None => element.and_then(bondi_radius),
+                }
+            }
+        }
+    }
+
+    fn united_atom_radius(&self, atom_type: AtomType) -> f32 {
+        use AtomType::*;
+        match self {
+            Self::Richards => match atom_type {
+                C3H0 => 1.7,
+                C3H1 => 1.85,
+                C4H1 | C4H2 | C4H3 => 2.0,
+                N3H0 | N3H1 | N3H2 | N4H3 => 1.5,
+                O1H0 | O2H1 => 1.4,
+                S2H0 | S2H1 => 1.8,
+            },
+            Self::ProtOr => match atom_type {
+                C3H0 => 1.61,
+                C3H1 => 1.76,
+                C4H1 | C4H2 | C4H3 => 1.88,
+                N3H0 | N3H1 | N3H2 | N4H3 => 1.64,
+                O1H0 => 1.42,
+                O2H1 => 1.46,
+                S2H0 | S2H1 => 1.77,
+            },
+            _ => match atom_type {
+                C3H0 | C3H1 => 1.76,
+                C4H1 | C4H2 | C4H3 => 1.87,
+                N3H0 | N3H1 | N3H2 | N4H3 => 1.65,
+                O1H0 | O2H1 => 1.4,
+                S2H0 | S2H1 => 1.85,
+            },
+        }
+    }
+}
+
+fn bondi_radius(element: &str) -> Option<f32> {
+    let radius = match element {
+        "H" | "D" => 1.2,
+        "He" => 1.4,
+        "Li" => 1.82,
+        "C" => 1.7,
+        "N" => 1.55,
+        "O" => 1.52,
+        "F" => 1.47,
+        "Ne" => 1.54,
+        "Na" => 2.27,
+        "Mg" => 1.73,
+        "Si" => 2.1,
+        "P" => 1.8,
+        "S" => 1.8,
+        "Cl" => 1.75,
+        "Ar" => 1.88,
+        "K" => 2.75,
+        "Ni" => 1.63,
+        "Cu" => 1.4,
+        "Zn" => 1.39,
+        "Ga" => 1.87,
+        "As" => 1.85,
+        "Se" => 1.9,
+        "Br" => 1.85,
+        "Kr" => 2.02,
+        "Pd" => 1.63,
+        "Ag" => 1.72,
+        "Cd" => 1.58,
+        "In" => 1.93,
+        "Sn" => 2.17,
+        "Te" => 2.06,
+        "I" => 1.98,
+        "Xe" => 2.16,
+        "Pt" => 1.72,
+        "Au" => 1.66,
+        "Hg" => 1.55,
+        "Tl" => 1.96,
+        "Pb" => 2.02,
+        "U" => 1.86,
+        _ => return None,
+    };
+    Some(radius)
+}
+
+fn alvarez_radius(element: &str) -> Option<f32> {
+    let radius = match element {
+        "H" | "D" => 1.2,
+        "He" => 1.43,
+        "Li" => 2.12,
+        "Be" => 1.98,
+        "B" => 1.91,
+        "C" => 1.77,
+        "N" => 1.66,
+        "O" => 1.5,
+        "F" => 1.46,
+        "Ne" => 1.58,
+        "Na" => 2.5,
+        "Mg" => 2.51,
+        "Al" => 2.25,
+        "Si" => 2.19,
+        "P" => 1.9,
+        "S" => 1.89,
+        "Cl" => 1.82,
+        "Ar" => 1.83,
+        "K" => 2.73,
+        "Ca" => 2.62,
+        "Mn" => 2.45,
+        "Fe" => 2.44,
+        "Co" => 2.4,
+        "Ni" => 2.4,
+        "Cu" => 2.38,
+        "Zn" => 2.39,
+        "Se" => 1.82,
+        "Br" => 1.86,
+        "I" => 2.04,
+        _ => return None,
+    };
+    Some(radius)
+}
+
+/// Types of heavy atoms in amino acids by their valence (trigonal or tetrahedral) and the number of bonded hydrogens,
+/// named as in the ProtOr set (e.g. `C4H2` is a tetrahedral carbon with two hydrogens).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AtomType {
+    C3H0,
+    C3H1,
+    C4H1,
+    C4H2,
+    C4H3,
+    N3H0,
+    N3H1,
+    N3H2,
+    N4H3,
+    O1H0,
+    O2H1,
+    S2H0,
+    S2H1,
+}
+
+const AMINO_ACIDS: [&str; 20] = [
+    "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE", "LEU", "LYS", "MET",
+    "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL",
+];
+
+/// Returns the type of the atom if it is a heavy atom of a standard amino acid.
+fn atom_type(residue_name: &str, atom_name: &str) -> Option<AtomType> {
+    use AtomType::*;
+    if !AMINO_ACIDS.contains(&residue_name) {
+        return None;
+    }
+
+    let atom_type = match (residue_name, atom_name) {
+        // Backbone
+        ("PRO", "N") => N3H0,
+        (_, "N") => N3H1,
+        ("GLY", "CA") => C4H2,
+        (_, "CA") => C4H1,
+        (_, "C") => C3H0,
+        (_, "O" | "OXT") => O1H0,
+
+        ("ALA", "CB") => C4H3,
+        ("ARG", "CB" | "CG" | "CD") => C4H2,
+        ("ARG", "NE") => N3H1,
+        ("ARG", "CZ") => C3H0,
+        ("ARG", "NH1" | "NH2") => N3H2,
+        ("ASN", "CB") => C4H2,
+        ("ASN", "CG") => C3H0,
+        ("ASN", "OD1") => O1H0,
+        ("ASN", "ND2") => N3H2,
+        ("ASP", "CB") => C4H2,
+        ("ASP", "CG") => C3H0,
+        ("ASP", "OD1" | "OD2") => O1H0,
+        ("CYS", "CB") => C4H2,
+        ("CYS", "SG") => S2H1,
+        ("GLN", "CB" | "CG") => C4H2,
+        ("GLN", "CD") => C3H0,
+        ("GLN", "OE1") => O1H0,
+        ("GLN", "NE2") => N3H2,
+        ("GLU", "CB" | "CG") => C4H2,
+        ("GLU", "CD") => C3H0,
+        ("GLU", "OE1" | "OE2") => O1H0,
+        ("HIS", "CB") => C4H2,
+        ("HIS", "CG") => C3H0,
+        ("HIS", "ND1" | "NE2") => N3H1,
+        ("HIS", "CD2" | "CE1") => C3H1,
+        ("ILE", "CB") => C4H1,
+        ("ILE", "CG1") => C4H2,
+        ("ILE", "CG2" | "CD1") => C4H3,
+        ("LEU", "CB") => C4H2,
+        ("LEU", "CG") => C4H1,
+        ("LEU", "CD1" | "CD2") => C4H3,
+        ("LYS", "CB" | "CG" | "CD" | "CE") => C4H2,
+        ("LYS", "NZ") => N4H3,
+        ("MET", "CB" | "CG") => C4H2,
+        ("MET", "SD") => S2H0,
+        ("MET", "CE") => C4H3,
+        ("PHE", "CB") => C4H2,
+        ("PHE", "CG") => C3H0,
+        ("PHE", "CD1" | "CD2" | "CE1" | "CE2" | "CZ") => C3H1,
+        ("PRO", "CB" | "CG" | "CD") => C4H2,
+        ("SER", "CB") => C4H2,
+        ("SER", "OG") => O2H1,
+        ("THR", "CB") => C4H1,
+        ("THR", "OG1") => O2H1,
+        ("THR", "CG2") => C4H3,
+        ("TRP", "CB") => C4H2,
+        ("TRP", "CG" | "CD2" | "CE2") => C3H0,
+        ("TRP", "NE1") => N3H1,
+        ("TRP", "CD1" | "CE3" | "CZ2" | "CZ3" | "CH2") => C3H1,
+        ("TYR", "CB") => C4H2,
+        ("TYR", "CG" | "CZ") => C3H0,
+        ("TYR", "CD1" | "CD2" | "CE1" | "CE2") => C3H1,
+        ("TYR", "OH") => O2H1,
+        ("VAL", "CB") => C4H1,
+        ("VAL", "CG1" | "CG2") => C4H3,
+        _ => return None,
+    };
+    Some(atom_type)
+}
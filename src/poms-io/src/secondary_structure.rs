@@ -0,0 +1,163 @@
+//! Assignment of secondary structure to atoms from the `HELIX` and `SHEET` records of PDB files,
+//! or the `_struct_conf` and `_struct_sheet_range` categories of mmCIF and BinaryCIF files.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use poms_common::models::atom::{AtomMetadata, SecondaryStructure};
+
+use super::structure_file::StructureFile;
+
+/// A continuous range of residues within a chain sharing the same secondary structure.
+struct SecondaryStructureRange {
+    kind: SecondaryStructure,
+    chain_id: String,
+    first_residue_number: isize,
+    last_residue_number: isize,
+}
+
+/// Ranges of a single chain, sorted by their first residue so that the range of a residue can be found by binary search.
+#[derive(Default)]
+struct ChainRanges {
+    ranges: Vec<SecondaryStructureRange>,
+    /// The largest last residue number of the ranges up to each index, as ranges may overlap.
+    max_last_residue_numbers: Vec<isize>,
+}
+
+impl ChainRanges {
+    fn find(&self, residue_number: isize) -> Option<&SecondaryStructureRange> {
+        let end = self
+            .ranges
+            .partition_point(|range| range.first_residue_number <= residue_number);
+        (0..end)
+            .rev()
+            .take_while(|&index| self.max_last_residue_numbers[index] >= residue_number)
+            .map(|index| &self.ranges[index])
+            .find(|range| range.last_residue_number >= residue_number)
+    }
+}
+
+/// Secondary structure ranges read from a file grouped by chain, to be assigned to the atoms of each model in the file.
+pub(crate) struct SecondaryStructureRanges(HashMap<String, ChainRanges>);
+
+impl SecondaryStructureRanges {
+    /// Reads the ranges from the file. Returns no ranges if the file does not specify any.
+    pub(crate) fn from_file(file: &StructureFile) -> Self {
+        let ranges = match file {
+            StructureFile::Pdb(content) => parse_pdb_records(content),
+            _ => parse_cif_categories(file),
+        };
+
+        let mut chains: HashMap<String, ChainRanges> = HashMap::new();
+        for range in ranges {
+            chains
+                .entry(range.chain_id.clone())
+                .or_default()
+                .ranges
+                .push(range);
+        }
+        for chain in chains.values_mut() {
+            chain.ranges.sort_by_key(|range| range.first_residue_number);
+            chain.max_last_residue_numbers = chain
+                .ranges
+                .iter()
+                .scan(isize::MIN, |max, range| {
+                    *max = (*max).max(range.last_residue_number);
+                    Some(*max)
+                })
+                .collect();
+        }
+        Self(chains)
+    }
+
+    /// Sets `secondary_structure` of each atom in `metadata` according to the ranges.
+    /// Atoms outside of any range are left as `SecondaryStructure::Coil`.
+    pub(crate) fn assign(&self, metadata: &mut [AtomMetadata]) {
+        if self.0.is_empty() {
+            return;
+        }
+
+        for atom in metadata.iter_mut() {
+            let range = self
+                .0
+                .get(&atom.chain_id)
+                .and_then(|chain| chain.find(atom.residue_number));
+            if let Some(range) = range {
+                atom.secondary_structure = range.kind;
+            }
+        }
+    }
+}
+
+fn parse_pdb_records(content: &str) -> Vec<SecondaryStructureRange> {
+    // Positions of the chain identifier and residue numbers of the first and the last residue in each record.
+    const HELIX_CHAIN_ID: Range<usize> = 19..20;
+    const HELIX_FIRST_RESIDUE: Range<usize> = 21..25;
+    const HELIX_LAST_RESIDUE: Range<usize> = 33..37;
+    const SHEET_CHAIN_ID: Range<usize> = 21..22;
+    const SHEET_FIRST_RESIDUE: Range<usize> = 22..26;
+    const SHEET_LAST_RESIDUE: Range<usize> = 33..37;
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let (kind, chain_id, first, last) = if line.starts_with("HELIX ") {
+                (
+                    SecondaryStructure::Helix,
+                    HELIX_CHAIN_ID,
+                    HELIX_FIRST_RESIDUE,
+                    HELIX_LAST_RESIDUE,
+                )
+            } else if line.starts_with("SHEET ") {
+                (
+                    SecondaryStructure::Sheet,
+                    SHEET_CHAIN_ID,
+                    SHEET_FIRST_RESIDUE,
+                    SHEET_LAST_RESIDUE,
+                )
+            } else {
+                return None;
+            };
+
+            Some(SecondaryStructureRange {
+                kind,
+                chain_id: field(line, chain_id)?.to_string(),
+                first_residue_number: field(line, first)?.parse().ok()?,
+                last_residue_number: field(line, last)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the trimmed field, or `None` if the line is too short.
+fn field(line: &str, range: Range<usize>) -> Option<&str> {
+    line.get(range).map(str::trim)
+}
+
+fn parse_cif_categories(file: &StructureFile) -> Vec<SecondaryStructureRange> {
+    let (helices, sheets) = (
+        file.cif_category("_struct_conf"),
+        file.cif_category("_struct_sheet_range"),
+    );
+    let helices = helices
+        .iter()
+        // Besides helices, the category may also contain turns.
+        .filter(|row| {
+            row.get("conf_type_id")
+                .is_some_and(|t| t.starts_with("HELX"))
+        })
+        .map(|row| (SecondaryStructure::Helix, row));
+    let sheets = sheets.iter().map(|row| (SecondaryStructure::Sheet, row));
+
+    helices
+        .chain(sheets)
+        .filter_map(|(kind, row)| {
+            Some(SecondaryStructureRange {
+                kind,
+                chain_id: row.get("beg_auth_asym_id")?.clone(),
+                first_residue_number: row.get("beg_auth_seq_id")?.parse().ok()?,
+                last_residue_number: row.get("end_auth_seq_id")?.parse().ok()?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+//! Content of a structure file shared by the readers of data that `pdbtbx` does not expose
+//! (e.g. secondary structure, assemblies or the crystal symmetry), so that the file is only parsed once for all of them.
+
+use std::borrow::Cow;
+
+use super::binary_cif::BinaryCif;
+use super::cif::{CifCategories, CifRow};
+use super::molecule_parser::MoleculeFormat;
+
+pub(crate) enum StructureFile<'a> {
+    /// A PDB file or another text format, whose records are read line by line.
+    Pdb(&'a str),
+    /// An mmCIF file with its categories.
+    Cif(CifCategories),
+    /// A BinaryCIF file, decoded once for the atoms and the readers, whose categories are decoded on demand.
+    BinaryCif(BinaryCif),
+}
+
+impl<'a> StructureFile<'a> {
+    /// Parses the `content` of a text file of the given format. Returns `None` if it is not valid UTF-8.
+    /// BinaryCIF files are decoded along with their atoms instead, see [`StructureFile::BinaryCif`].
+    pub(crate) fn parse(format: MoleculeFormat, content: &'a [u8]) -> Option<Self> {
+        match format {
+            MoleculeFormat::BinaryCif => None,
+            MoleculeFormat::Cif => Some(Self::Cif(CifCategories::parse(
+                std::str::from_utf8(content).ok()?,
+            ))),
+            _ => Some(Self::Pdb(std::str::from_utf8(content).ok()?)),
+        }
+    }
+
+    /// Returns the rows of the mmCIF `category` (e.g. `_struct_conf`), or no rows if the file has no such category.
+    /// Categories of BinaryCIF files that cannot be decoded are treated as missing.
+    pub(crate) fn cif_category(&self, category: &str) -> Cow<'_, [CifRow]> {
+        match self {
+            Self::Pdb(_) => Cow::Borrowed(&[]),
+            Self::Cif(categories) => Cow::Borrowed(categories.rows(category)),
+            Self::BinaryCif(file) => Cow::Owned(
+                file.category(category)
+                    .ok()
+                    .flatten()
+                    .map(|category| category.rows())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! CHARMM and NAMD DCD trajectories, storing positions (in Å) as Fortran records in either byte order.
+
+use std::io::SeekFrom;
+
+use super::{index_frames, FrameDecoder, FrameHeader, FrameIndex, TrajectorySource};
+
+/// Size of the first record, holding the `CORD` signature and 20 control integers.
+const HEADER_RECORD_SIZE: i32 = 84;
+
+/// Size of the optional unit cell record, holding 6 doubles.
+const UNIT_CELL_RECORD_SIZE: u64 = 48;
+
+/// Size of the markers surrounding each Fortran record.
+const MARKER_SIZE: u64 = 4;
+
+pub(super) struct DcdDecoder {
+    is_big_endian: bool,
+    num_atoms: usize,
+    has_unit_cell: bool,
+}
+
+/// Reads the file header, returning the decoder and the index of frames that follow it.
+pub(super) fn open(source: &mut dyn TrajectorySource) -> anyhow::Result<(DcdDecoder, FrameIndex)> {
+    source.seek(SeekFrom::Start(0))?;
+
+    // The byte order is detected from the size of the first record.
+    let mut marker = [0; 4];
+    source.read_exact(&mut marker)?;
+    let is_big_endian = match (i32::from_le_bytes(marker), i32::from_be_bytes(marker)) {
+        (HEADER_RECORD_SIZE, _) => false,
+        (_, HEADER_RECORD_SIZE) => true,
+        _ => anyhow::bail!("Not a DCD file (unexpected size of the first record)"),
+    };
+    source.seek(SeekFrom::Start(0))?;
+
+    let mut decoder = DcdDecoder {
+        is_big_endian,
+        num_atoms: 0,
+        has_unit_cell: false,
+    };
+
+    let header = decoder.read_record(source)?;
+    if &header[..4] != b"CORD" {
+        anyhow::bail!("Not a DCD file (missing the CORD signature)");
+    }
+    let control: Vec<i32> = header[4..]
+        .chunks_exact(4)
+        .map(|bytes| decoder.to_i32(bytes))
+        .collect();
+    if control[8] != 0 {
+        anyhow::bail!("DCD files with fixed atoms are not supported");
+    }
+    // Only files written by CHARMM (or NAMD, which sets the CHARMM version too) may contain the extra records.
+    let is_charmm = control[19] != 0;
+    if is_charmm && control[11] != 0 {
+        anyhow::bail!("DCD files with four dimensional dynamics are not supported");
+    }
+    decoder.has_unit_cell = is_charmm && control[10] != 0;
+
+    // The title record is not needed.
+    decoder.read_record(source)?;
+
+    let atoms_record = decoder.read_record(source)?;
+    if atoms_record.len() != 4 {
+        anyhow::bail!("Invalid DCD header (unexpected size of the atom count record)");
+    }
+    decoder.num_atoms = usize::try_from(decoder.to_i32(&atoms_record))?;
+
+    // All frames have the same size, so there is no need to read them while indexing.
+    let coordinates_record_size = 2 * MARKER_SIZE + decoder.num_atoms as u64 * 4;
+    let unit_cell_size = match decoder.has_unit_cell {
+        true => 2 * MARKER_SIZE + UNIT_CELL_RECORD_SIZE,
+        false => 0,
+    };
+    let frame_header = || FrameHeader {
+        num_atoms: decoder.num_atoms,
+        size: unit_cell_size + 3 * coordinates_record_size,
+        has_positions: true,
+    };
+    let header_end = source.stream_position()?;
+    let index = index_frames(source, header_end, |_| Ok(frame_header()))?;
+
+    Ok((decoder, index))
+}
+
+impl DcdDecoder {
+    fn to_i32(&self, bytes: &[u8]) -> i32 {
+        let bytes = bytes.try_into().expect("integers have 4 bytes");
+        match self.is_big_endian {
+            true => i32::from_be_bytes(bytes),
+            false => i32::from_le_bytes(bytes),
+        }
+    }
+
+    fn to_f32(&self, bytes: &[u8]) -> f32 {
+        let bytes = bytes.try_into().expect("floats have 4 bytes");
+        match self.is_big_endian {
+            true => f32::from_be_bytes(bytes),
+            false => f32::from_le_bytes(bytes),
+        }
+    }
+
+    /// Reads a Fortran record, checking that the markers before and after it agree.
+    fn read_record(&self, source: &mut dyn TrajectorySource) -> anyhow::Result<Vec<u8>> {
+        let mut marker = [0; 4];
+        source.read_exact(&mut marker)?;
+        let size = self.to_i32(&marker);
+        let size = usize::try_from(size)
+            .map_err(|_| anyhow::anyhow!("Invalid record size {} in the DCD file", size))?;
+
+        let mut record = vec![0; size];
+        source.read_exact(&mut record)?;
+
+        source.read_exact(&mut marker)?;
+        if self.to_i32(&marker) as usize != size {
+            anyhow::bail!("Corrupted DCD file (mismatched record markers)");
+        }
+        Ok(record)
+    }
+}
+
+impl FrameDecoder for DcdDecoder {
+    fn decode_frame(
+        &self,
+        source: &mut dyn TrajectorySource,
+        offset: u64,
+    ) -> anyhow::Result<Vec<[f32; 3]>> {
+        source.seek(SeekFrom::Start(offset))?;
+        if self.has_unit_cell {
+            self.read_record(source)?;
+        }
+
+        // Coordinates are stored as three separate records of all X, Y and Z values.
+        let mut positions = vec![[0.0; 3]; self.num_atoms];
+        for axis in 0..3 {
+            let record = self.read_record(source)?;
+            if record.len() != self.num_atoms * 4 {
+                anyhow::bail!("Corrupted DCD file (unexpected size of a coordinate record)");
+            }
+            for (position, bytes) in positions.iter_mut().zip(record.chunks_exact(4)) {
+                position[axis] = self.to_f32(bytes);
+            }
+        }
+        Ok(positions)
+    }
+}
@@ -0,0 +1,222 @@
+//! Streaming readers of molecular dynamics trajectories in the XTC, TRR and DCD formats.
+//!
+//! A trajectory holds only the positions of atoms, everything else comes from a topology file (e.g. PDB) opened with it.
+//! Frames are indexed when the trajectory is opened, but decoded only when needed, keeping a bounded cache of recent frames.
+
+mod dcd;
+mod trr;
+mod xdr;
+mod xtc;
+
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Conversion factor for formats that store positions in nanometers (GROMACS).
+pub(crate) const NANOMETERS_TO_ANGSTROMS: f32 = 10.0;
+
+/// Upper bound on the memory taken by the cached frames of a single trajectory.
+const FRAME_CACHE_SIZE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Supported trajectory formats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrajectoryFormat {
+    Xtc,
+    Trr,
+    Dcd,
+}
+
+impl TrajectoryFormat {
+    /// File extensions of all supported formats.
+    pub const EXTENSIONS: [&'static str; 3] = ["xtc", "trr", "dcd"];
+
+    /// Determines the format from the extension of the file name.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "xtc" => Some(Self::Xtc),
+            "trr" => Some(Self::Trr),
+            "dcd" => Some(Self::Dcd),
+            _ => None,
+        }
+    }
+}
+
+/// Anything the frames can be read from. On native platforms it is the file itself, so the frames are streamed from disk.
+pub trait TrajectorySource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> TrajectorySource for T {}
+
+/// Decodes frames of a single trajectory format.
+trait FrameDecoder: Send {
+    /// Decodes the positions (in Å) of all atoms in the frame starting at `offset` bytes.
+    fn decode_frame(
+        &self,
+        source: &mut dyn TrajectorySource,
+        offset: u64,
+    ) -> anyhow::Result<Vec<[f32; 3]>>;
+}
+
+/// Information about a frame read from its header, enough to skip to the next frame.
+struct FrameHeader {
+    num_atoms: usize,
+    /// Size of the whole frame in bytes, including the header.
+    size: u64,
+    /// Some formats allow frames with only velocities or forces, these are skipped.
+    has_positions: bool,
+}
+
+/// Positions of frames within the trajectory.
+struct FrameIndex {
+    num_atoms: usize,
+    /// Byte offset of each frame with positions.
+    offsets: Vec<u64>,
+}
+
+/// A trajectory opened for reading, frames are decoded on demand.
+pub struct Trajectory {
+    pub filename: String,
+    source: Box<dyn TrajectorySource>,
+    decoder: Box<dyn FrameDecoder>,
+    index: FrameIndex,
+    /// Recently decoded frames keyed by their index.
+    cache: HashMap<usize, CachedFrame>,
+    cache_capacity: usize,
+    /// Incremented on every read, to find the least recently used frame when the cache is full.
+    num_reads: u64,
+}
+
+struct CachedFrame {
+    positions: Vec<[f32; 3]>,
+    last_read: u64,
+}
+
+impl Trajectory {
+    /// Opens the trajectory and indexes its frames. Only the frame headers are read at this point.
+    pub fn open(
+        filename: String,
+        format: TrajectoryFormat,
+        mut source: Box<dyn TrajectorySource>,
+    ) -> anyhow::Result<Self> {
+        let (decoder, index): (Box<dyn FrameDecoder>, FrameIndex) = match format {
+            TrajectoryFormat::Xtc => (Box::new(xtc::XtcDecoder), xtc::index(source.as_mut())?),
+            TrajectoryFormat::Trr => (Box::new(trr::TrrDecoder), trr::index(source.as_mut())?),
+            TrajectoryFormat::Dcd => {
+                let (decoder, index) = dcd::open(source.as_mut())?;
+                (Box::new(decoder), index)
+            }
+        };
+        if index.offsets.is_empty() {
+            anyhow::bail!("The trajectory {} does not contain any frames", filename);
+        }
+
+        let frame_size = index.num_atoms.max(1) * std::mem::size_of::<[f32; 3]>();
+        Ok(Self {
+            filename,
+            source,
+            decoder,
+            index,
+            cache: HashMap::new(),
+            cache_capacity: (FRAME_CACHE_SIZE_BYTES / frame_size).max(1),
+            num_reads: 0,
+        })
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.index.offsets.len()
+    }
+
+    pub fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    /// Returns the positions (in Å) of all atoms in the given frame, decoding the frame if it is not cached.
+    pub fn read_frame(&mut self, frame: usize) -> anyhow::Result<&[[f32; 3]]> {
+        let Some(&offset) = self.index.offsets.get(frame) else {
+            anyhow::bail!(
+                "Frame {} is out of range, the trajectory has {} frames",
+                frame,
+                self.num_frames()
+            );
+        };
+
+        self.num_reads += 1;
+        if !self.cache.contains_key(&frame) {
+            let positions = self.decoder.decode_frame(self.source.as_mut(), offset)?;
+            if positions.len() != self.index.num_atoms {
+                anyhow::bail!(
+                    "Frame {} has {} atoms instead of {}",
+                    frame,
+                    positions.len(),
+                    self.index.num_atoms
+                );
+            }
+            if self.cache.len() >= self.cache_capacity {
+                let least_recently_read = self
+                    .cache
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.last_read)
+                    .map(|(&cached, _)| cached);
+                if let Some(least_recently_read) = least_recently_read {
+                    self.cache.remove(&least_recently_read);
+                }
+            }
+            self.cache.insert(
+                frame,
+                CachedFrame {
+                    positions,
+                    last_read: 0,
+                },
+            );
+        }
+
+        let cached = self.cache.get_mut(&frame).expect("the frame is cached");
+        cached.last_read = self.num_reads;
+        Ok(&cached.positions)
+    }
+}
+
+/// Indexes the frames by reading the header of each frame, starting at `start` and skipping over the frame data.
+/// A truncated last frame (e.g. of a simulation that is still running) is ignored.
+fn index_frames(
+    source: &mut dyn TrajectorySource,
+    start: u64,
+    mut read_header: impl FnMut(&mut dyn TrajectorySource) -> anyhow::Result<FrameHeader>,
+) -> anyhow::Result<FrameIndex> {
+    let length = source.seek(SeekFrom::End(0))?;
+    let mut index = FrameIndex {
+        num_atoms: 0,
+        offsets: Vec::new(),
+    };
+
+    let mut offset = start;
+    let mut is_first_frame = true;
+    while offset < length {
+        source.seek(SeekFrom::Start(offset))?;
+        let header = match read_header(source) {
+            Ok(header) => header,
+            // Garbage at the very beginning means the file is not in the expected format.
+            Err(error) if is_first_frame => return Err(error),
+            Err(_) => break,
+        };
+        if header.size == 0 || offset + header.size > length {
+            break;
+        }
+
+        if header.has_positions {
+            if !index.offsets.is_empty() && header.num_atoms != index.num_atoms {
+                anyhow::bail!(
+                    "Frame {} has {} atoms, but the previous frames have {}",
+                    index.offsets.len(),
+                    header.num_atoms,
+                    index.num_atoms
+                );
+            }
+            index.num_atoms = header.num_atoms;
+            index.offsets.push(offset);
+        }
+        offset += header.size;
+        is_first_frame = false;
+    }
+
+    Ok(index)
+}
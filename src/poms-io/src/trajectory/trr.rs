@@ -0,0 +1,124 @@
+//! GROMACS TRR trajectories, storing uncompressed positions, velocities and forces in single or double precision.
+
+use std::io::SeekFrom;
+
+use super::xdr::{padded, read_i32, read_reals, read_size, skip};
+use super::{
+    index_frames, FrameDecoder, FrameHeader, FrameIndex, TrajectorySource, NANOMETERS_TO_ANGSTROMS,
+};
+
+const TRR_MAGIC: i32 = 1993;
+
+/// Sizes of the sections of a frame as given in its header.
+struct TrrHeader {
+    num_atoms: usize,
+    is_double: bool,
+    /// Size of the header itself.
+    header_size: u64,
+    box_size: u64,
+    virial_size: u64,
+    pressure_size: u64,
+    positions_size: u64,
+    velocities_size: u64,
+    forces_size: u64,
+}
+
+impl TrrHeader {
+    fn read(source: &mut dyn TrajectorySource) -> anyhow::Result<Self> {
+        let magic = read_i32(source)?;
+        if magic != TRR_MAGIC {
+            anyhow::bail!("Not a TRR file (unexpected magic number {})", magic);
+        }
+        // The version string is preceded by its length twice, once as an integer and once as part of the XDR string.
+        read_i32(source)?;
+        let version_length = read_size(source)?;
+        skip(source, padded(version_length))?;
+
+        let mut sizes = [0; 13];
+        for size in sizes.iter_mut() {
+            *size = read_size(source)?;
+        }
+        let [_ir, _energy, box_size, virial, pressure, _topology, _symmetry, positions, velocities, forces, num_atoms, _step, _num_energies] =
+            sizes;
+
+        // The precision is not stored explicitly, it has to be deduced from the size of any present section.
+        let real_size = if box_size != 0 {
+            box_size / 9
+        } else {
+            [positions, velocities, forces]
+                .into_iter()
+                .find(|size| *size != 0)
+                .map_or(4, |size| size / (num_atoms.max(1) * 3))
+        };
+        if real_size != 4 && real_size != 8 {
+            anyhow::bail!("Unsupported precision of the TRR file");
+        }
+
+        // Fixed part of the header, followed by the time and lambda.
+        let header_size = 3 * 4 + padded(version_length) + 13 * 4 + 2 * real_size as u64;
+        Ok(Self {
+            num_atoms,
+            is_double: real_size == 8,
+            header_size,
+            box_size: box_size as u64,
+            virial_size: virial as u64,
+            pressure_size: pressure as u64,
+            positions_size: positions as u64,
+            velocities_size: velocities as u64,
+            forces_size: forces as u64,
+        })
+    }
+
+    fn frame_size(&self) -> u64 {
+        self.header_size
+            + self.box_size
+            + self.virial_size
+            + self.pressure_size
+            + self.positions_size
+            + self.velocities_size
+            + self.forces_size
+    }
+}
+
+pub(super) fn index(source: &mut dyn TrajectorySource) -> anyhow::Result<FrameIndex> {
+    index_frames(source, 0, |source| {
+        let header = TrrHeader::read(source)?;
+        Ok(FrameHeader {
+            num_atoms: header.num_atoms,
+            size: header.frame_size(),
+            has_positions: header.positions_size != 0,
+        })
+    })
+}
+
+pub(super) struct TrrDecoder;
+
+impl FrameDecoder for TrrDecoder {
+    fn decode_frame(
+        &self,
+        source: &mut dyn TrajectorySource,
+        offset: u64,
+    ) -> anyhow::Result<Vec<[f32; 3]>> {
+        source.seek(SeekFrom::Start(offset))?;
+        let header = TrrHeader::read(source)?;
+
+        // Skip the time and lambda and the sections preceding the positions.
+        source.seek(SeekFrom::Start(offset + header.header_size))?;
+        skip(
+            source,
+            header.box_size + header.virial_size + header.pressure_size,
+        )?;
+
+        let values = read_reals(source, header.num_atoms * 3, header.is_double)?;
+        Ok(values
+            .chunks_exact(3)
+            .map(|position| {
+                [
+                    position[0] * NANOMETERS_TO_ANGSTROMS,
+                    position[1] * NANOMETERS_TO_ANGSTROMS,
+                    position[2] * NANOMETERS_TO_ANGSTROMS,
+                ]
+            })
+            .collect())
+    }
+}
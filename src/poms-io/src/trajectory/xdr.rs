@@ -0,0 +1,55 @@
+//! Reading of big-endian XDR primitives used by the GROMACS formats.
+
+use std::io::SeekFrom;
+
+use super::TrajectorySource;
+
+pub(super) fn read_i32(source: &mut dyn TrajectorySource) -> std::io::Result<i32> {
+    let mut bytes = [0; 4];
+    source.read_exact(&mut bytes)?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+pub(super) fn read_f32(source: &mut dyn TrajectorySource) -> std::io::Result<f32> {
+    let mut bytes = [0; 4];
+    source.read_exact(&mut bytes)?;
+    Ok(f32::from_be_bytes(bytes))
+}
+
+/// Reads a non-negative integer used as a count or a size.
+pub(super) fn read_size(source: &mut dyn TrajectorySource) -> anyhow::Result<usize> {
+    let value = read_i32(source)?;
+    usize::try_from(value).map_err(|_| anyhow::anyhow!("Invalid size {} in the trajectory", value))
+}
+
+/// Reads `count` floating point numbers, either in single or double precision.
+pub(super) fn read_reals(
+    source: &mut dyn TrajectorySource,
+    count: usize,
+    is_double: bool,
+) -> std::io::Result<Vec<f32>> {
+    let size = if is_double { 8 } else { 4 };
+    let mut bytes = vec![0; count * size];
+    source.read_exact(&mut bytes)?;
+
+    let values = match is_double {
+        true => bytes
+            .chunks_exact(8)
+            .map(|chunk| f64::from_be_bytes(chunk.try_into().expect("chunk has 8 bytes")) as f32)
+            .collect(),
+        false => bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_be_bytes(chunk.try_into().expect("chunk has 4 bytes")))
+            .collect(),
+    };
+    Ok(values)
+}
+
+/// Size of opaque data or a string of `length` bytes, which XDR pads to a multiple of 4 bytes.
+pub(super) fn padded(length: usize) -> u64 {
+    length.div_ceil(4) as u64 * 4
+}
+
+pub(super) fn skip(source: &mut dyn TrajectorySource, bytes: u64) -> std::io::Result<()> {
+    source.seek(SeekFrom::Current(bytes as i64)).map(|_| ())
+}
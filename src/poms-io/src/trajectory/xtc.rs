@@ -0,0 +1,347 @@
+//! GROMACS XTC trajectories, storing positions with lossy compression.
+//!
+//! The decompression follows the reference implementation in the `xdrfile` library: positions are quantized
+//! to integers by the stored precision and packed into as few bits as possible, with runs of nearby atoms
+//! (e.g. atoms of a water molecule) stored as small differences.
+
+use std::cmp::Ordering;
+use std::io::SeekFrom;
+
+use super::xdr::{padded, read_f32, read_i32, read_size, skip};
+use super::{
+    index_frames, FrameDecoder, FrameHeader, FrameIndex, TrajectorySource, NANOMETERS_TO_ANGSTROMS,
+};
+
+const XTC_MAGIC: i32 = 1995;
+
+/// Frames with this few atoms are stored uncompressed.
+const MAX_UNCOMPRESSED_ATOMS: usize = 9;
+
+/// Size of the frame header: magic number, number of atoms, step, time, box and number of atoms again.
+const HEADER_SIZE: u64 = 4 + 4 + 4 + 4 + 9 * 4 + 4;
+
+/// Size of the parameters of the compression preceding the compressed data.
+const COMPRESSION_PARAMETERS_SIZE: u64 = 4 + 3 * 4 + 3 * 4 + 4 + 4;
+
+const MAGIC_INTS: [u32; 73] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 10, 12, 16, 20, 25, 32, 40, 50, 64, 80, 101, 128, 161, 203, 256,
+    322, 406, 512, 645, 812, 1024, 1290, 1625, 2048, 2580, 3250, 4096, 5060, 6501, 8192, 10321,
+    13003, 16384, 20642, 26007, 32768, 41285, 52015, 65536, 82570, 104031, 131072, 165140, 208063,
+    262144, 330280, 416127, 524287, 660561, 832255, 1048576, 1321122, 1664510, 2097152, 2642245,
+    3329021, 4194304, 5284491, 6658042, 8388607, 10568983, 13316085, 16777216,
+];
+
+/// Index of the first non-zero value in `MAGIC_INTS`.
+const FIRST_MAGIC_INDEX: usize = 9;
+
+/// Reads the header of the frame, returning the number of atoms.
+fn read_header(source: &mut dyn TrajectorySource) -> anyhow::Result<usize> {
+    let magic = read_i32(source)?;
+    if magic != XTC_MAGIC {
+        anyhow::bail!("Not an XTC file (unexpected magic number {})", magic);
+    }
+    let num_atoms = read_size(source)?;
+    // Skip the step, time and box.
+    skip(source, 4 + 4 + 9 * 4)?;
+    if read_size(source)? != num_atoms {
+        anyhow::bail!("Inconsistent number of atoms in the XTC frame header");
+    }
+    Ok(num_atoms)
+}
+
+pub(super) fn index(source: &mut dyn TrajectorySource) -> anyhow::Result<FrameIndex> {
+    index_frames(source, 0, |source| {
+        let num_atoms = read_header(source)?;
+        let data_size = match num_atoms <= MAX_UNCOMPRESSED_ATOMS {
+            true => num_atoms as u64 * 3 * 4,
+            false => {
+                skip(source, COMPRESSION_PARAMETERS_SIZE - 4)?;
+                COMPRESSION_PARAMETERS_SIZE + padded(read_size(source)?)
+            }
+        };
+        Ok(FrameHeader {
+            num_atoms,
+            size: HEADER_SIZE + data_size,
+            has_positions: true,
+        })
+    })
+}
+
+pub(super) struct XtcDecoder;
+
+impl FrameDecoder for XtcDecoder {
+    fn decode_frame(
+        &self,
+        source: &mut dyn TrajectorySource,
+        offset: u64,
+    ) -> anyhow::Result<Vec<[f32; 3]>> {
+        source.seek(SeekFrom::Start(offset))?;
+        let num_atoms = read_header(source)?;
+
+        let positions = match num_atoms <= MAX_UNCOMPRESSED_ATOMS {
+            true => (0..num_atoms)
+                .map(|_| Ok([read_f32(source)?, read_f32(source)?, read_f32(source)?]))
+                .collect::<std::io::Result<Vec<_>>>()?,
+            false => decompress(source, num_atoms)?,
+        };
+
+        Ok(positions
+            .into_iter()
+            .map(|position| position.map(|c| c * NANOMETERS_TO_ANGSTROMS))
+            .collect())
+    }
+}
+
+/// Reads the compressed positions (in nm) of `num_atoms` atoms.
+fn decompress(
+    source: &mut dyn TrajectorySource,
+    num_atoms: usize,
+) -> anyhow::Result<Vec<[f32; 3]>> {
+    let precision = read_f32(source)?;
+    let min_int = [read_i32(source)?, read_i32(source)?, read_i32(source)?];
+    let max_int = [read_i32(source)?, read_i32(source)?, read_i32(source)?];
+    let mut small_index = read_size(source)?;
+    let byte_count = read_size(source)?;
+
+    let mut bytes = vec![0; byte_count];
+    source.read_exact(&mut bytes)?;
+    let mut bits = BitReader::new(&bytes);
+
+    if precision <= 0.0 || !(FIRST_MAGIC_INDEX..MAGIC_INTS.len()).contains(&small_index) {
+        anyhow::bail!("Invalid compression parameters in the XTC frame");
+    }
+
+    let sizes: [u32; 3] =
+        std::array::from_fn(|i| max_int[i].wrapping_sub(min_int[i]).wrapping_add(1) as u32);
+    // Large ranges are stored as separate integers, otherwise the three integers are packed together.
+    let is_large = (sizes[0] | sizes[1] | sizes[2]) > 0xffffff;
+    let bit_sizes = sizes.map(size_of_int);
+    let packed_bit_size = size_of_ints(&sizes);
+
+    let mut smaller = MAGIC_INTS[FIRST_MAGIC_INDEX.max(small_index - 1)] / 2;
+    let mut small_num = MAGIC_INTS[small_index] / 2;
+    let mut small_sizes = [MAGIC_INTS[small_index]; 3];
+
+    let inverse_precision = 1.0 / precision;
+    let to_position = |coordinates: [i32; 3]| coordinates.map(|c| c as f32 * inverse_precision);
+
+    let mut positions = Vec::with_capacity(num_atoms);
+    let mut run = 0;
+    while positions.len() < num_atoms {
+        let mut this = match is_large {
+            true => [
+                bits.read(bit_sizes[0])?,
+                bits.read(bit_sizes[1])?,
+                bits.read(bit_sizes[2])?,
+            ],
+            false => bits.read_ints(packed_bit_size, &sizes)?,
+        }
+        .map(|c| c as i32);
+        for (coordinate, min) in this.iter_mut().zip(min_int) {
+            *coordinate = coordinate.wrapping_add(min);
+        }
+        let mut previous = this;
+
+        let mut is_smaller = 0;
+        if bits.read(1)? == 1 {
+            run = bits.read(5)? as i32;
+            is_smaller = run % 3;
+            run -= is_smaller;
+            is_smaller -= 1;
+        }
+
+        if run > 0 {
+            for k in (0..run).step_by(3) {
+                // The magic integers are chosen so that three of them fit into `small_index` bits.
+                let small = bits.read_ints(small_index as u32, &small_sizes)?;
+                let mut this: [i32; 3] =
+                    std::array::from_fn(|i| small[i] as i32 + previous[i] - small_num as i32);
+                if k == 0 {
+                    // The first two atoms of a run are swapped, for better compression of water molecules.
+                    std::mem::swap(&mut this, &mut previous);
+                    positions.push(to_position(previous));
+                } else {
+                    previous = this;
+                }
+                positions.push(to_position(this));
+            }
+        } else {
+            positions.push(to_position(this));
+        }
+
+        small_index = small_index
+            .checked_add_signed(is_smaller as isize)
+            .filter(|index| (FIRST_MAGIC_INDEX..MAGIC_INTS.len()).contains(index))
+            .ok_or_else(|| anyhow::anyhow!("Corrupted compressed data in the XTC frame"))?;
+        match is_smaller.cmp(&0) {
+            Ordering::Less => {
+                small_num = smaller;
+                smaller = match small_index > FIRST_MAGIC_INDEX {
+                    true => MAGIC_INTS[small_index - 1] / 2,
+                    false => 0,
+                };
+            }
+            Ordering::Greater => {
+                smaller = small_num;
+                small_num = MAGIC_INTS[small_index] / 2;
+            }
+            Ordering::Equal => {}
+        }
+        small_sizes = [MAGIC_INTS[small_index]; 3];
+    }
+
+    positions.truncate(num_atoms);
+    Ok(positions)
+}
+
+/// Number of bits needed to store `size` itself, as computed by the reference implementation.
+fn size_of_int(size: u32) -> u32 {
+    u32::BITS - size.leading_zeros()
+}
+
+/// Number of bits needed to store the integers packed together, each smaller than its size.
+fn size_of_ints(sizes: &[u32; 3]) -> u32 {
+    // Multiply the sizes as a little-endian big integer stored in bytes.
+    let mut bytes = vec![1u32];
+    for &size in sizes {
+        let mut carry = 0u64;
+        for byte in bytes.iter_mut() {
+            let product = *byte as u64 * size as u64 + carry;
+            *byte = (product & 0xff) as u32;
+            carry = product >> 8;
+        }
+        while carry != 0 {
+            bytes.push((carry & 0xff) as u32);
+            carry >>= 8;
+        }
+    }
+
+    let last = *bytes.last().expect("there is always at least one byte");
+    let last_bits = u32::BITS - last.leading_zeros();
+    last_bits + (bytes.len() as u32 - 1) * 8
+}
+
+/// Reads individual bits from the compressed data, most significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    /// Number of bits of `last_byte` not read yet.
+    last_bits: u32,
+    last_byte: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            last_bits: 0,
+            last_byte: 0,
+        }
+    }
+
+    fn next_byte(&mut self) -> anyhow::Result<u32> {
+        let byte = self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| anyhow::anyhow!("Unexpected end of the compressed XTC data"))?;
+        self.position += 1;
+        Ok(*byte as u32)
+    }
+
+    /// Reads an unsigned integer of `num_bits` bits (at most 32).
+    fn read(&mut self, mut num_bits: u32) -> anyhow::Result<u32> {
+        let mask = ((1u64 << num_bits) - 1) as u32;
+        let mut value = 0u32;
+
+        while num_bits >= 8 {
+            self.last_byte = (self.last_byte << 8) | self.next_byte()?;
+            value |= (self.last_byte >> self.last_bits) << (num_bits - 8);
+            num_bits -= 8;
+        }
+        if num_bits > 0 {
+            if self.last_bits < num_bits {
+                self.last_bits += 8;
+                self.last_byte = (self.last_byte << 8) | self.next_byte()?;
+            }
+            self.last_bits -= num_bits;
+            value |= (self.last_byte >> self.last_bits) & ((1 << num_bits) - 1);
+        }
+
+        Ok(value & mask)
+    }
+
+    /// Reads three integers packed together into `num_bits` bits, each smaller than its size.
+    fn read_ints(&mut self, mut num_bits: u32, sizes: &[u32; 3]) -> anyhow::Result<[u32; 3]> {
+        let mut bytes = Vec::with_capacity(num_bits.div_ceil(8) as usize);
+        while num_bits > 8 {
+            bytes.push(self.read(8)?);
+            num_bits -= 8;
+        }
+        if num_bits > 0 {
+            bytes.push(self.read(num_bits)?);
+        }
+
+        // Unpack by repeatedly dividing the big integer stored in the bytes by the sizes.
+        let mut values = [0; 3];
+        for i in (1..3).rev() {
+            let mut remainder = 0u64;
+            for byte in bytes.iter_mut().rev() {
+                let number = (remainder << 8) | *byte as u64;
+                *byte = (number / sizes[i] as u64) as u32;
+                remainder = number % sizes[i] as u64;
+            }
+            values[i] = remainder as u32;
+        }
+        values[0] = bytes
+            .iter()
+            .take(4)
+            .enumerate()
+            .fold(0, |value, (i, byte)| value | (byte << (8 * i)));
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Trajectory, TrajectoryFormat};
+
+    const XTC: &[u8] = include_bytes!("../../../../data/trajectory/water.xtc");
+    /// Topology of the trajectory, with the positions (in nm, to three decimals) of its first frame.
+    const GRO: &str = include_str!("../../../../data/trajectory/water.gro");
+
+    #[test]
+    fn decodes_positions_of_the_topology() {
+        let mut trajectory = Trajectory::open(
+            "water.xtc".to_string(),
+            TrajectoryFormat::Xtc,
+            Box::new(std::io::Cursor::new(XTC)),
+        )
+        .unwrap();
+
+        // The title, the number of atoms and then a fixed-width line per atom.
+        let num_atoms = GRO.lines().nth(1).unwrap().trim().parse::<usize>().unwrap();
+        let expected = GRO
+            .lines()
+            .skip(2)
+            .take(num_atoms)
+            .map(|line| {
+                [20, 28, 36]
+                    .map(|start| line[start..start + 8].trim().parse::<f32>().unwrap() * 10.0)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(trajectory.num_atoms(), num_atoms);
+        assert_eq!(trajectory.num_frames(), 3);
+
+        let positions = trajectory.read_frame(0).unwrap();
+        for (position, expected) in positions.iter().zip(&expected) {
+            for (coordinate, expected) in position.iter().zip(expected) {
+                assert!(
+                    (coordinate - expected).abs() < 1e-4,
+                    "{position:?} != {expected:?}"
+                );
+            }
+        }
+    }
+}
@@ -76,7 +76,8 @@ impl RenderJobs {
         };
 
         let spacefill_pass = SpacefillPass::new(device, &resources, params.common_resources);
-        let molecular_surface_pass = MolecularSurfacePass::new(device, &resources);
+        let molecular_surface_pass =
+            MolecularSurfacePass::new(device, &resources, params.common_resources);
         let postprocess_pass = PostprocessPass::new(
             device,
             params.queue,
@@ -107,7 +108,8 @@ impl RenderJobs {
         }
 
         if self.settings.render_molecular_surface {
-            self.molecular_surface_pass.render(encoder, &self.resources);
+            self.molecular_surface_pass
+                .render(encoder, &self.resources, common_resources);
         }
 
         self.postprocess_pass
@@ -124,6 +126,12 @@ impl RenderJobs {
         self.resources.distance_field = DistanceField::from_texture(device, grid, texture);
     }
 
+    /// Returns the distance field texture the molecular surface is rendered from, with the grid it was computed on.
+    pub fn distance_field(&self) -> (&wgpu::Texture, GridUniform) {
+        let distance_field = &self.resources.distance_field;
+        (&distance_field.texture, distance_field.grid)
+    }
+
     /// On resize, all dependent textures needs to be recreated.
     pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
         self.resources.color_texture = ColorTexture::new(device, config);
@@ -1,5 +1,7 @@
 use crate::RenderResources;
 
+use poms_common::resources::CommonResources;
+
 /// Wrapper around `wgpu::RenderPipeline` that is used to render the molecular surface representation of the molecule.
 pub struct MolecularSurfacePass {
     render_pipeline: wgpu::RenderPipeline,
@@ -9,14 +11,19 @@ impl MolecularSurfacePass {
     const WGPU_LABEL: &'static str = "molecular_surface_pass";
 
     /// Creates a new instance of `MolecularSurfacePass` using the provided resources.
-    /// The surface is rendered using raymarching and the signed distance field.
-    pub fn new(device: &wgpu::Device, render_resources: &RenderResources) -> Self {
+    /// The surface is rendered using raymarching and the signed distance field, and colored by the atoms nearest to it.
+    pub fn new(
+        device: &wgpu::Device,
+        render_resources: &RenderResources,
+        common_resources: &CommonResources,
+    ) -> Self {
         let shader = wgpu::include_wgsl!("../shaders/molecular_surface.wgsl");
 
         let bind_group_layouts = &[
             &render_resources.camera.bind_group_layout,
             &render_resources.light.bind_group_layout,
             &render_resources.distance_field.bind_group_layout,
+            &common_resources.atoms_resource.bind_group_layout,
         ];
 
         let render_pipeline: wgpu::RenderPipeline =
@@ -27,7 +34,12 @@ impl MolecularSurfacePass {
 
     /// Records the created render pass to the provided `encoder`.
     /// Call this every frame to render the molecular surface.
-    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_resources: &RenderResources) {
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_resources: &RenderResources,
+        common_resources: &CommonResources,
+    ) {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some(Self::WGPU_LABEL),
             color_attachments: &[
@@ -64,6 +76,7 @@ impl MolecularSurfacePass {
         render_pass.set_bind_group(0, &render_resources.camera.bind_group, &[]);
         render_pass.set_bind_group(1, &render_resources.light.bind_group, &[]);
         render_pass.set_bind_group(2, &render_resources.distance_field.bind_group, &[]);
+        render_pass.set_bind_group(3, &common_resources.atoms_resource.bind_group, &[]);
 
         // Render a full screen quad used for raymarching.
         let number_of_vertices: u32 = 6;
@@ -5,8 +5,11 @@ use wgpu::util::DeviceExt;
 
 /// The signed distance field that is used for rendering of the molecular surface using raymarching.
 /// Each voxel in the distance field is a signed distance to the nearest surface.
+/// The remaining channels of each voxel hold the index of the nearest atom, which is used to color the surface.
 /// The distance field can either be provided externally or generated using the `poms-compute` crate.
 pub struct DistanceField {
+    /// The grid the distance field was computed on, also stored in `grid_buffer`.
+    pub grid: GridUniform,
     pub grid_buffer: wgpu::Buffer,
     pub texture: wgpu::Texture,
 
@@ -62,6 +65,7 @@ impl DistanceField {
         });
 
         Self {
+            grid,
             grid_buffer,
             texture,
             bind_group_layout,
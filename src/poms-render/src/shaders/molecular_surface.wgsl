@@ -20,6 +20,12 @@ struct LightUniform {
     direction: vec3<f32>,
 };
 
+struct Atom {
+    position: vec3<f32>,
+    radius: f32,
+    color: vec4<f32>,
+};
+
 
 // Camera & Light Resources
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
@@ -30,6 +36,9 @@ struct LightUniform {
 @group(2) @binding(1) var df_texture: texture_3d<f32>;
 @group(2) @binding(2) var df_sampler: sampler;
 
+// Atoms Resource
+@group(3) @binding(0) var<storage, read> atoms_sorted: array<Atom>;
+
 
 fn distance_from_df_trilinear(position: vec3<f32>) -> f32 {
     let tex_coord: vec3<f32> = (position - df_grid.origin.xyz) / (f32(df_grid.resolution) * df_grid.offset);
@@ -90,6 +99,41 @@ fn compute_gradient(point: vec3<f32>) -> vec3<f32> {
     ) / epsilon;
 }
 
+// The nearest atom index is split across the `g` and `b` channels, see `refinement.wgsl` in `poms-compute`.
+const ATOM_INDEX_PART: u32 = 2048u;
+
+// Blends the colors of the atoms nearest to the eight grid points around the position.
+// Grid points with no atom nearby are skipped, if there are none at all, the default surface color is used.
+fn surface_color(position: vec3<f32>) -> vec3<f32> {
+    let max_index = vec3<i32>(i32(df_grid.resolution) - 1);
+    let coord_grid: vec3<f32> = (position - df_grid.origin.xyz) / df_grid.offset - vec3<f32>(0.5);
+    let base: vec3<f32> = floor(coord_grid);
+    let fraction: vec3<f32> = coord_grid - base;
+
+    var color = vec3<f32>(0.0);
+    var total_weight: f32 = 0.0;
+
+    for (var i: u32 = 0u; i < 8u; i += 1u) {
+        let corner = vec3<u32>(i & 1u, (i >> 1u) & 1u, (i >> 2u) & 1u);
+        let texel: vec4<f32> = textureLoad(df_texture, clamp(vec3<i32>(base) + vec3<i32>(corner), vec3<i32>(0), max_index), 0);
+        if (texel.a < 0.5) {
+            continue;
+        }
+
+        let weights: vec3<f32> = select(vec3<f32>(1.0) - fraction, fraction, corner == vec3<u32>(1u));
+        let weight: f32 = weights.x * weights.y * weights.z;
+
+        let atom_index: u32 = u32(round(texel.g)) + u32(round(texel.b)) * ATOM_INDEX_PART;
+        color += atoms_sorted[atom_index].color.rgb * weight;
+        total_weight += weight;
+    }
+
+    if (total_weight <= 0.0) {
+        return SURFACE_COLOR;
+    }
+    return color / total_weight;
+}
+
 struct RayHit {
     hit: bool,
     position: vec3<f32>,
@@ -102,6 +146,7 @@ const MINIMUM_HIT_DISTANCE: f32 = 0.05;
 const TRICUBIC_THRESHOLD: f32 = 0.1;
 
 const NO_HIT: RayHit = RayHit(false, vec3<f32>(0.0), vec3<f32>(0.0), vec3<f32>(0.0));
+// Used for the parts of the surface without any atom nearby.
 const SURFACE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.8, 0.8);
 
 fn ray_march(origin: vec3<f32>, direction: vec3<f32>) -> RayHit {
@@ -141,7 +186,7 @@ fn ray_march(origin: vec3<f32>, direction: vec3<f32>) -> RayHit {
             let point: vec3<f32> = current_position + distance * direction;
             let normal: vec3<f32> = normalize(compute_gradient(point));
 
-            let color = vec3<f32>(1.0);
+            let color: vec3<f32> = surface_color(point);
             let ambient: f32 = 0.15;
 
             let light_dir: vec3<f32> = normalize(light.direction);
@@ -150,7 +195,7 @@ fn ray_march(origin: vec3<f32>, direction: vec3<f32>) -> RayHit {
             let reflect_dir: vec3<f32> = reflect(light_dir, normal);  
             let specular: f32 = pow(max(dot(direction, reflect_dir), 0.0), 16.0) * 0.3;
 
-            let color_shaded = color * (ambient + specular + diffuse) * SURFACE_COLOR;
+            let color_shaded = color * (ambient + specular + diffuse);
             let normal_view: vec3<f32> = normalize((camera.view * vec4<f32>(normal, 0.0)).xyz);
 
             return RayHit(true, point, color_shaded, normal_view);